@@ -1,5 +1,11 @@
+mod budget;
 mod orchestrator;
 mod runner;
+mod settings;
+mod usage;
 
-pub use orchestrator::{OrchestratorConfig, PlanManager};
+pub use budget::{BudgetLimits, Spend, UsageLedger};
+pub use orchestrator::{AgentRole, OrchestratorConfig, PlanManager};
 pub use runner::{AgentConfig, AgentRunner, AgentStatus};
+pub use settings::write_settings;
+pub use usage::{format_tokens, total_usage, usage_by_agent, TokenUsage, UsageRecord};
@@ -1,12 +1,15 @@
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 use anyhow::{Context, Result};
 use serde::{Deserialize, Serialize};
 
-/// Agent role (for future config-based role selection)
+use super::budget::{BudgetConfig, BudgetLimits};
+use super::settings::AgentSettings;
+use super::usage::{Pricing, UsageFormat};
+
+/// Agent role
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "lowercase")]
-#[allow(dead_code)]
 pub enum AgentRole {
     /// Planning (Gemini/Codex)
     Planner,
@@ -18,13 +21,51 @@ pub enum AgentRole {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AgentSpec {
     pub command: String,
+    #[serde(default)]
     pub args: Vec<String>,
     #[serde(default)]
     pub description: String,
+    /// Structured output format for usage accounting (defaults by agent name)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub usage_format: Option<UsageFormat>,
+    /// Token prices for cost estimation
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pricing: Option<Pricing>,
+    /// Caps applied to each run of this agent
+    #[serde(default)]
+    pub budget: BudgetLimits,
+    /// Arguments for the version probe (defaults to `--version`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version_args: Option<Vec<String>>,
+    /// Minimum supported version (reported as outdated below it)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min_version: Option<String>,
+    /// Config, instructions and MCP files written into task worktrees
+    #[serde(default)]
+    pub settings: AgentSettings,
+}
+
+impl AgentSpec {
+    /// Arguments for the version probe
+    pub fn version_args(&self) -> Vec<String> {
+        self.version_args
+            .clone()
+            .unwrap_or_else(|| vec!["--version".into()])
+    }
 }
 
-/// Orchestrator configuration
+/// Default Claude Code arguments. Stream-json output reports usage while the run goes on,
+/// so token and cost budgets can stop it early (it requires `--verbose` with `-p`).
+pub(super) fn claude_args() -> Vec<String> {
+    ["-p", "--dangerously-skip-permissions", "--output-format", "stream-json", "--verbose"]
+        .into_iter()
+        .map(String::from)
+        .collect()
+}
+
+/// Orchestrator configuration (fields missing from config.json keep their defaults)
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct OrchestratorConfig {
     /// Default planner
     pub default_planner: String,
@@ -34,6 +75,18 @@ pub struct OrchestratorConfig {
     pub planners: std::collections::HashMap<String, AgentSpec>,
     /// Available executors
     pub executors: std::collections::HashMap<String, AgentSpec>,
+    /// Per-task and daily budget caps
+    pub budget: BudgetConfig,
+}
+
+impl AgentRole {
+    /// Get display name for the role
+    pub fn display_name(&self) -> &'static str {
+        match self {
+            Self::Planner => "planner",
+            Self::Executor => "executor",
+        }
+    }
 }
 
 impl Default for OrchestratorConfig {
@@ -45,6 +98,12 @@ impl Default for OrchestratorConfig {
                 command: "gemini".into(),
                 args: vec!["-y".into()],
                 description: "Fast and cheap. Best for general tasks".into(),
+                usage_format: None,
+                pricing: None,
+                budget: BudgetLimits::default(),
+                version_args: None,
+                min_version: None,
+                settings: AgentSettings::default(),
             },
         );
         planners.insert(
@@ -53,6 +112,12 @@ impl Default for OrchestratorConfig {
                 command: "codex".into(),
                 args: vec![],
                 description: "Strong reasoning. For complex architecture design".into(),
+                usage_format: None,
+                pricing: None,
+                budget: BudgetLimits::default(),
+                version_args: None,
+                min_version: None,
+                settings: AgentSettings::default(),
             },
         );
 
@@ -61,8 +126,14 @@ impl Default for OrchestratorConfig {
             "claude".into(),
             AgentSpec {
                 command: "claude".into(),
-                args: vec!["-p".into(), "--dangerously-skip-permissions".into()],
+                args: claude_args(),
                 description: "High code quality. Best for implementation".into(),
+                usage_format: None,
+                pricing: None,
+                budget: BudgetLimits::default(),
+                version_args: None,
+                min_version: None,
+                settings: AgentSettings::default(),
             },
         );
 
@@ -71,13 +142,14 @@ impl Default for OrchestratorConfig {
             default_executor: "claude".into(),
             planners,
             executors,
+            budget: BudgetConfig::default(),
         }
     }
 }
 
 impl OrchestratorConfig {
     /// Load from config file
-    pub fn load(hive_dir: &PathBuf) -> Result<Self> {
+    pub fn load(hive_dir: &Path) -> Result<Self> {
         let config_path = hive_dir.join("config.json");
         if config_path.exists() {
             let content = std::fs::read_to_string(&config_path)
@@ -115,6 +187,11 @@ impl OrchestratorConfig {
     pub fn get_executor(&self, name: &str) -> Option<&AgentSpec> {
         self.executors.get(name)
     }
+
+    /// Find agent spec by name (planners first, then executors)
+    pub fn get_agent(&self, name: &str) -> Option<&AgentSpec> {
+        self.planners.get(name).or_else(|| self.executors.get(name))
+    }
 }
 
 /// Plan file manager
@@ -126,9 +203,16 @@ impl PlanManager {
     pub fn new(hive_dir: PathBuf) -> Self {
         let plans_dir = hive_dir.join("plans");
         std::fs::create_dir_all(&plans_dir).ok();
+        // Absolute, so agents find plans from any worktree
+        let plans_dir = std::fs::canonicalize(&plans_dir).unwrap_or(plans_dir);
         Self { plans_dir }
     }
 
+    /// Directory holding plan files
+    pub fn plans_dir(&self) -> &Path {
+        &self.plans_dir
+    }
+
     /// Get plan file path
     pub fn plan_path(&self, task_id: &str) -> PathBuf {
         self.plans_dir.join(format!("{}.md", task_id))
@@ -146,6 +230,25 @@ impl PlanManager {
             .context(format!("Failed to read plan: {}", path.display()))
     }
 
+    /// Extract the "## Overview" section of the plan
+    pub fn plan_overview(&self, task_id: &str) -> Option<String> {
+        self.plan_section(task_id, "Overview")
+    }
+
+    /// Extract a "## <heading>" section of the plan (None when missing or empty)
+    pub fn plan_section(&self, task_id: &str, heading: &str) -> Option<String> {
+        let plan = self.load_plan(task_id).ok()?;
+        let marker = format!("## {}", heading);
+        let mut lines = plan
+            .lines()
+            .skip_while(|line| line.trim() != marker)
+            .skip(1)
+            .take_while(|line| !line.starts_with("## "));
+        let section = lines.by_ref().collect::<Vec<_>>().join("\n");
+        let section = section.trim();
+        (!section.is_empty()).then(|| section.to_string())
+    }
+
     /// Save plan content
     #[allow(dead_code)]
     pub fn save_plan(&self, task_id: &str, content: &str) -> Result<()> {
@@ -161,7 +264,7 @@ impl PlanManager {
         task_title: &str,
         task_description: &str,
     ) -> String {
-        let plan_file = format!("../../plans/{}.md", task_id);
+        let plan_file = self.plan_path(task_id).display().to_string();
         format!(
             r#"Please create an implementation plan for the following task.
 
@@ -209,6 +312,138 @@ Remember: You MUST write the plan to `{}` for the workflow to continue.
         )
     }
 
+    /// Create conflict resolution prompt (task intent from the plan, base intent from incoming commits)
+    pub fn create_conflict_prompt(
+        &self,
+        task_id: &str,
+        base_branch: &str,
+        files: &[String],
+        incoming_commits: &[String],
+    ) -> String {
+        let plan = self
+            .load_plan(task_id)
+            .unwrap_or_else(|_| "(No plan available)".into());
+        let files = files
+            .iter()
+            .map(|f| format!("- `{}`", f))
+            .collect::<Vec<_>>()
+            .join("\n");
+        let commits = if incoming_commits.is_empty() {
+            "(No commit information)".to_string()
+        } else {
+            incoming_commits
+                .iter()
+                .map(|c| format!("- {}", c))
+                .collect::<Vec<_>>()
+                .join("\n")
+        };
+        format!(
+            r#"Merging `{base}` into this task branch produced conflicts. Please resolve them.
+
+## Conflicted Files
+{files}
+
+## Intent of This Task (implementation plan)
+{plan}
+
+## Intent of `{base}` (incoming commits)
+{commits}
+
+## Instructions
+1. Resolve every conflict so that both the task's intent and the incoming changes are preserved
+2. Remove all conflict markers (`<<<<<<<`, `=======`, `>>>>>>>`)
+3. Make sure the code builds and tests pass
+4. Do not commit; the merge is concluded after verification
+"#,
+            base = base_branch,
+            files = files,
+            plan = plan,
+            commits = commits
+        )
+    }
+
+    /// Create prompt asking an agent to summarize a diff into a commit message
+    pub fn create_commit_message_prompt(&self, task_title: &str, diff: &str) -> String {
+        const MAX_DIFF_CHARS: usize = 20_000;
+        let diff = match diff.char_indices().nth(MAX_DIFF_CHARS) {
+            Some((end, _)) => format!("{}\n... (diff truncated)", &diff[..end]),
+            None => diff.to_string(),
+        };
+        format!(
+            r#"Write a git commit message for the following changes made for the task "{}".
+
+Reply with the commit message only: a summary line of at most 72 characters, a blank line, then a short body.
+Do not modify any files.
+
+```diff
+{}
+```
+"#,
+            task_title, diff
+        )
+    }
+
+    /// Create prompt asking the executor to address review comments (one section per comment)
+    pub fn create_review_prompt(&self, task_id: &str, comments: &[String]) -> String {
+        let plan = self
+            .load_plan(task_id)
+            .unwrap_or_else(|_| "(No plan available)".into());
+        format!(
+            r#"A reviewer requested changes to your implementation. Please address every comment below.
+
+## Review Comments
+{comments}
+
+## Implementation Plan
+{plan}
+
+## Instructions
+1. Address each comment at the file and line it refers to (line numbers are from the reviewed diff)
+2. Keep the rest of the implementation as it is unless a comment requires otherwise
+3. Make sure the code builds and tests pass
+"#,
+            comments = comments.join("\n\n"),
+            plan = plan
+        )
+    }
+
+    /// Create prompt asking the executor for a follow-up change on top of its implementation
+    pub fn create_follow_up_prompt(&self, task_id: &str, instruction: &str, earlier: &[&str]) -> String {
+        let plan = self
+            .load_plan(task_id)
+            .unwrap_or_else(|_| "(No plan available)".into());
+        let earlier = if earlier.is_empty() {
+            String::new()
+        } else {
+            format!(
+                "\n## Earlier Follow-ups (already addressed)\n{}\n",
+                earlier
+                    .iter()
+                    .map(|f| format!("- {}", f))
+                    .collect::<Vec<_>>()
+                    .join("\n")
+            )
+        };
+        format!(
+            r#"The implementation plan below has already been implemented in this worktree. Please make the following follow-up change.
+
+## Follow-up Instruction
+{instruction}
+
+## Implementation Plan
+{plan}
+{earlier}
+## Instructions
+1. Build on the existing changes; do not start over or re-plan
+2. Keep the change focused on the follow-up instruction
+3. Make sure the code builds and tests pass
+"#,
+            instruction = instruction,
+            plan = plan,
+            earlier = earlier
+        )
+    }
+
     /// Create execution prompt with plan
     pub fn create_execution_prompt(&self, task_id: &str) -> Result<String> {
         let plan = self.load_plan(task_id)?;
@@ -309,7 +544,7 @@ mod tests {
     #[test]
     fn test_load_returns_default_when_no_config() {
         let temp_dir = TempDir::new().unwrap();
-        let config = OrchestratorConfig::load(&temp_dir.path().to_path_buf()).unwrap();
+        let config = OrchestratorConfig::load(temp_dir.path()).unwrap();
 
         // Should return default config
         assert_eq!(config.default_planner, "gemini");
@@ -344,13 +579,127 @@ mod tests {
         }"#;
         std::fs::write(&config_path, config_json).unwrap();
 
-        let config = OrchestratorConfig::load(&temp_dir.path().to_path_buf()).unwrap();
+        let config = OrchestratorConfig::load(temp_dir.path()).unwrap();
         assert_eq!(config.default_planner, "codex");
 
         let codex = config.get_planner("codex").unwrap();
         assert!(codex.args.contains(&"--model".to_string()));
     }
 
+    #[test]
+    fn test_load_budget_from_config_file() {
+        let temp_dir = TempDir::new().unwrap();
+        let config_json = r#"{
+            "orchestrator": {
+                "default_planner": "gemini",
+                "default_executor": "claude",
+                "planners": {},
+                "executors": {
+                    "claude": {
+                        "command": "claude",
+                        "args": [],
+                        "budget": { "max_minutes": 20 },
+                        "pricing": { "input_per_mtok": 3.0, "output_per_mtok": 15.0 }
+                    }
+                },
+                "budget": { "daily": { "max_cost_usd": 10.0 } }
+            }
+        }"#;
+        std::fs::write(temp_dir.path().join("config.json"), config_json).unwrap();
+
+        let config = OrchestratorConfig::load(temp_dir.path()).unwrap();
+        assert_eq!(config.budget.daily.max_cost_usd, Some(10.0));
+        assert!(config.budget.per_task.is_unlimited());
+
+        let claude = config.get_executor("claude").unwrap();
+        assert_eq!(claude.budget.max_minutes, Some(20.0));
+        assert_eq!(claude.pricing.unwrap().output_per_mtok, 15.0);
+    }
+
+    #[test]
+    fn test_load_partial_config() {
+        let temp_dir = TempDir::new().unwrap();
+        let config_json = r#"{
+            "orchestrator": {
+                "executors": {
+                    "claude": { "command": "claude", "args": ["-p"], "usage_format": "claude" }
+                }
+            }
+        }"#;
+        std::fs::write(temp_dir.path().join("config.json"), config_json).unwrap();
+
+        // Missing fields keep their defaults
+        let config = OrchestratorConfig::load(temp_dir.path()).unwrap();
+        assert_eq!(config.default_planner, "gemini");
+        assert_eq!(config.default_executor, "claude");
+        assert!(config.get_planner("codex").is_some());
+        assert_eq!(config.get_executor("claude").unwrap().usage_format, Some(UsageFormat::Claude));
+    }
+
+    #[test]
+    fn test_load_budget_only_config() {
+        // Example from the README "Budgets" section
+        let temp_dir = TempDir::new().unwrap();
+        let config_json = r#"{
+            "orchestrator": {
+                "budget": {
+                    "per_task": { "max_cost_usd": 5.0, "max_minutes": 45 },
+                    "daily": { "max_cost_usd": 20.0 }
+                }
+            }
+        }"#;
+        std::fs::write(temp_dir.path().join("config.json"), config_json).unwrap();
+
+        let config = OrchestratorConfig::load(temp_dir.path()).unwrap();
+        assert_eq!(config.budget.per_task.max_cost_usd, Some(5.0));
+        assert_eq!(config.budget.per_task.max_minutes, Some(45.0));
+        assert_eq!(config.budget.daily.max_cost_usd, Some(20.0));
+        assert!(config.get_planner("gemini").is_some());
+        assert!(config.get_executor("claude").is_some());
+    }
+
+    #[test]
+    fn test_load_agent_settings_config() {
+        // Example from the README "Agent Settings" section
+        let temp_dir = TempDir::new().unwrap();
+        let config_json = r#"{
+            "orchestrator": {
+                "executors": {
+                    "claude": {
+                        "command": "claude",
+                        "args": ["-p", "--dangerously-skip-permissions"],
+                        "settings": {
+                            "instructions": "Run `cargo test` before finishing.",
+                            "mcp_servers": { "docs": { "command": "docs-mcp", "args": ["--stdio"] } }
+                        }
+                    },
+                    "aider": {
+                        "command": "aider",
+                        "args": ["--yes-always", "--message"],
+                        "settings": { "files": { "CONVENTIONS.md": "Follow the existing error handling." } }
+                    }
+                }
+            }
+        }"#;
+        std::fs::write(temp_dir.path().join("config.json"), config_json).unwrap();
+
+        let config = OrchestratorConfig::load(temp_dir.path()).unwrap();
+        let claude = config.get_executor("claude").unwrap();
+        assert!(claude.settings.instructions.is_some());
+        assert!(claude.settings.mcp_servers.contains_key("docs"));
+        assert!(config.get_executor("aider").unwrap().settings.files.contains_key("CONVENTIONS.md"));
+        assert_eq!(config.default_planner, "gemini");
+        assert!(config.get_planner("gemini").is_some());
+    }
+
+    #[test]
+    fn test_load_invalid_config_is_an_error() {
+        let temp_dir = TempDir::new().unwrap();
+        let config_json = r#"{ "orchestrator": { "executors": { "claude": { "args": [] } } } }"#;
+        std::fs::write(temp_dir.path().join("config.json"), config_json).unwrap();
+        assert!(OrchestratorConfig::load(temp_dir.path()).is_err());
+    }
+
     // ========================================
     // PlanManager Tests
     // ========================================
@@ -426,7 +775,9 @@ mod tests {
         assert!(prompt.contains("## Scope of Impact"));
         assert!(prompt.contains("## Test Strategy"));
         // Verify plan file path is included
-        assert!(prompt.contains("../../plans/task-123.md"));
+        let plan_file = manager.plan_path("task-123");
+        assert!(plan_file.is_absolute());
+        assert!(prompt.contains(&*plan_file.to_string_lossy()));
     }
 
     #[test]
@@ -454,6 +805,99 @@ mod tests {
         assert!(result.is_err());
     }
 
+    #[test]
+    fn test_plan_overview() {
+        let temp_dir = TempDir::new().unwrap();
+        let manager = PlanManager::new(temp_dir.path().to_path_buf());
+        manager
+            .save_plan(
+                "task-overview",
+                "# Implementation Plan: X\n\n## Overview\nAdd caching\nto the API\n\n## Implementation Steps\n1. Step",
+            )
+            .unwrap();
+
+        assert_eq!(
+            manager.plan_overview("task-overview").as_deref(),
+            Some("Add caching\nto the API")
+        );
+        assert_eq!(
+            manager.plan_section("task-overview", "Implementation Steps").as_deref(),
+            Some("1. Step")
+        );
+        assert!(manager.plan_overview("missing").is_none());
+
+        manager.save_plan("task-no-overview", "# Plan\n## Steps").unwrap();
+        assert!(manager.plan_overview("task-no-overview").is_none());
+    }
+
+    #[test]
+    fn test_create_conflict_prompt() {
+        let temp_dir = TempDir::new().unwrap();
+        let manager = PlanManager::new(temp_dir.path().to_path_buf());
+        manager.save_plan("task-conflict", "# Plan\nAdd caching").unwrap();
+
+        let prompt = manager.create_conflict_prompt(
+            "task-conflict",
+            "main",
+            &["src/lib.rs".into()],
+            &["abc1234 Rename cache module".into()],
+        );
+
+        assert!(prompt.contains("- `src/lib.rs`"));
+        assert!(prompt.contains("Add caching"));
+        assert!(prompt.contains("abc1234 Rename cache module"));
+        assert!(prompt.contains("Merging `main`"));
+
+        // Missing plan does not prevent resolution
+        let prompt = manager.create_conflict_prompt("no-plan", "main", &[], &[]);
+        assert!(prompt.contains("No plan available"));
+    }
+
+    #[test]
+    fn test_create_review_prompt() {
+        let temp_dir = TempDir::new().unwrap();
+        let manager = PlanManager::new(temp_dir.path().to_path_buf());
+        manager.save_plan("task-review", "# Plan\nAdd caching").unwrap();
+
+        let prompt = manager.create_review_prompt(
+            "task-review",
+            &["### `src/lib.rs:3`\nUse a constant".into(), "### `src/main.rs:9`\nHandle errors".into()],
+        );
+        assert!(prompt.contains("### `src/lib.rs:3`\nUse a constant\n\n### `src/main.rs:9`"));
+        assert!(prompt.contains("Add caching"));
+
+        let prompt = manager.create_review_prompt("no-plan", &[]);
+        assert!(prompt.contains("No plan available"));
+    }
+
+    #[test]
+    fn test_create_follow_up_prompt() {
+        let temp_dir = TempDir::new().unwrap();
+        let manager = PlanManager::new(temp_dir.path().to_path_buf());
+        manager.save_plan("task-follow", "# Plan\nAdd caching").unwrap();
+
+        let prompt = manager.create_follow_up_prompt("task-follow", "Also test the empty case", &[]);
+        assert!(prompt.contains("## Follow-up Instruction\nAlso test the empty case"));
+        assert!(prompt.contains("Add caching"));
+        assert!(!prompt.contains("Earlier Follow-ups"));
+
+        let prompt = manager.create_follow_up_prompt("task-follow", "Update docs", &["Add a test"]);
+        assert!(prompt.contains("## Earlier Follow-ups (already addressed)\n- Add a test"));
+    }
+
+    #[test]
+    fn test_create_commit_message_prompt() {
+        let temp_dir = TempDir::new().unwrap();
+        let manager = PlanManager::new(temp_dir.path().to_path_buf());
+
+        let prompt = manager.create_commit_message_prompt("Add caching", "+cache");
+        assert!(prompt.contains("\"Add caching\""));
+        assert!(prompt.contains("```diff\n+cache\n```"));
+
+        let prompt = manager.create_commit_message_prompt("Big", &"x".repeat(30_000));
+        assert!(prompt.contains("(diff truncated)"));
+    }
+
     // ========================================
     // AgentRole Tests
     // ========================================
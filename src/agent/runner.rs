@@ -1,18 +1,34 @@
 use std::collections::HashMap;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::process::Stdio;
 
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
 use anyhow::{Context, Result};
 use tokio::io::{AsyncBufReadExt, BufReader};
 use tokio::process::{Child, Command};
 use tokio::sync::mpsc;
 
+use super::budget::{BudgetLimits, Spend};
+use super::orchestrator::{claude_args, AgentSpec};
+use super::settings::AgentSettings;
+use super::usage::{Pricing, TokenUsage, UsageFormat};
+
 /// Agent configuration
 #[derive(Debug, Clone)]
 pub struct AgentConfig {
     pub name: String,
     pub command: String,
     pub args: Vec<String>,
+    /// Structured output format used for usage accounting
+    pub usage_format: UsageFormat,
+    /// Token prices for estimating cost when the agent does not report it
+    pub pricing: Option<Pricing>,
+    /// Extra environment variables for the agent process
+    pub env: Vec<(String, String)>,
+    /// Files written into the agent's worktree
+    pub settings: AgentSettings,
 }
 
 impl AgentConfig {
@@ -21,7 +37,11 @@ impl AgentConfig {
         Self {
             name: "claude".into(),
             command: "claude".into(),
-            args: vec!["-p".into(), "--dangerously-skip-permissions".into()],
+            args: claude_args(),
+            usage_format: UsageFormat::Claude,
+            pricing: None,
+            env: Vec::new(),
+            settings: AgentSettings::default(),
         }
     }
 
@@ -31,6 +51,10 @@ impl AgentConfig {
             name: "gemini".into(),
             command: "gemini".into(),
             args: vec!["-y".into()],
+            usage_format: UsageFormat::Gemini,
+            pricing: None,
+            env: Vec::new(),
+            settings: AgentSettings::default(),
         }
     }
 
@@ -40,6 +64,10 @@ impl AgentConfig {
             name: "codex".into(),
             command: "codex".into(),
             args: vec![],
+            usage_format: UsageFormat::Codex,
+            pricing: None,
+            env: Vec::new(),
+            settings: AgentSettings::default(),
         }
     }
 
@@ -53,6 +81,21 @@ impl AgentConfig {
         }
     }
 
+    /// Build configuration from an orchestrator agent spec
+    pub fn from_spec(name: &str, spec: &AgentSpec) -> Self {
+        Self {
+            name: name.into(),
+            command: spec.command.clone(),
+            args: spec.args.clone(),
+            usage_format: spec
+                .usage_format
+                .unwrap_or_else(|| UsageFormat::for_agent(name)),
+            pricing: spec.pricing,
+            env: Vec::new(),
+            settings: spec.settings.clone(),
+        }
+    }
+
     /// Get list of available agents
     #[allow(dead_code)]
     pub fn available_agents() -> Vec<&'static str> {
@@ -68,6 +111,8 @@ pub enum AgentStatus {
     Running,
     Completed,
     Failed(String),
+    /// Stopped because a budget cap was reached
+    BudgetExceeded(String),
 }
 
 /// Running agent information
@@ -77,6 +122,13 @@ pub struct RunningAgent {
     pub config: AgentConfig,
     pub status: AgentStatus,
     pub output_lines: Vec<String>,
+    /// Usage accumulated from structured output
+    usage: Arc<Mutex<TokenUsage>>,
+    /// Remaining budget for this run
+    limits: BudgetLimits,
+    started_at: Instant,
+    /// Process exit code (after completion)
+    exit_code: Option<i32>,
     child: Option<Child>,
 }
 
@@ -84,8 +136,6 @@ pub struct RunningAgent {
 pub struct AgentRunner {
     /// Running agents (task_id -> RunningAgent)
     pub agents: HashMap<String, RunningAgent>,
-    /// Log directory
-    log_dir: PathBuf,
 }
 
 impl AgentRunner {
@@ -95,10 +145,17 @@ impl AgentRunner {
 
         Self {
             agents: HashMap::new(),
-            log_dir,
         }
     }
 
+    /// Get log file path for a run
+    pub fn log_path(hive_dir: &Path, task_id: &str, run_id: &str) -> PathBuf {
+        hive_dir
+            .join("logs")
+            .join(task_id)
+            .join(format!("{}.log", run_id))
+    }
+
     /// Start agent
     pub async fn start(
         &mut self,
@@ -106,6 +163,8 @@ impl AgentRunner {
         config: AgentConfig,
         working_dir: PathBuf,
         prompt: &str,
+        limits: BudgetLimits,
+        log_path: PathBuf,
     ) -> Result<mpsc::Receiver<String>> {
         // Channel to receive output
         let (tx, rx) = mpsc::channel::<String>(100);
@@ -118,16 +177,24 @@ impl AgentRunner {
         let mut child = Command::new(&config.command)
             .args(&args)
             .current_dir(&working_dir)
+            .envs(config.env.iter().cloned())
             .stdout(Stdio::piped())
             .stderr(Stdio::piped())
             .spawn()
             .context(format!("Failed to start {}", config.name))?;
 
+        let usage = Arc::new(Mutex::new(TokenUsage::default()));
+
         // Read stdout asynchronously
         if let Some(stdout) = child.stdout.take() {
             let tx_clone = tx.clone();
             let _task_id_clone = task_id.to_string();
-            let log_path = self.log_dir.join(format!("{}.log", task_id));
+            if let Some(parent) = log_path.parent() {
+                std::fs::create_dir_all(parent).ok();
+            }
+            let mut parser = config.usage_format.parser();
+            let pricing = config.pricing;
+            let usage = Arc::clone(&usage);
 
             tokio::spawn(async move {
                 let reader = BufReader::new(stdout);
@@ -139,15 +206,38 @@ impl AgentRunner {
                     .await
                     .ok();
 
+                // Cost reported by the agent, and estimated from pricing since its last report
+                let (mut reported, mut estimated) = (0.0, 0.0);
                 while let Ok(Some(line)) = lines.next_line().await {
                     // Write to log file
                     if let Some(ref mut file) = log_file {
                         use tokio::io::AsyncWriteExt;
                         let _ = file.write_all(format!("{}\n", line).as_bytes()).await;
                     }
-                    // Send to channel
-                    if tx_clone.send(line).await.is_err() {
-                        break;
+                    // Accumulate usage
+                    if let Some(mut run_usage) = parser.as_mut().and_then(|p| p.parse_line(&line)) {
+                        let cost = std::mem::take(&mut run_usage.cost_usd);
+                        if cost > 0.0 {
+                            // Reported cost replaces the estimates made so far
+                            reported += cost;
+                            estimated = 0.0;
+                        } else if let Some(pricing) = pricing {
+                            estimated += pricing.estimate(&run_usage);
+                        }
+                        if let Ok(mut total) = usage.lock() {
+                            total.add(&run_usage);
+                            total.cost_usd = reported + estimated;
+                        }
+                    }
+                    // Send the readable part to the channel
+                    let line = match &parser {
+                        Some(parser) => parser.display_line(&line),
+                        None => Some(line),
+                    };
+                    if let Some(line) = line {
+                        if tx_clone.send(line).await.is_err() {
+                            break;
+                        }
                     }
                 }
                 drop(tx_clone);
@@ -172,6 +262,10 @@ impl AgentRunner {
             config,
             status: AgentStatus::Running,
             output_lines: Vec::new(),
+            usage,
+            limits,
+            started_at: Instant::now(),
+            exit_code: None,
             child: Some(child),
         };
         self.agents.insert(task_id.to_string(), running);
@@ -179,6 +273,41 @@ impl AgentRunner {
         Ok(rx)
     }
 
+    /// Run an agent to completion and return its trimmed stdout (short one-off prompts)
+    pub async fn run_once(
+        config: &AgentConfig,
+        working_dir: &Path,
+        prompt: &str,
+        timeout: Duration,
+    ) -> Result<String> {
+        let mut args = config.args.clone();
+        args.push(prompt.to_string());
+
+        let output = tokio::time::timeout(
+            timeout,
+            Command::new(&config.command)
+                .args(&args)
+                .current_dir(working_dir)
+                .envs(config.env.iter().cloned())
+                .stdin(Stdio::null())
+                .kill_on_drop(true)
+                .output(),
+        )
+        .await
+        .map_err(|_| anyhow::anyhow!("{} timed out after {}s", config.name, timeout.as_secs()))?
+        .context(format!("Failed to start {}", config.name))?;
+
+        if !output.status.success() {
+            anyhow::bail!(
+                "{} exited with {}: {}",
+                config.name,
+                output.status,
+                String::from_utf8_lossy(&output.stderr).trim()
+            );
+        }
+        Ok(config.usage_format.reply(&String::from_utf8_lossy(&output.stdout)))
+    }
+
     /// Stop agent
     pub async fn stop(&mut self, task_id: &str) -> Result<()> {
         if let Some(agent) = self.agents.get_mut(task_id) {
@@ -196,6 +325,47 @@ impl AgentRunner {
         self.agents.get(task_id).map(|a| &a.status)
     }
 
+    /// Get usage accumulated so far for a task's agent
+    pub fn get_usage(&self, task_id: &str) -> Option<TokenUsage> {
+        self.agents
+            .get(task_id)
+            .and_then(|a| a.usage.lock().ok().map(|u| *u))
+    }
+
+    /// Get process exit code of a task's finished agent
+    pub fn get_exit_code(&self, task_id: &str) -> Option<i32> {
+        self.agents.get(task_id).and_then(|a| a.exit_code)
+    }
+
+    /// Get elapsed wall-clock time of a task's agent
+    pub fn elapsed_secs(&self, task_id: &str) -> Option<u64> {
+        self.agents
+            .get(task_id)
+            .map(|a| a.started_at.elapsed().as_secs())
+    }
+
+    /// Check running agent against its budget. Returns the reason if exceeded.
+    pub fn check_budget(&self, task_id: &str) -> Option<String> {
+        let agent = self.agents.get(task_id)?;
+        if agent.status != AgentStatus::Running || agent.limits.is_unlimited() {
+            return None;
+        }
+        let spent = Spend {
+            usage: agent.usage.lock().map(|u| *u).unwrap_or_default(),
+            secs: agent.started_at.elapsed().as_secs(),
+        };
+        agent.limits.exceeded(&spent)
+    }
+
+    /// Detach the process of a running agent and mark it with `status`.
+    /// The caller is responsible for terminating the returned process.
+    pub fn halt(&mut self, task_id: &str, status: AgentStatus) -> Option<Child> {
+        let agent = self.agents.get_mut(task_id)?;
+        let child = agent.child.take()?;
+        agent.status = status;
+        Some(child)
+    }
+
     /// Get count of running agents
     pub fn running_count(&self) -> usize {
         self.agents
@@ -204,6 +374,23 @@ impl AgentRunner {
             .count()
     }
 
+    /// Terminate process gracefully (SIGTERM, then kill after a grace period)
+    pub async fn terminate_gracefully(mut child: Child, grace: Duration) -> Result<()> {
+        #[cfg(unix)]
+        if let Some(pid) = child.id() {
+            let _ = Command::new("kill")
+                .args(["-TERM", &pid.to_string()])
+                .status()
+                .await;
+            if tokio::time::timeout(grace, child.wait()).await.is_ok() {
+                return Ok(());
+            }
+        }
+        #[cfg(not(unix))]
+        let _ = grace;
+        child.kill().await.context("Failed to kill process")
+    }
+
     /// Check completion and update status
     #[allow(dead_code)]
     pub async fn check_completion(&mut self) {
@@ -212,6 +399,7 @@ impl AgentRunner {
                 if let Some(ref mut child) = agent.child {
                     match child.try_wait() {
                         Ok(Some(status)) => {
+                            agent.exit_code = status.code();
                             agent.status = if status.success() {
                                 AgentStatus::Completed
                             } else {
@@ -237,6 +425,7 @@ impl AgentRunner {
                 if let Some(ref mut child) = agent.child {
                     match child.try_wait() {
                         Ok(Some(status)) => {
+                            agent.exit_code = status.code();
                             agent.status = if status.success() {
                                 AgentStatus::Completed
                             } else {
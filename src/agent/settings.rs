@@ -0,0 +1,476 @@
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// What hive provides to an agent through files in its worktree
+pub struct SettingsContext<'a> {
+    /// Absolute path of the plans directory
+    pub plans_dir: &'a Path,
+    /// Extra instructions for the agent
+    pub instructions: Option<&'a str>,
+    /// MCP server definitions (name -> server)
+    pub mcp_servers: &'a BTreeMap<String, Value>,
+}
+
+/// Content hive merges into a worktree file
+#[derive(Debug, Clone, PartialEq)]
+pub enum SettingsContent {
+    /// JSON object merged into the file's object (other keys are kept)
+    Json(Value),
+    /// Text kept between hive markers (appended to Markdown, prepended otherwise)
+    Text(String),
+}
+
+/// Worktree file written for an agent
+#[derive(Debug, Clone, PartialEq)]
+pub struct SettingsFile {
+    /// Path relative to the worktree
+    pub path: String,
+    pub content: SettingsContent,
+}
+
+impl SettingsFile {
+    fn json(path: &str, value: Value) -> Self {
+        Self {
+            path: path.into(),
+            content: SettingsContent::Json(value),
+        }
+    }
+
+    fn text(path: &str, text: impl Into<String>) -> Self {
+        Self {
+            path: path.into(),
+            content: SettingsContent::Text(text.into()),
+        }
+    }
+}
+
+/// Produces the config, instructions and MCP files an agent reads from its worktree
+pub trait SettingsProvider: Send {
+    fn files(&self, context: &SettingsContext) -> Vec<SettingsFile>;
+}
+
+/// Claude Code: `.claude/settings.local.json`, `.mcp.json` and `CLAUDE.md`
+pub struct ClaudeSettings;
+
+impl SettingsProvider for ClaudeSettings {
+    fn files(&self, context: &SettingsContext) -> Vec<SettingsFile> {
+        let mut settings = serde_json::json!({ "plansDirectory": context.plans_dir });
+        if !context.mcp_servers.is_empty() {
+            settings["enableAllProjectMcpServers"] = Value::Bool(true);
+        }
+        let mut files = vec![SettingsFile::json(".claude/settings.local.json", settings)];
+        if !context.mcp_servers.is_empty() {
+            files.push(SettingsFile::json(
+                ".mcp.json",
+                serde_json::json!({ "mcpServers": context.mcp_servers }),
+            ));
+        }
+        if let Some(instructions) = context.instructions {
+            files.push(SettingsFile::text("CLAUDE.md", instructions));
+        }
+        files
+    }
+}
+
+/// Gemini CLI: `.gemini/settings.json` and `GEMINI.md`
+pub struct GeminiSettings;
+
+impl SettingsProvider for GeminiSettings {
+    fn files(&self, context: &SettingsContext) -> Vec<SettingsFile> {
+        // The plans directory is outside the worktree, so it has to be added to the workspace
+        let mut settings =
+            serde_json::json!({ "context": { "includeDirectories": [context.plans_dir] } });
+        if !context.mcp_servers.is_empty() {
+            settings["mcpServers"] = serde_json::json!(context.mcp_servers);
+        }
+        let mut files = vec![SettingsFile::json(".gemini/settings.json", settings)];
+        if let Some(instructions) = context.instructions {
+            files.push(SettingsFile::text("GEMINI.md", instructions));
+        }
+        files
+    }
+}
+
+/// Codex: `.codex/config.toml` and `AGENTS.md`
+pub struct CodexSettings;
+
+impl SettingsProvider for CodexSettings {
+    fn files(&self, context: &SettingsContext) -> Vec<SettingsFile> {
+        let plans_dir = Value::from(context.plans_dir.to_string_lossy());
+        let mut config = format!(
+            "sandbox_workspace_write.writable_roots = [{}]\n",
+            toml_value(&plans_dir)
+        );
+        for (name, server) in context.mcp_servers {
+            config.push_str(&format!(
+                "mcp_servers.{} = {}\n",
+                toml_value(&Value::from(name.as_str())),
+                toml_value(server)
+            ));
+        }
+        let mut files = vec![SettingsFile::text(".codex/config.toml", config.trim_end())];
+        if let Some(instructions) = context.instructions {
+            files.push(SettingsFile::text("AGENTS.md", instructions));
+        }
+        files
+    }
+}
+
+/// Render a JSON value as a TOML value (objects become inline tables, nulls empty strings)
+fn toml_value(value: &Value) -> String {
+    match value {
+        Value::Null => "\"\"".into(),
+        // JSON string escapes are valid TOML basic string escapes
+        Value::Bool(_) | Value::Number(_) | Value::String(_) => value.to_string(),
+        Value::Array(items) => {
+            let items: Vec<String> = items.iter().map(toml_value).collect();
+            format!("[{}]", items.join(", "))
+        }
+        Value::Object(map) => {
+            let entries: Vec<String> = map
+                .iter()
+                .map(|(key, value)| format!("{} = {}", Value::from(key.as_str()), toml_value(value)))
+                .collect();
+            format!("{{ {} }}", entries.join(", "))
+        }
+    }
+}
+
+/// Built-in settings provider
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ProviderKind {
+    /// No agent-specific files (only configured `files`)
+    #[default]
+    None,
+    Claude,
+    Gemini,
+    Codex,
+}
+
+impl ProviderKind {
+    /// Create provider for this kind
+    pub fn provider(self) -> Option<Box<dyn SettingsProvider>> {
+        match self {
+            Self::None => None,
+            Self::Claude => Some(Box::new(ClaudeSettings)),
+            Self::Gemini => Some(Box::new(GeminiSettings)),
+            Self::Codex => Some(Box::new(CodexSettings)),
+        }
+    }
+
+    /// Default provider for a built-in agent name
+    pub fn for_agent(name: &str) -> Self {
+        match name {
+            "claude" => Self::Claude,
+            "gemini" => Self::Gemini,
+            "codex" => Self::Codex,
+            _ => Self::None,
+        }
+    }
+}
+
+/// Worktree settings of an agent
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct AgentSettings {
+    /// Settings provider (defaults by agent name)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub provider: Option<ProviderKind>,
+    /// Extra instructions written into the agent's instructions file
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub instructions: Option<String>,
+    /// MCP servers (name -> server definition)
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub mcp_servers: BTreeMap<String, Value>,
+    /// Additional files (worktree path -> JSON object to merge, or text to keep in a marked block)
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub files: BTreeMap<String, Value>,
+}
+
+impl AgentSettings {
+    /// Files to write into a worktree for `agent`
+    pub fn files(&self, agent: &str, plans_dir: &Path) -> Vec<SettingsFile> {
+        let context = SettingsContext {
+            plans_dir,
+            instructions: self.instructions.as_deref(),
+            mcp_servers: &self.mcp_servers,
+        };
+        let provider = self.provider.unwrap_or_else(|| ProviderKind::for_agent(agent));
+        let mut files = provider
+            .provider()
+            .map(|provider| provider.files(&context))
+            .unwrap_or_default();
+        files.extend(self.files.iter().map(|(path, value)| match value {
+            Value::String(text) => SettingsFile::text(path, text.as_str()),
+            value => SettingsFile::json(path, value.clone()),
+        }));
+        files
+    }
+}
+
+/// Merge `overlay` into `base`: objects recursively, arrays without duplicates, other values replaced
+fn merge_json(base: &mut Value, overlay: Value) {
+    match (base, overlay) {
+        (Value::Object(base), Value::Object(overlay)) => {
+            for (key, value) in overlay {
+                match base.get_mut(&key) {
+                    Some(existing) => merge_json(existing, value),
+                    None => {
+                        base.insert(key, value);
+                    }
+                }
+            }
+        }
+        (Value::Array(base), Value::Array(overlay)) => {
+            for value in overlay {
+                if !base.contains(&value) {
+                    base.push(value);
+                }
+            }
+        }
+        (base, overlay) => *base = overlay,
+    }
+}
+
+/// Put `text` between hive markers in `existing`, replacing an earlier block
+fn merge_block(existing: &str, text: &str, markdown: bool) -> String {
+    let (start, end) = if markdown {
+        ("<!-- hive:start -->", "<!-- hive:end -->")
+    } else {
+        ("# hive:start", "# hive:end")
+    };
+    let block = format!("{}\n{}\n{}\n", start, text.trim_end(), end);
+
+    if let (Some(from), Some(to)) = (existing.find(start), existing.find(end)) {
+        if from < to {
+            let rest = &existing[to + end.len()..];
+            let rest = rest.strip_prefix('\n').unwrap_or(rest);
+            return format!("{}{}{}", &existing[..from], block, rest);
+        }
+    }
+    if existing.is_empty() {
+        block
+    } else if markdown {
+        let separator = if existing.ends_with('\n') { "\n" } else { "\n\n" };
+        format!("{}{}{}", existing, separator, block)
+    } else {
+        format!("{}\n{}", block, existing)
+    }
+}
+
+/// Write settings files into `worktree`, merging with existing files instead of
+/// replacing them. Returns the written paths (relative to the worktree).
+pub fn write_settings(worktree: &Path, files: &[SettingsFile]) -> Result<Vec<String>> {
+    let mut written = Vec::new();
+    for file in files {
+        let path = worktree.join(&file.path);
+        let existing = match std::fs::read_to_string(&path) {
+            Ok(content) => Some(content),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => None,
+            Err(e) => return Err(e).context(format!("Failed to read {}", file.path)),
+        };
+
+        let content = match &file.content {
+            SettingsContent::Json(overlay) => {
+                let mut value = match existing.as_deref().filter(|c| !c.trim().is_empty()) {
+                    Some(content) => serde_json::from_str(content)
+                        .context(format!("Failed to parse {}", file.path))?,
+                    None => Value::Object(Default::default()),
+                };
+                merge_json(&mut value, overlay.clone());
+                format!("{}\n", serde_json::to_string_pretty(&value)?)
+            }
+            SettingsContent::Text(text) => {
+                let markdown = file.path.ends_with(".md");
+                merge_block(existing.as_deref().unwrap_or_default(), text, markdown)
+            }
+        };
+
+        if existing.as_deref() != Some(content.as_str()) {
+            if let Some(parent) = path.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            std::fs::write(&path, content).context(format!("Failed to write {}", file.path))?;
+        }
+        written.push(file.path.clone());
+    }
+    Ok(written)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn servers() -> BTreeMap<String, Value> {
+        BTreeMap::from([(
+            "docs".to_string(),
+            serde_json::json!({ "command": "docs-mcp", "args": ["--port", 3000] }),
+        )])
+    }
+
+    // ========================================
+    // Provider Tests
+    // ========================================
+
+    #[test]
+    fn test_provider_for_agent() {
+        assert_eq!(ProviderKind::for_agent("claude"), ProviderKind::Claude);
+        assert_eq!(ProviderKind::for_agent("gemini"), ProviderKind::Gemini);
+        assert_eq!(ProviderKind::for_agent("codex"), ProviderKind::Codex);
+        assert_eq!(ProviderKind::for_agent("aider"), ProviderKind::None);
+        assert!(ProviderKind::None.provider().is_none());
+    }
+
+    #[test]
+    fn test_claude_files() {
+        let settings = AgentSettings {
+            instructions: Some("Run cargo test".into()),
+            mcp_servers: servers(),
+            ..Default::default()
+        };
+        let files = settings.files("claude", Path::new("/repo/.hive/plans"));
+        let paths: Vec<&str> = files.iter().map(|f| f.path.as_str()).collect();
+        assert_eq!(paths, vec![".claude/settings.local.json", ".mcp.json", "CLAUDE.md"]);
+        assert_eq!(
+            files[0].content,
+            SettingsContent::Json(serde_json::json!({
+                "plansDirectory": "/repo/.hive/plans",
+                "enableAllProjectMcpServers": true
+            }))
+        );
+        assert_eq!(
+            files[1].content,
+            SettingsContent::Json(serde_json::json!({ "mcpServers": servers() }))
+        );
+    }
+
+    #[test]
+    fn test_gemini_files_without_extras() {
+        let files = AgentSettings::default().files("gemini", Path::new("/plans"));
+        assert_eq!(
+            files,
+            vec![SettingsFile::json(
+                ".gemini/settings.json",
+                serde_json::json!({ "context": { "includeDirectories": ["/plans"] } })
+            )]
+        );
+    }
+
+    #[test]
+    fn test_codex_config_toml() {
+        let settings = AgentSettings {
+            mcp_servers: servers(),
+            ..Default::default()
+        };
+        let files = settings.files("codex", Path::new("/plans"));
+        assert_eq!(
+            files,
+            vec![SettingsFile::text(
+                ".codex/config.toml",
+                "sandbox_workspace_write.writable_roots = [\"/plans\"]\n\
+                 mcp_servers.\"docs\" = { \"args\" = [\"--port\", 3000], \"command\" = \"docs-mcp\" }"
+            )]
+        );
+    }
+
+    #[test]
+    fn test_custom_agent_files_and_provider_override() {
+        let settings = AgentSettings {
+            files: BTreeMap::from([
+                (".aider.conf.json".to_string(), serde_json::json!({ "auto-commits": false })),
+                ("CONVENTIONS.md".to_string(), Value::from("Keep functions small")),
+            ]),
+            ..Default::default()
+        };
+        let files = settings.files("aider", Path::new("/plans"));
+        assert_eq!(
+            files,
+            vec![
+                SettingsFile::json(".aider.conf.json", serde_json::json!({ "auto-commits": false })),
+                SettingsFile::text("CONVENTIONS.md", "Keep functions small"),
+            ]
+        );
+
+        let settings = AgentSettings {
+            provider: Some(ProviderKind::Claude),
+            ..Default::default()
+        };
+        assert_eq!(settings.files("my-claude", Path::new("/plans")).len(), 1);
+    }
+
+    // ========================================
+    // Write Tests
+    // ========================================
+
+    #[test]
+    fn test_write_merges_existing_json() {
+        let temp_dir = TempDir::new().unwrap();
+        let settings_path = temp_dir.path().join(".gemini/settings.json");
+        std::fs::create_dir_all(settings_path.parent().unwrap()).unwrap();
+        std::fs::write(
+            &settings_path,
+            r#"{"theme": "dark", "context": {"includeDirectories": ["../shared"]}}"#,
+        )
+        .unwrap();
+
+        let files = AgentSettings::default().files("gemini", Path::new("/plans"));
+        let written = write_settings(temp_dir.path(), &files).unwrap();
+        assert_eq!(written, vec![".gemini/settings.json"]);
+        // Writing again is idempotent
+        write_settings(temp_dir.path(), &files).unwrap();
+
+        let value: Value = serde_json::from_str(&std::fs::read_to_string(&settings_path).unwrap()).unwrap();
+        assert_eq!(
+            value,
+            serde_json::json!({
+                "theme": "dark",
+                "context": { "includeDirectories": ["../shared", "/plans"] }
+            })
+        );
+    }
+
+    #[test]
+    fn test_write_rejects_invalid_json() {
+        let temp_dir = TempDir::new().unwrap();
+        std::fs::create_dir_all(temp_dir.path().join(".claude")).unwrap();
+        std::fs::write(temp_dir.path().join(".claude/settings.local.json"), "{ oops").unwrap();
+
+        let files = AgentSettings::default().files("claude", Path::new("/plans"));
+        assert!(write_settings(temp_dir.path(), &files).is_err());
+        assert_eq!(
+            std::fs::read_to_string(temp_dir.path().join(".claude/settings.local.json")).unwrap(),
+            "{ oops"
+        );
+    }
+
+    #[test]
+    fn test_write_replaces_marked_block() {
+        let temp_dir = TempDir::new().unwrap();
+        std::fs::write(temp_dir.path().join("AGENTS.md"), "# Project rules\n").unwrap();
+
+        let mut settings = AgentSettings {
+            instructions: Some("First".into()),
+            ..Default::default()
+        };
+        write_settings(temp_dir.path(), &settings.files("codex", Path::new("/plans"))).unwrap();
+        settings.instructions = Some("Second".into());
+        write_settings(temp_dir.path(), &settings.files("codex", Path::new("/plans"))).unwrap();
+
+        assert_eq!(
+            std::fs::read_to_string(temp_dir.path().join("AGENTS.md")).unwrap(),
+            "# Project rules\n\n<!-- hive:start -->\nSecond\n<!-- hive:end -->\n"
+        );
+    }
+
+    #[test]
+    fn test_merge_block_prepends_to_config() {
+        let merged = merge_block("[profile]\nmodel = \"o3\"\n", "a = 1", false);
+        assert_eq!(merged, "# hive:start\na = 1\n# hive:end\n\n[profile]\nmodel = \"o3\"\n");
+        assert_eq!(merge_block(&merged, "a = 2", false), merged.replace("a = 1", "a = 2"));
+    }
+}
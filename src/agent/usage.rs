@@ -0,0 +1,523 @@
+use std::collections::{BTreeMap, HashMap};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// Token usage and cost for one or more agent runs
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct TokenUsage {
+    #[serde(default)]
+    pub input_tokens: u64,
+    #[serde(default)]
+    pub output_tokens: u64,
+    #[serde(default)]
+    pub cache_read_tokens: u64,
+    #[serde(default)]
+    pub cache_creation_tokens: u64,
+    /// Cost in USD (reported by the agent or estimated from pricing)
+    #[serde(default)]
+    pub cost_usd: f64,
+}
+
+impl TokenUsage {
+    /// Total tokens (input + output + cache)
+    pub fn total_tokens(&self) -> u64 {
+        self.input_tokens + self.output_tokens + self.cache_read_tokens + self.cache_creation_tokens
+    }
+
+    /// Check if nothing was recorded
+    pub fn is_empty(&self) -> bool {
+        self.total_tokens() == 0 && self.cost_usd == 0.0
+    }
+
+    /// Usage in `self` beyond `other` (per field, never negative)
+    pub fn since(&self, other: &TokenUsage) -> TokenUsage {
+        TokenUsage {
+            input_tokens: self.input_tokens.saturating_sub(other.input_tokens),
+            output_tokens: self.output_tokens.saturating_sub(other.output_tokens),
+            cache_read_tokens: self.cache_read_tokens.saturating_sub(other.cache_read_tokens),
+            cache_creation_tokens: self
+                .cache_creation_tokens
+                .saturating_sub(other.cache_creation_tokens),
+            cost_usd: (self.cost_usd - other.cost_usd).max(0.0),
+        }
+    }
+
+    /// Add another usage to this one
+    pub fn add(&mut self, other: &TokenUsage) {
+        self.input_tokens += other.input_tokens;
+        self.output_tokens += other.output_tokens;
+        self.cache_read_tokens += other.cache_read_tokens;
+        self.cache_creation_tokens += other.cache_creation_tokens;
+        self.cost_usd += other.cost_usd;
+    }
+
+    /// Short display string (e.g., "12.3k tok $0.42")
+    pub fn summary(&self) -> String {
+        format!("{} tok ${:.2}", format_tokens(self.total_tokens()), self.cost_usd)
+    }
+}
+
+/// Format token count in a compact form (e.g., 950, 12.3k, 1.2M)
+pub fn format_tokens(tokens: u64) -> String {
+    if tokens >= 1_000_000 {
+        format!("{:.1}M", tokens as f64 / 1_000_000.0)
+    } else if tokens >= 1_000 {
+        format!("{:.1}k", tokens as f64 / 1_000.0)
+    } else {
+        tokens.to_string()
+    }
+}
+
+/// Usage recorded for a single agent run
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UsageRecord {
+    /// Agent name (claude, gemini, ...)
+    pub agent: String,
+    /// Usage of the run
+    pub usage: TokenUsage,
+    /// Wall-clock duration of the run
+    #[serde(default)]
+    pub duration_secs: u64,
+    /// When the run finished
+    pub recorded_at: DateTime<Utc>,
+    /// Run this usage belongs to
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub run_id: Option<String>,
+}
+
+impl UsageRecord {
+    pub fn new(agent: impl Into<String>, usage: TokenUsage, duration_secs: u64) -> Self {
+        Self {
+            agent: agent.into(),
+            usage,
+            duration_secs,
+            recorded_at: Utc::now(),
+            run_id: None,
+        }
+    }
+}
+
+/// Token prices used to estimate cost when the agent does not report it
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct Pricing {
+    /// USD per million input tokens
+    #[serde(default)]
+    pub input_per_mtok: f64,
+    /// USD per million output tokens
+    #[serde(default)]
+    pub output_per_mtok: f64,
+    /// USD per million cached tokens (read and creation)
+    #[serde(default)]
+    pub cached_per_mtok: f64,
+}
+
+impl Pricing {
+    /// Estimate cost of usage
+    pub fn estimate(&self, usage: &TokenUsage) -> f64 {
+        let cached = usage.cache_read_tokens + usage.cache_creation_tokens;
+        (usage.input_tokens as f64 * self.input_per_mtok
+            + usage.output_tokens as f64 * self.output_per_mtok
+            + cached as f64 * self.cached_per_mtok)
+            / 1_000_000.0
+    }
+}
+
+/// Sum usage records
+pub fn total_usage<'a>(records: impl IntoIterator<Item = &'a UsageRecord>) -> TokenUsage {
+    let mut total = TokenUsage::default();
+    for record in records {
+        total.add(&record.usage);
+    }
+    total
+}
+
+/// Sum usage records grouped by agent name
+pub fn usage_by_agent<'a>(
+    records: impl IntoIterator<Item = &'a UsageRecord>,
+) -> BTreeMap<String, TokenUsage> {
+    let mut by_agent: BTreeMap<String, TokenUsage> = BTreeMap::new();
+    for record in records {
+        by_agent
+            .entry(record.agent.clone())
+            .or_default()
+            .add(&record.usage);
+    }
+    by_agent
+}
+
+/// Structured output format emitted by an agent
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum UsageFormat {
+    /// No usage parsing
+    #[default]
+    None,
+    /// Claude Code `--output-format stream-json` assistant messages and result record
+    Claude,
+    /// Codex `exec --json` turn.completed events
+    Codex,
+    /// Gemini CLI `--output-format json` stats
+    Gemini,
+}
+
+impl UsageFormat {
+    /// Create parser for this format
+    pub fn parser(self) -> Option<Box<dyn UsageParser>> {
+        match self {
+            Self::None => None,
+            Self::Claude => Some(Box::new(ClaudeUsageParser::default())),
+            Self::Codex => Some(Box::new(CodexUsageParser)),
+            Self::Gemini => Some(Box::new(GeminiUsageParser)),
+        }
+    }
+
+    /// Reply text of a one-off run's stdout (the result record of structured Claude output)
+    pub fn reply(self, stdout: &str) -> String {
+        let result = match self {
+            Self::Claude => stdout.lines().rev().find_map(|line| {
+                let value = parse_json(line)?;
+                (value.get("type")?.as_str()? == "result")
+                    .then(|| value.get("result")?.as_str().map(str::to_string))
+                    .flatten()
+            }),
+            _ => None,
+        };
+        result.unwrap_or_else(|| stdout.to_string()).trim().to_string()
+    }
+
+    /// Default format for a built-in agent name
+    pub fn for_agent(name: &str) -> Self {
+        match name {
+            "claude" => Self::Claude,
+            "codex" => Self::Codex,
+            "gemini" => Self::Gemini,
+            _ => Self::None,
+        }
+    }
+}
+
+/// Extracts usage from agent output lines
+pub trait UsageParser: Send {
+    /// Parse one stdout line. Returns usage if the line carries a usage record.
+    /// Returned usage is added to the run total.
+    fn parse_line(&mut self, line: &str) -> Option<TokenUsage>;
+
+    /// Readable form of a stdout line for the live log (`None` hides it)
+    fn display_line(&self, line: &str) -> Option<String> {
+        Some(line.to_string())
+    }
+}
+
+fn parse_json(line: &str) -> Option<serde_json::Value> {
+    let trimmed = line.trim();
+    if !trimmed.starts_with('{') {
+        return None;
+    }
+    serde_json::from_str(trimmed).ok()
+}
+
+fn get_u64(value: &serde_json::Value, key: &str) -> u64 {
+    value.get(key).and_then(|v| v.as_u64()).unwrap_or(0)
+}
+
+/// Parser for Claude Code stream-json output. Usage is counted as assistant messages
+/// arrive, so budgets apply during the run; the final result record adds whatever the
+/// messages did not report (at least the cost).
+/// `{"type":"assistant","message":{"id":"msg_1","usage":{"input_tokens":..}}}`
+/// `{"type":"result","total_cost_usd":0.12,"usage":{"input_tokens":..}}`
+#[derive(Default)]
+pub struct ClaudeUsageParser {
+    /// Usage counted per message ID (events of one message repeat its usage)
+    messages: HashMap<String, TokenUsage>,
+    /// Usage counted so far
+    counted: TokenUsage,
+}
+
+fn claude_usage(usage: &serde_json::Value) -> TokenUsage {
+    TokenUsage {
+        input_tokens: get_u64(usage, "input_tokens"),
+        output_tokens: get_u64(usage, "output_tokens"),
+        cache_read_tokens: get_u64(usage, "cache_read_input_tokens"),
+        cache_creation_tokens: get_u64(usage, "cache_creation_input_tokens"),
+        cost_usd: 0.0,
+    }
+}
+
+impl UsageParser for ClaudeUsageParser {
+    fn parse_line(&mut self, line: &str) -> Option<TokenUsage> {
+        let value = parse_json(line)?;
+        let usage = match value.get("type").and_then(|t| t.as_str()) {
+            Some("assistant") => {
+                let message = value.get("message")?;
+                let usage = claude_usage(message.get("usage")?);
+                let id = message.get("id").and_then(|id| id.as_str()).unwrap_or_default();
+                let previous = self.messages.insert(id.to_string(), usage).unwrap_or_default();
+                usage.since(&previous)
+            }
+            Some("result") => {
+                let mut total = claude_usage(value.get("usage")?);
+                total.cost_usd = value
+                    .get("total_cost_usd")
+                    .or_else(|| value.get("cost_usd"))
+                    .and_then(|c| c.as_f64())
+                    .unwrap_or(0.0);
+                total.since(&self.counted)
+            }
+            _ => return None,
+        };
+        self.counted.add(&usage);
+        Some(usage)
+    }
+
+    /// Text of assistant messages; other events are hidden
+    fn display_line(&self, line: &str) -> Option<String> {
+        let Some(value) = parse_json(line) else {
+            return Some(line.to_string());
+        };
+        if value.get("type").and_then(|t| t.as_str()) != Some("assistant") {
+            return None;
+        }
+        let text: Vec<&str> = value
+            .get("message")?
+            .get("content")?
+            .as_array()?
+            .iter()
+            .filter(|block| block.get("type").and_then(|t| t.as_str()) == Some("text"))
+            .filter_map(|block| block.get("text")?.as_str())
+            .collect();
+        let text = text.join(" ");
+        (!text.trim().is_empty()).then(|| text.trim().to_string())
+    }
+}
+
+/// Parser for Codex JSON events
+/// `{"type":"turn.completed","usage":{"input_tokens":..,"cached_input_tokens":..,"output_tokens":..}}`
+pub struct CodexUsageParser;
+
+impl UsageParser for CodexUsageParser {
+    fn parse_line(&mut self, line: &str) -> Option<TokenUsage> {
+        let value = parse_json(line)?;
+        if value.get("type").and_then(|t| t.as_str()) != Some("turn.completed") {
+            return None;
+        }
+        let usage = value.get("usage")?;
+        let cached = get_u64(usage, "cached_input_tokens");
+        Some(TokenUsage {
+            // input_tokens includes cached tokens
+            input_tokens: get_u64(usage, "input_tokens").saturating_sub(cached),
+            output_tokens: get_u64(usage, "output_tokens"),
+            cache_read_tokens: cached,
+            cache_creation_tokens: 0,
+            cost_usd: 0.0,
+        })
+    }
+}
+
+/// Parser for Gemini CLI JSON output
+/// `{"response":..,"stats":{"models":{"<model>":{"tokens":{"prompt":..,"candidates":..,"cached":..}}}}}`
+pub struct GeminiUsageParser;
+
+impl UsageParser for GeminiUsageParser {
+    fn parse_line(&mut self, line: &str) -> Option<TokenUsage> {
+        let value = parse_json(line)?;
+        let models = value.get("stats")?.get("models")?.as_object()?;
+        let mut usage = TokenUsage::default();
+        for model in models.values() {
+            if let Some(tokens) = model.get("tokens") {
+                let cached = get_u64(tokens, "cached");
+                usage.input_tokens += get_u64(tokens, "prompt").saturating_sub(cached);
+                usage.output_tokens += get_u64(tokens, "candidates");
+                usage.cache_read_tokens += cached;
+            }
+        }
+        Some(usage)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // ========================================
+    // TokenUsage Tests
+    // ========================================
+
+    #[test]
+    fn test_token_usage_add_and_total() {
+        let mut usage = TokenUsage {
+            input_tokens: 100,
+            output_tokens: 50,
+            cost_usd: 0.01,
+            ..Default::default()
+        };
+        usage.add(&TokenUsage {
+            input_tokens: 10,
+            cache_read_tokens: 5,
+            cost_usd: 0.02,
+            ..Default::default()
+        });
+
+        assert_eq!(usage.input_tokens, 110);
+        assert_eq!(usage.total_tokens(), 165);
+        assert!((usage.cost_usd - 0.03).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_token_usage_is_empty() {
+        assert!(TokenUsage::default().is_empty());
+        let usage = TokenUsage {
+            output_tokens: 1,
+            ..Default::default()
+        };
+        assert!(!usage.is_empty());
+    }
+
+    #[test]
+    fn test_format_tokens() {
+        assert_eq!(format_tokens(950), "950");
+        assert_eq!(format_tokens(12_345), "12.3k");
+        assert_eq!(format_tokens(1_200_000), "1.2M");
+    }
+
+    // ========================================
+    // Aggregation Tests
+    // ========================================
+
+    #[test]
+    fn test_usage_by_agent() {
+        let usage = TokenUsage {
+            input_tokens: 10,
+            cost_usd: 0.5,
+            ..Default::default()
+        };
+        let records = vec![
+            UsageRecord::new("gemini", usage, 0),
+            UsageRecord::new("claude", usage, 0),
+            UsageRecord::new("claude", usage, 0),
+        ];
+
+        let by_agent = usage_by_agent(&records);
+        assert_eq!(by_agent.len(), 2);
+        assert_eq!(by_agent["claude"].input_tokens, 20);
+        assert_eq!(by_agent["gemini"].input_tokens, 10);
+
+        let total = total_usage(&records);
+        assert_eq!(total.input_tokens, 30);
+        assert!((total.cost_usd - 1.5).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_pricing_estimate() {
+        let pricing = Pricing {
+            input_per_mtok: 3.0,
+            output_per_mtok: 15.0,
+            cached_per_mtok: 0.3,
+        };
+        let usage = TokenUsage {
+            input_tokens: 1_000_000,
+            output_tokens: 100_000,
+            cache_read_tokens: 1_000_000,
+            ..Default::default()
+        };
+        assert!((pricing.estimate(&usage) - 4.8).abs() < 1e-9);
+    }
+
+    // ========================================
+    // Parser Tests
+    // ========================================
+
+    #[test]
+    fn test_claude_parser_result_record() {
+        let mut parser = UsageFormat::Claude.parser().unwrap();
+        let line = r#"{"type":"result","subtype":"success","total_cost_usd":0.42,"usage":{"input_tokens":1200,"output_tokens":300,"cache_read_input_tokens":5000,"cache_creation_input_tokens":100}}"#;
+
+        let usage = parser.parse_line(line).unwrap();
+        assert_eq!(usage.input_tokens, 1200);
+        assert_eq!(usage.output_tokens, 300);
+        assert_eq!(usage.cache_read_tokens, 5000);
+        assert_eq!(usage.cache_creation_tokens, 100);
+        assert!((usage.cost_usd - 0.42).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_claude_parser_ignores_other_lines() {
+        let mut parser = UsageFormat::Claude.parser().unwrap();
+        assert!(parser.parse_line("plain text output").is_none());
+        assert!(parser.parse_line(r#"{"type":"system","subtype":"init"}"#).is_none());
+        assert!(parser.parse_line("{ not json").is_none());
+    }
+
+    #[test]
+    fn test_claude_parser_streamed_messages() {
+        let mut parser = UsageFormat::Claude.parser().unwrap();
+        let message = |id: &str, output: u64| {
+            format!(
+                r#"{{"type":"assistant","message":{{"id":"{}","content":[],"usage":{{"input_tokens":100,"output_tokens":{}}}}}}}"#,
+                id, output
+            )
+        };
+        let mut total = TokenUsage::default();
+        for line in [message("msg_1", 10), message("msg_1", 10), message("msg_2", 5)] {
+            if let Some(usage) = parser.parse_line(&line) {
+                total.add(&usage);
+            }
+        }
+        // Repeated events of msg_1 are counted once
+        assert_eq!((total.input_tokens, total.output_tokens), (200, 15));
+
+        // The result record only adds what the messages did not report
+        let result = r#"{"type":"result","total_cost_usd":0.05,"usage":{"input_tokens":200,"output_tokens":20}}"#;
+        total.add(&parser.parse_line(result).unwrap());
+        assert_eq!((total.input_tokens, total.output_tokens), (200, 20));
+        assert!((total.cost_usd - 0.05).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_claude_display_and_reply() {
+        let parser = UsageFormat::Claude.parser().unwrap();
+        let text = r#"{"type":"assistant","message":{"content":[{"type":"text","text":"Adding the test"},{"type":"tool_use","name":"Edit"}]}}"#;
+        assert_eq!(parser.display_line(text).as_deref(), Some("Adding the test"));
+        assert!(parser.display_line(r#"{"type":"user","message":{}}"#).is_none());
+        assert_eq!(parser.display_line("plain").as_deref(), Some("plain"));
+
+        let stdout = "{\"type\":\"system\"}\n{\"type\":\"result\",\"result\":\"Fix parser\\n\"}\n";
+        assert_eq!(UsageFormat::Claude.reply(stdout), "Fix parser");
+        assert_eq!(UsageFormat::None.reply(" plain\n"), "plain");
+    }
+
+    #[test]
+    fn test_codex_parser_turn_completed() {
+        let mut parser = UsageFormat::Codex.parser().unwrap();
+        let line = r#"{"type":"turn.completed","usage":{"input_tokens":1000,"cached_input_tokens":400,"output_tokens":200}}"#;
+
+        let usage = parser.parse_line(line).unwrap();
+        assert_eq!(usage.input_tokens, 600);
+        assert_eq!(usage.cache_read_tokens, 400);
+        assert_eq!(usage.output_tokens, 200);
+        assert!(parser.parse_line(r#"{"type":"item.completed"}"#).is_none());
+    }
+
+    #[test]
+    fn test_gemini_parser_stats() {
+        let mut parser = UsageFormat::Gemini.parser().unwrap();
+        let line = r#"{"response":"ok","stats":{"models":{"gemini-2.5-pro":{"tokens":{"prompt":800,"candidates":100,"cached":300}}}}}"#;
+
+        let usage = parser.parse_line(line).unwrap();
+        assert_eq!(usage.input_tokens, 500);
+        assert_eq!(usage.output_tokens, 100);
+        assert_eq!(usage.cache_read_tokens, 300);
+    }
+
+    #[test]
+    fn test_usage_format_none_has_no_parser() {
+        assert!(UsageFormat::None.parser().is_none());
+    }
+
+    #[test]
+    fn test_usage_format_for_agent() {
+        assert_eq!(UsageFormat::for_agent("claude"), UsageFormat::Claude);
+        assert_eq!(UsageFormat::for_agent("codex"), UsageFormat::Codex);
+        assert_eq!(UsageFormat::for_agent("custom"), UsageFormat::None);
+    }
+}
@@ -0,0 +1,621 @@
+use std::cell::Cell;
+use std::ops::Range;
+
+use ratatui::style::Color;
+
+use crate::git::{word_diff, DiffHunk, DiffLine, FileDiff, LineKind};
+use crate::highlight::{highlight_lines, Segments};
+
+/// Terminal width from which the automatic layout shows both sides
+pub const SIDE_BY_SIDE_MIN_WIDTH: u16 = 140;
+
+/// Files with more lines than this are shown without syntax colors
+const MAX_HIGHLIGHT_LINES: usize = 10_000;
+
+/// Row of the hunks pane
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DiffRow {
+    /// Hunk header
+    Hunk(usize),
+    /// Line of a hunk (unified layout)
+    Line(usize, usize),
+    /// Old and new line side by side (side-by-side layout)
+    Pair(usize, Option<usize>, Option<usize>),
+}
+
+/// Unified or side-by-side hunks
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum LayoutMode {
+    /// Side by side on wide terminals, unified otherwise
+    #[default]
+    Auto,
+    Unified,
+    SideBySide,
+}
+
+/// Piece of a rendered line
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Segment {
+    pub text: String,
+    /// Syntax color (None when the language is unknown)
+    pub color: Option<Color>,
+    /// Part of an intra-line (word-level) change
+    pub emphasized: bool,
+}
+
+/// State of the two-pane diff view (file list + hunks of the selected file).
+/// Rows, syntax colors and word-level changes of the selected file are computed once
+/// on selection so each frame only renders the visible window. A cursor row selects
+/// the line review comments are attached to; the window follows it while rendering.
+#[derive(Debug, Default)]
+pub struct DiffView {
+    files: Vec<FileDiff>,
+    selected: usize,
+    unified: Vec<DiffRow>,
+    split: Vec<DiffRow>,
+    cursor: usize,
+    /// First visible row (adjusted by `window` to keep the cursor visible)
+    scroll: Cell<usize>,
+    mode: LayoutMode,
+    width: u16,
+    /// Syntax colors per hunk line
+    colors: Vec<Vec<Option<Segments>>>,
+    /// Changed byte ranges per hunk line
+    emphasis: Vec<Vec<Vec<Range<usize>>>>,
+}
+
+/// Removed/added runs of a hunk paired line by line (context lines pair with themselves)
+fn pair_lines(hunk: &DiffHunk) -> Vec<(Option<usize>, Option<usize>)> {
+    let mut pairs = Vec::new();
+    let mut index = 0;
+    while index < hunk.lines.len() {
+        if hunk.lines[index].kind == LineKind::Context {
+            pairs.push((Some(index), Some(index)));
+            index += 1;
+            continue;
+        }
+        let removed_start = index;
+        while index < hunk.lines.len() && hunk.lines[index].kind == LineKind::Removed {
+            index += 1;
+        }
+        let added_start = index;
+        while index < hunk.lines.len() && hunk.lines[index].kind == LineKind::Added {
+            index += 1;
+        }
+        let removed = added_start - removed_start;
+        let added = index - added_start;
+        for offset in 0..removed.max(added) {
+            pairs.push((
+                (offset < removed).then_some(removed_start + offset),
+                (offset < added).then_some(added_start + offset),
+            ));
+        }
+    }
+    pairs
+}
+
+/// Syntax colors for a hunk: old side (context + removed) and new side (context + added)
+/// are highlighted separately so each keeps a consistent parser state.
+fn hunk_colors(path: &str, hunk: &DiffHunk) -> Vec<Option<Segments>> {
+    let side = |kind: LineKind| -> Vec<(usize, &DiffLine)> {
+        hunk.lines
+            .iter()
+            .enumerate()
+            .filter(|(_, line)| line.kind == LineKind::Context || line.kind == kind)
+            .collect()
+    };
+    let mut colors = vec![None; hunk.lines.len()];
+    for kind in [LineKind::Removed, LineKind::Added] {
+        let lines = side(kind);
+        let Some(highlighted) = highlight_lines(path, lines.iter().map(|(_, l)| l.content.as_str()))
+        else {
+            return colors;
+        };
+        for ((index, _), segments) in lines.into_iter().zip(highlighted) {
+            colors[index] = Some(segments);
+        }
+    }
+    colors
+}
+
+impl DiffView {
+    pub fn new(files: Vec<FileDiff>) -> Self {
+        let mut view = Self {
+            files,
+            ..Self::default()
+        };
+        view.select_file(0);
+        view
+    }
+
+    pub fn files(&self) -> &[FileDiff] {
+        &self.files
+    }
+
+    /// Index of the selected file
+    pub fn selected(&self) -> usize {
+        self.selected
+    }
+
+    pub fn selected_file(&self) -> Option<&FileDiff> {
+        self.files.get(self.selected)
+    }
+
+    /// Hunk of the selected file
+    pub fn hunk(&self, index: usize) -> Option<&DiffHunk> {
+        self.selected_file()?.hunks.get(index)
+    }
+
+    /// Line of the selected file
+    pub fn line(&self, hunk: usize, line: usize) -> Option<&DiffLine> {
+        self.hunk(hunk)?.lines.get(line)
+    }
+
+    /// Whether hunks are shown side by side
+    pub fn side_by_side(&self) -> bool {
+        match self.mode {
+            LayoutMode::Auto => self.width >= SIDE_BY_SIDE_MIN_WIDTH,
+            LayoutMode::Unified => false,
+            LayoutMode::SideBySide => true,
+        }
+    }
+
+    /// Rows of the selected file in the current layout
+    pub fn rows(&self) -> &[DiffRow] {
+        if self.side_by_side() {
+            &self.split
+        } else {
+            &self.unified
+        }
+    }
+
+    /// Selected row
+    pub fn cursor(&self) -> usize {
+        self.cursor
+    }
+
+    /// First visible row of a pane `height` rows high, scrolled to keep the cursor visible
+    pub fn window(&self, height: usize) -> usize {
+        let mut scroll = self.scroll.get();
+        if self.cursor < scroll {
+            scroll = self.cursor;
+        } else if height > 0 && self.cursor >= scroll + height {
+            scroll = self.cursor + 1 - height;
+        }
+        self.scroll.set(scroll);
+        scroll
+    }
+
+    /// Hunk and line under the cursor (the new side of side-by-side pairs when present)
+    pub fn cursor_line(&self) -> Option<(usize, usize)> {
+        match *self.rows().get(self.cursor)? {
+            DiffRow::Hunk(_) => None,
+            DiffRow::Line(hunk, line) => Some((hunk, line)),
+            DiffRow::Pair(hunk, old, new) => new.or(old).map(|line| (hunk, line)),
+        }
+    }
+
+    /// Total added and removed lines
+    pub fn totals(&self) -> (usize, usize) {
+        self.files
+            .iter()
+            .fold((0, 0), |(a, d), f| (a + f.additions, d + f.deletions))
+    }
+
+    /// Line text split by syntax color and word-level changes
+    pub fn segments(&self, hunk: usize, line: usize) -> Vec<Segment> {
+        let Some(diff_line) = self.line(hunk, line) else {
+            return Vec::new();
+        };
+        let colored = self
+            .colors
+            .get(hunk)
+            .and_then(|lines| lines.get(line).cloned().flatten());
+        let pieces: Vec<(Option<Color>, &str)> = match &colored {
+            Some(segments) => segments.iter().map(|(c, t)| (Some(*c), t.as_str())).collect(),
+            None => vec![(None, diff_line.content.as_str())],
+        };
+        let emphasis = self
+            .emphasis
+            .get(hunk)
+            .and_then(|lines| lines.get(line))
+            .map(Vec::as_slice)
+            .unwrap_or_default();
+
+        // Split colored pieces at emphasis boundaries
+        let mut segments: Vec<Segment> = Vec::new();
+        let mut offset = 0;
+        for (color, text) in pieces {
+            let mut cut = 0;
+            while cut < text.len() {
+                let position = offset + cut;
+                let emphasized = emphasis.iter().any(|r| r.contains(&position));
+                let boundary = emphasis
+                    .iter()
+                    .flat_map(|r| [r.start, r.end])
+                    .filter(|&b| b > position && b < offset + text.len())
+                    .min()
+                    .unwrap_or(offset + text.len());
+                let piece = &text[cut..boundary - offset];
+                match segments.last_mut() {
+                    Some(last) if last.color == color && last.emphasized == emphasized => {
+                        last.text.push_str(piece)
+                    }
+                    _ => segments.push(Segment {
+                        text: piece.to_string(),
+                        color,
+                        emphasized,
+                    }),
+                }
+                cut = boundary - offset;
+            }
+            offset += text.len();
+        }
+        segments
+    }
+
+    /// Select a file and prepare its rows, colors and word-level changes
+    pub fn select_file(&mut self, index: usize) {
+        self.selected = index.min(self.files.len().saturating_sub(1));
+        self.unified.clear();
+        self.split.clear();
+        self.colors.clear();
+        self.emphasis.clear();
+        self.cursor = 0;
+        self.scroll.set(0);
+        let Some(file) = self.files.get(self.selected) else {
+            return;
+        };
+
+        let total_lines: usize = file.hunks.iter().map(|h| h.lines.len()).sum();
+        for (hunk_index, hunk) in file.hunks.iter().enumerate() {
+            self.unified.push(DiffRow::Hunk(hunk_index));
+            self.unified
+                .extend((0..hunk.lines.len()).map(|line| DiffRow::Line(hunk_index, line)));
+
+            let pairs = pair_lines(hunk);
+            let mut emphasis = vec![Vec::new(); hunk.lines.len()];
+            for &(old, new) in &pairs {
+                if let (Some(old), Some(new)) = (old, new) {
+                    if old != new {
+                        let (removed, added) =
+                            word_diff(&hunk.lines[old].content, &hunk.lines[new].content);
+                        emphasis[old] = removed;
+                        emphasis[new] = added;
+                    }
+                }
+            }
+            self.emphasis.push(emphasis);
+
+            self.split.push(DiffRow::Hunk(hunk_index));
+            self.split.extend(
+                pairs
+                    .into_iter()
+                    .map(|(old, new)| DiffRow::Pair(hunk_index, old, new)),
+            );
+
+            self.colors.push(if total_lines <= MAX_HIGHLIGHT_LINES {
+                hunk_colors(&file.path, hunk)
+            } else {
+                Vec::new()
+            });
+        }
+    }
+
+    pub fn next_file(&mut self) {
+        if self.selected + 1 < self.files.len() {
+            self.select_file(self.selected + 1);
+        }
+    }
+
+    pub fn prev_file(&mut self) {
+        if self.selected > 0 {
+            self.select_file(self.selected - 1);
+        }
+    }
+
+    /// Move the cursor by `delta` rows
+    pub fn move_cursor(&mut self, delta: isize) {
+        let max = self.rows().len().saturating_sub(1);
+        self.cursor = self.cursor.saturating_add_signed(delta).min(max);
+    }
+
+    /// Put the cursor on a hunk header and scroll it to the top
+    fn jump_to(&mut self, row: usize) {
+        self.cursor = row;
+        self.scroll.set(row);
+    }
+
+    /// Row indexes of hunk headers in the current layout
+    fn hunk_rows(&self) -> impl DoubleEndedIterator<Item = usize> + '_ {
+        self.rows()
+            .iter()
+            .enumerate()
+            .filter(|(_, row)| matches!(row, DiffRow::Hunk(_)))
+            .map(|(index, _)| index)
+    }
+
+    /// Jump to the next hunk, continuing with the next file
+    pub fn next_hunk(&mut self) {
+        let next = self.hunk_rows().find(|&row| row > self.cursor);
+        if let Some(row) = next {
+            self.jump_to(row);
+        } else if self.selected + 1 < self.files.len() {
+            self.next_file();
+        }
+    }
+
+    /// Jump to the previous hunk, continuing with the previous file's last hunk
+    pub fn prev_hunk(&mut self) {
+        let previous = self.hunk_rows().rev().find(|&row| row < self.cursor);
+        if let Some(row) = previous {
+            self.jump_to(row);
+        } else if self.selected > 0 {
+            self.prev_file();
+            let last = self.hunk_rows().last().unwrap_or(0);
+            self.jump_to(last);
+        }
+    }
+
+    /// Apply a layout change, keeping the cursor on the same line
+    fn relayout(&mut self, change: impl FnOnce(&mut Self)) {
+        let was_side_by_side = self.side_by_side();
+        let current = self.rows().get(self.cursor).copied();
+        change(self);
+        if self.side_by_side() != was_side_by_side {
+            let row = current.and_then(|current| {
+                self.rows().iter().position(|row| match (current, *row) {
+                    (DiffRow::Hunk(a), DiffRow::Hunk(b)) => a == b,
+                    (DiffRow::Line(h, l), DiffRow::Pair(hunk, old, new))
+                    | (DiffRow::Pair(hunk, old, new), DiffRow::Line(h, l)) => {
+                        h == hunk && (old == Some(l) || new == Some(l))
+                    }
+                    _ => false,
+                })
+            });
+            self.jump_to(row.unwrap_or(0));
+        }
+    }
+
+    /// Record the terminal width (drives the automatic layout)
+    pub fn set_width(&mut self, width: u16) {
+        self.relayout(|view| view.width = width);
+    }
+
+    /// Switch between unified and side-by-side
+    pub fn toggle_layout(&mut self) {
+        self.relayout(|view| {
+            view.mode = if view.side_by_side() {
+                LayoutMode::Unified
+            } else {
+                LayoutMode::SideBySide
+            };
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::git::ChangeKind;
+
+    fn line(kind: LineKind, content: &str) -> DiffLine {
+        DiffLine {
+            kind,
+            old_line: None,
+            new_line: None,
+            content: content.into(),
+        }
+    }
+
+    fn file(path: &str, hunk_sizes: &[usize]) -> FileDiff {
+        FileDiff {
+            path: path.into(),
+            old_path: None,
+            kind: ChangeKind::Modified,
+            additions: hunk_sizes.iter().sum(),
+            deletions: 1,
+            binary: false,
+            hunks: hunk_sizes
+                .iter()
+                .map(|&size| DiffHunk {
+                    header: "@@ -1 +1 @@".into(),
+                    lines: (0..size)
+                        .map(|n| line(LineKind::Added, &format!("line {}", n)))
+                        .collect(),
+                })
+                .collect(),
+        }
+    }
+
+    fn edit_file() -> FileDiff {
+        FileDiff {
+            hunks: vec![DiffHunk {
+                header: "@@ -1,3 +1,3 @@".into(),
+                lines: vec![
+                    line(LineKind::Context, "fn main() {"),
+                    line(LineKind::Removed, "    let count = 1;"),
+                    line(LineKind::Removed, "    drop(count);"),
+                    line(LineKind::Added, "    let total = 1;"),
+                    line(LineKind::Context, "}"),
+                ],
+            }],
+            ..file("src/main.rs", &[])
+        }
+    }
+
+    // ========================================
+    // Navigation Tests
+    // ========================================
+
+    #[test]
+    fn test_rows_and_totals() {
+        let view = DiffView::new(vec![file("a.rs", &[2, 1]), file("b.rs", &[3])]);
+        assert_eq!(
+            view.rows(),
+            &[
+                DiffRow::Hunk(0),
+                DiffRow::Line(0, 0),
+                DiffRow::Line(0, 1),
+                DiffRow::Hunk(1),
+                DiffRow::Line(1, 0),
+            ]
+        );
+        assert_eq!(view.totals(), (6, 2));
+        assert_eq!(view.line(1, 0).unwrap().content, "line 0");
+        assert!(view.line(2, 0).is_none());
+        assert!(DiffView::new(Vec::new()).selected_file().is_none());
+    }
+
+    #[test]
+    fn test_hunk_navigation_crosses_files() {
+        let mut view = DiffView::new(vec![file("a.rs", &[2, 1]), file("b.rs", &[3, 3])]);
+
+        view.next_hunk();
+        assert_eq!((view.selected(), view.cursor()), (0, 3));
+        view.next_hunk();
+        assert_eq!((view.selected(), view.cursor()), (1, 0));
+        view.next_hunk();
+        assert_eq!((view.selected(), view.cursor()), (1, 4));
+        // Last hunk of the last file stays put
+        view.next_hunk();
+        assert_eq!((view.selected(), view.cursor()), (1, 4));
+
+        view.prev_hunk();
+        assert_eq!((view.selected(), view.cursor()), (1, 0));
+        view.prev_hunk();
+        assert_eq!((view.selected(), view.cursor()), (0, 3));
+    }
+
+    #[test]
+    fn test_cursor_and_file_selection() {
+        let mut view = DiffView::new(vec![file("a.rs", &[2]), file("b.rs", &[1])]);
+        view.move_cursor(10);
+        assert_eq!(view.cursor(), 2);
+        view.move_cursor(-5);
+        assert_eq!(view.cursor(), 0);
+
+        view.move_cursor(1);
+        view.next_file();
+        assert_eq!((view.selected(), view.cursor()), (1, 0));
+        view.next_file();
+        assert_eq!(view.selected(), 1);
+        view.prev_file();
+        assert_eq!(view.selected_file().unwrap().path, "a.rs");
+    }
+
+    #[test]
+    fn test_window_follows_cursor() {
+        let mut view = DiffView::new(vec![file("a.rs", &[20])]);
+        assert_eq!(view.window(5), 0);
+        view.move_cursor(7);
+        assert_eq!(view.window(5), 3);
+        // Moving within the window does not scroll
+        view.move_cursor(-2);
+        assert_eq!(view.window(5), 3);
+        view.move_cursor(-5);
+        assert_eq!(view.window(5), 0);
+    }
+
+    #[test]
+    fn test_cursor_line() {
+        let mut view = DiffView::new(vec![edit_file()]);
+        // Hunk header has no line
+        assert_eq!(view.cursor_line(), None);
+        view.move_cursor(2);
+        assert_eq!(view.cursor_line(), Some((0, 1)));
+
+        // Side by side prefers the new side, falling back to the old one
+        view.toggle_layout();
+        assert_eq!(view.rows()[view.cursor()], DiffRow::Pair(0, Some(1), Some(3)));
+        assert_eq!(view.cursor_line(), Some((0, 3)));
+        view.move_cursor(1);
+        assert_eq!(view.cursor_line(), Some((0, 2)));
+
+        // Back to unified keeps the cursor on the same line
+        view.toggle_layout();
+        assert_eq!(view.cursor_line(), Some((0, 2)));
+    }
+
+    // ========================================
+    // Layout Tests
+    // ========================================
+
+    #[test]
+    fn test_side_by_side_rows() {
+        let mut view = DiffView::new(vec![edit_file()]);
+        view.toggle_layout();
+        assert!(view.side_by_side());
+        assert_eq!(
+            view.rows(),
+            &[
+                DiffRow::Hunk(0),
+                DiffRow::Pair(0, Some(0), Some(0)),
+                DiffRow::Pair(0, Some(1), Some(3)),
+                DiffRow::Pair(0, Some(2), None),
+                DiffRow::Pair(0, Some(4), Some(4)),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_layout_follows_width_and_toggle() {
+        let mut view = DiffView::new(vec![file("a.rs", &[3, 3])]);
+        view.set_width(100);
+        assert!(!view.side_by_side());
+
+        view.next_hunk();
+        assert_eq!(view.cursor(), 4);
+        view.set_width(SIDE_BY_SIDE_MIN_WIDTH);
+        assert!(view.side_by_side());
+        // Still at the second hunk
+        assert_eq!(view.rows()[view.cursor()], DiffRow::Hunk(1));
+
+        // An explicit choice overrides the width
+        view.toggle_layout();
+        assert!(!view.side_by_side());
+        view.set_width(200);
+        assert!(!view.side_by_side());
+    }
+
+    // ========================================
+    // Highlighting Tests
+    // ========================================
+
+    #[test]
+    fn test_segments_mark_word_changes() {
+        let view = DiffView::new(vec![edit_file()]);
+
+        let segments = view.segments(0, 3);
+        let text: String = segments.iter().map(|s| s.text.as_str()).collect();
+        assert_eq!(text, "    let total = 1;");
+        let emphasized: Vec<&str> = segments
+            .iter()
+            .filter(|s| s.emphasized)
+            .map(|s| s.text.as_str())
+            .collect();
+        assert_eq!(emphasized, vec!["total"]);
+        // Rust is highlighted
+        assert!(segments.iter().all(|s| s.color.is_some()));
+
+        // Unpaired removed line has no word-level emphasis
+        assert!(view.segments(0, 2).iter().all(|s| !s.emphasized));
+    }
+
+    #[test]
+    fn test_segments_without_language() {
+        let mut file = edit_file();
+        file.path = "notes.unknownext".into();
+        let view = DiffView::new(vec![file]);
+
+        let segments = view.segments(0, 1);
+        assert_eq!(
+            segments,
+            vec![
+                Segment { text: "    let ".into(), color: None, emphasized: false },
+                Segment { text: "count".into(), color: None, emphasized: true },
+                Segment { text: " = 1;".into(), color: None, emphasized: false },
+            ]
+        );
+    }
+}
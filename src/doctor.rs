@@ -0,0 +1,450 @@
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::time::{Duration, Instant};
+
+use crate::agent::OrchestratorConfig;
+use crate::git::{GitConfig, GitValidator};
+use crate::pr::Forge;
+
+/// Result status of a health check
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HealthStatus {
+    Ok,
+    /// Usable but needs attention (e.g., outdated, unknown version)
+    Warning,
+    /// Not usable
+    Error,
+}
+
+impl HealthStatus {
+    pub fn icon(&self) -> &'static str {
+        match self {
+            Self::Ok => "✅",
+            Self::Warning => "⚠️",
+            Self::Error => "❌",
+        }
+    }
+}
+
+/// Health of an external command (agent CLI, git, gh/glab)
+#[derive(Debug, Clone)]
+pub struct ToolHealth {
+    /// Display name
+    pub name: String,
+    /// Resolved executable path
+    pub path: Option<PathBuf>,
+    /// Detected version
+    pub version: Option<String>,
+    pub status: HealthStatus,
+    /// Problem description and remediation hint (empty when Ok)
+    pub message: String,
+}
+
+/// Single check line in the doctor report
+#[derive(Debug, Clone)]
+pub struct Check {
+    pub status: HealthStatus,
+    pub message: String,
+}
+
+/// Full `hive doctor` report
+#[derive(Debug, Clone, Default)]
+pub struct DoctorReport {
+    /// (agent name, role, health)
+    pub agents: Vec<(String, &'static str, ToolHealth)>,
+    pub tools: Vec<ToolHealth>,
+    pub repository: Vec<Check>,
+}
+
+impl DoctorReport {
+    /// Check if any check failed
+    pub fn has_errors(&self) -> bool {
+        self.agents.iter().any(|(_, _, h)| h.status == HealthStatus::Error)
+            || self.tools.iter().any(|h| h.status == HealthStatus::Error)
+            || self.repository.iter().any(|c| c.status == HealthStatus::Error)
+    }
+
+    /// Render report as plain text
+    pub fn render(&self) -> String {
+        let mut out = String::from("Hive doctor\n\nAgents\n");
+        for (name, role, health) in &self.agents {
+            out.push_str(&format!(
+                "  {} {} ({}){}\n",
+                health.status.icon(),
+                name,
+                role,
+                tool_detail(health)
+            ));
+        }
+        out.push_str("\nTools\n");
+        for health in &self.tools {
+            out.push_str(&format!(
+                "  {} {}{}\n",
+                health.status.icon(),
+                health.name,
+                tool_detail(health)
+            ));
+        }
+        out.push_str("\nRepository\n");
+        for check in &self.repository {
+            out.push_str(&format!("  {} {}\n", check.status.icon(), check.message));
+        }
+        out
+    }
+}
+
+fn tool_detail(health: &ToolHealth) -> String {
+    let mut detail = String::new();
+    if let Some(version) = &health.version {
+        detail.push_str(&format!(" {}", version));
+    }
+    if let Some(path) = &health.path {
+        detail.push_str(&format!(" [{}]", path.display()));
+    }
+    if !health.message.is_empty() {
+        detail.push_str(&format!(" - {}", health.message));
+    }
+    detail
+}
+
+/// Resolve a command on PATH (or as a path if it contains a separator)
+pub fn resolve_command(command: &str) -> Option<PathBuf> {
+    if command.is_empty() {
+        return None;
+    }
+    if command.contains(std::path::MAIN_SEPARATOR) || command.contains('/') {
+        let path = PathBuf::from(command);
+        return is_executable(&path).then_some(path);
+    }
+    let paths = std::env::var_os("PATH")?;
+    std::env::split_paths(&paths)
+        .map(|dir| dir.join(command))
+        .find(|candidate| is_executable(candidate))
+}
+
+#[cfg(unix)]
+fn is_executable(path: &Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    path.metadata()
+        .map(|m| m.is_file() && m.permissions().mode() & 0o111 != 0)
+        .unwrap_or(false)
+}
+
+#[cfg(not(unix))]
+fn is_executable(path: &Path) -> bool {
+    path.is_file() || path.with_extension("exe").is_file()
+}
+
+/// Run a command and return its first output line (stdout, else stderr)
+pub fn probe_version(path: &Path, args: &[String], timeout: Duration) -> Option<String> {
+    let mut child = Command::new(path)
+        .args(args)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .ok()?;
+
+    // Some CLIs start an interactive session on unknown flags; don't hang
+    let start = Instant::now();
+    loop {
+        match child.try_wait() {
+            Ok(Some(_)) => break,
+            Ok(None) if start.elapsed() < timeout => std::thread::sleep(Duration::from_millis(20)),
+            _ => {
+                let _ = child.kill();
+                let _ = child.wait();
+                return None;
+            }
+        }
+    }
+
+    let output = child.wait_with_output().ok()?;
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    stdout
+        .lines()
+        .chain(stderr.lines())
+        .map(str::trim)
+        .find(|l| !l.is_empty())
+        .map(str::to_string)
+}
+
+/// Extract the first dotted version number from text (e.g., "git version 2.43.0" -> "2.43.0")
+pub fn parse_version(text: &str) -> Option<String> {
+    text.split(|c: char| !(c.is_ascii_digit() || c == '.'))
+        .map(|token| token.trim_matches('.'))
+        .find(|token| token.contains('.') && token.split('.').all(|p| !p.is_empty()))
+        .map(str::to_string)
+}
+
+/// Compare dotted versions numerically
+pub fn version_at_least(version: &str, minimum: &str) -> bool {
+    let parse = |v: &str| -> Vec<u64> { v.split('.').map(|p| p.parse().unwrap_or(0)).collect() };
+    let (mut v, mut m) = (parse(version), parse(minimum));
+    let len = v.len().max(m.len());
+    v.resize(len, 0);
+    m.resize(len, 0);
+    v >= m
+}
+
+/// Check an external command: resolve on PATH, probe version, compare with minimum
+pub fn check_tool(
+    name: &str,
+    command: &str,
+    version_args: &[String],
+    min_version: Option<&str>,
+) -> ToolHealth {
+    let mut health = ToolHealth {
+        name: name.to_string(),
+        path: resolve_command(command),
+        version: None,
+        status: HealthStatus::Ok,
+        message: String::new(),
+    };
+
+    let Some(path) = health.path.clone() else {
+        health.status = HealthStatus::Error;
+        health.message = format!("'{}' not found on PATH. Install it or fix the command", command);
+        return health;
+    };
+
+    let raw = probe_version(&path, version_args, Duration::from_secs(5));
+    health.version = raw.as_deref().and_then(parse_version);
+
+    match (health.version.as_deref(), min_version) {
+        (Some(version), Some(min)) if !version_at_least(version, min) => {
+            health.status = HealthStatus::Warning;
+            health.message = format!("outdated (requires >= {})", min);
+        }
+        (None, _) => {
+            health.status = HealthStatus::Warning;
+            health.message = format!("version unknown ({} {})", command, version_args.join(" "));
+        }
+        _ => {}
+    }
+
+    health
+}
+
+/// Check whether an agent command is installed (PATH lookup only, no process spawned)
+pub fn is_agent_available(config: &OrchestratorConfig, name: &str) -> bool {
+    config
+        .get_agent(name)
+        .map(|spec| resolve_command(&spec.command).is_some())
+        .unwrap_or(false)
+}
+
+/// Build the full doctor report
+pub fn run(config: &OrchestratorConfig, forge: Forge, repo_root: &Path) -> DoctorReport {
+    let mut report = DoctorReport::default();
+
+    let mut agents: Vec<(&String, &'static str)> = config
+        .planners
+        .keys()
+        .map(|n| (n, "planner"))
+        .chain(config.executors.keys().map(|n| (n, "executor")))
+        .collect();
+    agents.sort();
+    for (name, role) in agents {
+        if let Some(spec) = config.get_agent(name) {
+            let health = check_tool(
+                name,
+                &spec.command,
+                &spec.version_args(),
+                spec.min_version.as_deref(),
+            );
+            report.agents.push((name.clone(), role, health));
+        }
+    }
+
+    let version_flag = vec!["--version".to_string()];
+    report.tools.push(check_tool("git", "git", &version_flag, Some("2.20")));
+    let forge_cli = match forge {
+        Forge::Github => Some(("gh", "GitHub CLI")),
+        Forge::Gitlab => Some(("glab", "GitLab CLI")),
+        Forge::Fake => None,
+    };
+    if let Some((tool, label)) = forge_cli {
+        let mut health = check_tool(tool, tool, &version_flag, None);
+        if health.status == HealthStatus::Error {
+            // The forge CLI is only required for PRs
+            health.status = HealthStatus::Warning;
+            health.message = format!("not found on PATH. Install {} to create PRs", label);
+        } else if !cli_authenticated(tool) {
+            health.status = HealthStatus::Warning;
+            health.message = format!("not authenticated. Run `{} auth login`", tool);
+        }
+        report.tools.push(health);
+    }
+
+    report.repository = check_repository(repo_root);
+    report
+}
+
+fn cli_authenticated(tool: &str) -> bool {
+    Command::new(tool)
+        .args(["auth", "status"])
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()
+        .map(|s| s.success())
+        .unwrap_or(false)
+}
+
+fn check_repository(repo_root: &Path) -> Vec<Check> {
+    let git_config = GitConfig::load(&repo_root.join(".hive")).unwrap_or_default();
+    let validator = GitValidator::new(repo_root.to_path_buf())
+        .with_min_free_space(git_config.min_free_space());
+    let mut checks = Vec::new();
+
+    if !validator.is_git_repo() {
+        checks.push(Check {
+            status: HealthStatus::Error,
+            message: "Not a git repository. Run hive from a repository root".into(),
+        });
+        return checks;
+    }
+
+    match validator.current_branch() {
+        Ok(branch) => checks.push(Check {
+            status: HealthStatus::Ok,
+            message: format!("Git repository (branch: {})", branch),
+        }),
+        Err(e) => checks.push(Check {
+            status: HealthStatus::Warning,
+            message: format!("Git repository ({})", e),
+        }),
+    }
+
+    let base_branch = git_config
+        .base_branch
+        .or_else(|| validator.default_branch().ok());
+    match validator.validate_for_worktree_creation(base_branch.as_deref()) {
+        Ok(result) => {
+            for error in result.errors {
+                checks.push(Check {
+                    status: HealthStatus::Error,
+                    message: error,
+                });
+            }
+            for warning in result.warnings {
+                checks.push(Check {
+                    status: HealthStatus::Warning,
+                    message: warning,
+                });
+            }
+        }
+        Err(e) => checks.push(Check {
+            status: HealthStatus::Error,
+            message: format!("Validation failed: {}", e),
+        }),
+    }
+
+    let has_origin = git2::Repository::discover(repo_root)
+        .map(|repo| repo.find_remote("origin").is_ok())
+        .unwrap_or(false);
+    if !has_origin {
+        checks.push(Check {
+            status: HealthStatus::Warning,
+            message: "No 'origin' remote. PR creation will fail".into(),
+        });
+    }
+
+    checks
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // ========================================
+    // Version Parsing Tests
+    // ========================================
+
+    #[test]
+    fn test_parse_version() {
+        assert_eq!(parse_version("git version 2.43.0"), Some("2.43.0".into()));
+        assert_eq!(parse_version("gh version 2.40.1 (2023-12-13)"), Some("2.40.1".into()));
+        assert_eq!(parse_version("1.0.12 (Claude Code)"), Some("1.0.12".into()));
+        assert_eq!(parse_version("codex-cli 0.46.0"), Some("0.46.0".into()));
+        assert_eq!(parse_version("no version here"), None);
+    }
+
+    #[test]
+    fn test_version_at_least() {
+        assert!(version_at_least("2.43.0", "2.20"));
+        assert!(version_at_least("2.20", "2.20.0"));
+        assert!(!version_at_least("1.9.9", "2.0"));
+        assert!(version_at_least("10.0", "9.99"));
+    }
+
+    // ========================================
+    // Command Resolution Tests
+    // ========================================
+
+    #[test]
+    fn test_resolve_command_found() {
+        assert!(resolve_command("git").is_some());
+    }
+
+    #[test]
+    fn test_resolve_command_missing() {
+        assert!(resolve_command("hive-definitely-missing-command").is_none());
+        assert!(resolve_command("").is_none());
+        assert!(resolve_command("/nonexistent/path/to/agent").is_none());
+    }
+
+    #[test]
+    fn test_check_tool_missing_is_error() {
+        let health = check_tool("ghost", "hive-definitely-missing-command", &[], None);
+        assert_eq!(health.status, HealthStatus::Error);
+        assert!(health.path.is_none());
+        assert!(health.message.contains("not found"));
+    }
+
+    #[test]
+    fn test_check_tool_detects_version() {
+        let health = check_tool("git", "git", &["--version".into()], Some("1.0"));
+        assert_eq!(health.status, HealthStatus::Ok);
+        assert!(health.version.is_some());
+    }
+
+    #[test]
+    fn test_check_tool_outdated() {
+        let health = check_tool("git", "git", &["--version".into()], Some("999.0"));
+        assert_eq!(health.status, HealthStatus::Warning);
+        assert!(health.message.contains("outdated"));
+    }
+
+    // ========================================
+    // Report Tests
+    // ========================================
+
+    #[test]
+    fn test_is_agent_available() {
+        let mut config = OrchestratorConfig::default();
+        config.executors.get_mut("claude").unwrap().command = "git".into();
+        config.planners.get_mut("gemini").unwrap().command =
+            "hive-definitely-missing-command".into();
+
+        assert!(is_agent_available(&config, "claude"));
+        assert!(!is_agent_available(&config, "gemini"));
+        assert!(!is_agent_available(&config, "unknown"));
+    }
+
+    #[test]
+    fn test_report_not_git_repo() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let checks = check_repository(temp_dir.path());
+        assert_eq!(checks.len(), 1);
+        assert_eq!(checks[0].status, HealthStatus::Error);
+
+        let report = DoctorReport {
+            repository: checks,
+            ..Default::default()
+        };
+        assert!(report.has_errors());
+        assert!(report.render().contains("Not a git repository"));
+    }
+}
@@ -0,0 +1,240 @@
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use super::config::{render_template, BootstrapConfig};
+use super::worktree::WorktreeManager;
+
+/// Copy a file, or a directory recursively
+fn copy_recursive(from: &Path, to: &Path) -> std::io::Result<()> {
+    if from.is_dir() {
+        std::fs::create_dir_all(to)?;
+        for entry in std::fs::read_dir(from)? {
+            let entry = entry?;
+            copy_recursive(&entry.path(), &to.join(entry.file_name()))?;
+        }
+        Ok(())
+    } else {
+        std::fs::copy(from, to).map(|_| ())
+    }
+}
+
+#[cfg(unix)]
+fn symlink(target: &Path, link: &Path) -> std::io::Result<()> {
+    std::os::unix::fs::symlink(target, link)
+}
+
+#[cfg(windows)]
+fn symlink(target: &Path, link: &Path) -> std::io::Result<()> {
+    if target.is_dir() {
+        std::os::windows::fs::symlink_dir(target, link)
+    } else {
+        std::os::windows::fs::symlink_file(target, link)
+    }
+}
+
+/// Last non-empty line of command output (the usual place for the error)
+fn last_line(output: &[u8]) -> String {
+    String::from_utf8_lossy(output)
+        .lines()
+        .rev()
+        .find(|line| !line.trim().is_empty())
+        .unwrap_or_default()
+        .trim()
+        .to_string()
+}
+
+impl WorktreeManager {
+    /// Use `config` to bootstrap new worktrees
+    pub fn with_bootstrap(mut self, config: BootstrapConfig) -> Self {
+        self.bootstrap = config;
+        self
+    }
+
+    /// Repository root as an absolute path (targets of symlinks and `{repo}`)
+    fn absolute_repo_root(&self) -> PathBuf {
+        std::fs::canonicalize(&self.repo_root).unwrap_or_else(|_| self.repo_root.clone())
+    }
+
+    /// Environment variables for processes run in a task worktree
+    pub fn bootstrap_env(&self, worktree_path: &Path) -> Vec<(String, String)> {
+        let repo = self.absolute_repo_root().to_string_lossy().to_string();
+        let worktree = std::fs::canonicalize(worktree_path)
+            .unwrap_or_else(|_| worktree_path.to_path_buf())
+            .to_string_lossy()
+            .to_string();
+        self.bootstrap
+            .env
+            .iter()
+            .map(|(name, value)| {
+                let value = render_template(value, &[("repo", &repo), ("worktree", &worktree)]);
+                (name.clone(), value)
+            })
+            .collect()
+    }
+
+    /// Check if a worktree-relative path was put there by bootstrap (never committed)
+    pub(super) fn is_bootstrapped(&self, path: &Path) -> bool {
+        self.bootstrap
+            .copy
+            .iter()
+            .chain(&self.bootstrap.symlink)
+            .any(|entry| path.starts_with(entry))
+    }
+
+    /// Copy files, create symlinks and run setup commands in a new worktree.
+    /// Sources missing from the repository root are skipped. Returns the failures.
+    pub(super) fn run_bootstrap(&self, worktree_path: &Path) -> Vec<String> {
+        let repo_root = self.absolute_repo_root();
+        let mut failures = Vec::new();
+
+        for entry in &self.bootstrap.copy {
+            let (source, target) = (repo_root.join(entry), worktree_path.join(entry));
+            if !source.exists() || target.exists() {
+                continue;
+            }
+            let copied = target
+                .parent()
+                .map_or(Ok(()), std::fs::create_dir_all)
+                .and_then(|()| copy_recursive(&source, &target));
+            if let Err(e) = copied {
+                failures.push(format!("copy {}: {}", entry, e));
+            }
+        }
+
+        for entry in &self.bootstrap.symlink {
+            let (source, target) = (repo_root.join(entry), worktree_path.join(entry));
+            if !source.exists() || target.symlink_metadata().is_ok() {
+                continue;
+            }
+            let linked = target
+                .parent()
+                .map_or(Ok(()), std::fs::create_dir_all)
+                .and_then(|()| symlink(&source, &target));
+            if let Err(e) = linked {
+                failures.push(format!("symlink {}: {}", entry, e));
+            }
+        }
+
+        let env = self.bootstrap_env(worktree_path);
+        for command in &self.bootstrap.setup {
+            let output = Command::new("sh")
+                .args(["-c", command])
+                .current_dir(worktree_path)
+                .envs(env.iter().map(|(name, value)| (name, value)))
+                .output();
+            let failure = match output {
+                Ok(output) if output.status.success() => continue,
+                Ok(output) => {
+                    let detail = match last_line(&output.stderr) {
+                        line if line.is_empty() => last_line(&output.stdout),
+                        line => line,
+                    };
+                    format!("setup `{}` failed ({}): {}", command, output.status, detail)
+                }
+                Err(e) => format!("setup `{}` could not run: {}", command, e),
+            };
+            // Later commands usually depend on earlier ones
+            failures.push(failure);
+            break;
+        }
+
+        failures
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::git::test_support::{git, init_repo};
+    use std::collections::BTreeMap;
+    use tempfile::TempDir;
+
+    /// Repository with untracked `.env`, `node_modules/` and `config/local/` in its root
+    fn create_repo(bootstrap: BootstrapConfig) -> (TempDir, WorktreeManager, String) {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        let base = init_repo(root);
+        std::fs::write(root.join(".gitignore"), ".hive/\n.env\nnode_modules\nconfig/local/\n").unwrap();
+        git(root, &["commit", "-qam", "Ignore local files"]);
+        std::fs::write(root.join(".env"), "SECRET=1\n").unwrap();
+        std::fs::create_dir_all(root.join("node_modules/left-pad")).unwrap();
+        std::fs::create_dir_all(root.join("config/local")).unwrap();
+        std::fs::write(root.join("config/local/settings.toml"), "debug = true\n").unwrap();
+        let manager = WorktreeManager::new(root.to_path_buf(), root.join(".hive")).with_bootstrap(bootstrap);
+        (temp_dir, manager, base)
+    }
+
+    // ========================================
+    // Bootstrap Tests
+    // ========================================
+
+    #[test]
+    fn test_copy_and_symlink() {
+        let (temp, manager, base) = create_repo(BootstrapConfig {
+            copy: vec![".env".into(), "config/local".into(), "missing.txt".into()],
+            symlink: vec!["node_modules".into()],
+            ..Default::default()
+        });
+
+        let (worktree, failures) = manager.create("task-boot", "hive/task-boot", &base).unwrap();
+        assert!(failures.is_empty(), "{:?}", failures);
+        assert_eq!(std::fs::read_to_string(worktree.join(".env")).unwrap(), "SECRET=1\n");
+        assert!(worktree.join("config/local/settings.toml").exists());
+        assert!(!worktree.join("missing.txt").exists());
+
+        let link = worktree.join("node_modules");
+        assert!(link.symlink_metadata().unwrap().file_type().is_symlink());
+        assert_eq!(
+            std::fs::canonicalize(&link).unwrap(),
+            std::fs::canonicalize(temp.path().join("node_modules")).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_bootstrapped_paths_are_not_committed() {
+        let (_temp, manager, base) = create_repo(BootstrapConfig {
+            copy: vec!["config/local".into()],
+            symlink: vec!["node_modules".into()],
+            ..Default::default()
+        });
+        let (worktree, _) = manager.create("task-boot", "hive/task-boot", &base).unwrap();
+        // Not ignored inside the worktree: a symlink does not match a directory pattern
+        std::fs::write(worktree.join("src.rs"), "fn main() {}\n").unwrap();
+
+        manager.commit_all(&worktree, "Task change").unwrap();
+        let files = git(&worktree, &["show", "--name-only", "--format=", "HEAD"]);
+        assert_eq!(files, "src.rs");
+        assert!(manager.is_bootstrapped(Path::new("config/local/settings.toml")));
+        assert!(!manager.is_bootstrapped(Path::new("config/app.toml")));
+    }
+
+    #[test]
+    fn test_setup_commands_use_env_and_stop_at_failure() {
+        let (temp, manager, base) = create_repo(BootstrapConfig {
+            env: BTreeMap::from([("CARGO_TARGET_DIR".to_string(), "{repo}/target".to_string())]),
+            setup: vec![
+                "echo \"$CARGO_TARGET_DIR\" > target-dir.txt".into(),
+                "echo boom >&2; exit 3".into(),
+                "touch never.txt".into(),
+            ],
+            ..Default::default()
+        });
+
+        let (worktree, failures) = manager.create("task-boot", "hive/task-boot", &base).unwrap();
+        let repo = std::fs::canonicalize(temp.path()).unwrap();
+        assert_eq!(
+            std::fs::read_to_string(worktree.join("target-dir.txt")).unwrap().trim(),
+            repo.join("target").to_string_lossy()
+        );
+        assert_eq!(failures.len(), 1);
+        assert!(failures[0].starts_with("setup `echo boom >&2; exit 3` failed"));
+        assert!(failures[0].ends_with("boom"));
+        assert!(!worktree.join("never.txt").exists());
+
+        let env = manager.bootstrap_env(&worktree);
+        assert_eq!(
+            env,
+            vec![("CARGO_TARGET_DIR".to_string(), repo.join("target").to_string_lossy().to_string())]
+        );
+    }
+}
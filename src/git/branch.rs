@@ -0,0 +1,344 @@
+use std::path::Path;
+
+use super::config::{render_template, BranchConfig};
+use crate::task::Task;
+
+/// Lowercase ASCII slug ("Fix OAuth login!" -> "fix-oauth-login"), cut at a word boundary to `max_len`
+pub fn slugify(text: &str, max_len: usize) -> String {
+    let mut slug = String::new();
+    for c in text.chars() {
+        if c.is_ascii_alphanumeric() {
+            slug.push(c.to_ascii_lowercase());
+        } else if !slug.is_empty() && !slug.ends_with('-') {
+            slug.push('-');
+        }
+    }
+    let slug = slug.trim_end_matches('-');
+    if slug.len() <= max_len {
+        return slug.to_string();
+    }
+
+    let cut = &slug[..max_len];
+    let cut = match cut.rfind('-') {
+        // Cut inside a word: drop the partial word
+        Some(end) if slug.as_bytes()[max_len] != b'-' => &cut[..end],
+        _ => cut,
+    };
+    cut.trim_end_matches('-').to_string()
+}
+
+/// Conventional change type guessed from the first word of a title
+pub fn change_type(title: &str) -> &'static str {
+    let first = title
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .find(|word| !word.is_empty())
+        .unwrap_or_default()
+        .to_ascii_lowercase();
+    match first.as_str() {
+        "fix" | "fixes" | "fixed" | "bug" | "bugfix" | "hotfix" => "fix",
+        "doc" | "docs" | "document" | "documentation" => "docs",
+        "refactor" | "cleanup" => "refactor",
+        "test" | "tests" => "test",
+        "perf" | "optimize" => "perf",
+        "chore" | "bump" | "ci" | "build" => "chore",
+        _ => "feat",
+    }
+}
+
+/// Make a rendered name usable as a git branch name
+fn sanitize(name: &str) -> String {
+    name.split('/')
+        .map(|component| {
+            let mut cleaned = String::new();
+            for c in component.chars() {
+                let c = if c.is_ascii_alphanumeric() || matches!(c, '.' | '_' | '-') {
+                    c
+                } else {
+                    '-'
+                };
+                if matches!(c, '-' | '.') && cleaned.ends_with(c) {
+                    continue;
+                }
+                cleaned.push(c);
+            }
+            let cleaned = cleaned.trim_matches(|c| c == '-' || c == '.');
+            cleaned.strip_suffix(".lock").unwrap_or(cleaned).to_string()
+        })
+        .filter(|component| !component.is_empty())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// Whether `needle` occurs in `haystack` delimited by `/`, `-` or the string ends
+fn contains_token(haystack: &str, needle: &str) -> bool {
+    haystack.match_indices(needle).any(|(start, _)| {
+        let before = haystack[..start].chars().last();
+        let after = haystack[start + needle.len()..].chars().next();
+        before.is_none_or(|c| c == '/' || c == '-') && after.is_none_or(|c| c == '/' || c == '-')
+    })
+}
+
+/// First token shaped like a generated task id (`task-` + 8 hex digits)
+fn find_task_id(text: &str) -> Option<String> {
+    text.match_indices("task-").find_map(|(start, _)| {
+        let id = text.get(start..start + 13)?;
+        let is_id = id[5..].chars().all(|c| c.is_ascii_hexdigit());
+        (is_id && contains_token(text, id)).then(|| id.to_string())
+    })
+}
+
+/// Branch naming from a template such as `{user}/{type}/{id}-{title-slug}`
+#[derive(Debug, Clone, PartialEq)]
+pub struct BranchNaming {
+    template: String,
+    max_length: usize,
+    user: String,
+}
+
+impl Default for BranchNaming {
+    fn default() -> Self {
+        let config = BranchConfig::default();
+        Self {
+            template: config.template().to_string(),
+            max_length: config.max_length(),
+            user: "hive".into(),
+        }
+    }
+}
+
+impl BranchNaming {
+    /// Naming for the repository at `repo_root` ({user} falls back to git config and $USER)
+    pub fn new(config: &BranchConfig, repo_root: &Path) -> Self {
+        let user = config
+            .user
+            .clone()
+            .or_else(|| {
+                let git_config = git2::Repository::discover(repo_root)
+                    .and_then(|repo| repo.config())
+                    .ok()?;
+                git_config
+                    .get_string("github.user")
+                    .or_else(|_| git_config.get_string("user.name"))
+                    .ok()
+            })
+            .or_else(|| std::env::var("USER").ok())
+            .map(|user| slugify(&user, 32))
+            .filter(|user| !user.is_empty())
+            .unwrap_or_else(|| "hive".into());
+        Self {
+            template: config.template().to_string(),
+            max_length: config.max_length(),
+            user,
+        }
+    }
+
+    /// Render the branch name for a task (sanitized and within the length limit)
+    pub fn render(&self, task_id: &str, title: &str) -> String {
+        self.render_within(task_id, title, self.max_length)
+    }
+
+    /// Render the branch name within `max_length`, never cutting the task id
+    fn render_within(&self, task_id: &str, title: &str, max_length: usize) -> String {
+        let fill = |template: &str, slug: &str| {
+            render_template(
+                template,
+                &[
+                    ("user", &self.user),
+                    ("type", change_type(title)),
+                    ("id", task_id),
+                    ("title-slug", slug),
+                ],
+            )
+        };
+        let truncated = |mut name: String| {
+            if name.len() > max_length {
+                name.truncate(max_length);
+                name = sanitize(&name);
+            }
+            name
+        };
+
+        // The slug gets whatever the rest of the name leaves over
+        let fixed = sanitize(&fill(&self.template, "")).len();
+        let slug = slugify(title, max_length.saturating_sub(fixed + 1));
+        let mut name = truncated(sanitize(&fill(&self.template, &slug)));
+
+        // Too long to keep the id: drop what follows it, then the rest of the template
+        if let Some(end) = self.template.find("{id}").map(|start| start + "{id}".len()) {
+            let id = sanitize(task_id);
+            if !contains_token(&name, &id) {
+                name = truncated(sanitize(&fill(&self.template[..end], "")));
+            }
+            if !contains_token(&name, &id) {
+                name = format!("hive/{}", id);
+            }
+        }
+
+        if !name.is_empty() && git2::Branch::name_is_valid(&name).unwrap_or(false) {
+            name
+        } else {
+            format!("hive/{}", sanitize(task_id))
+        }
+    }
+
+    /// Branch for `task`: the recorded one, or a freshly rendered name.
+    /// Names without the task id get a numeric suffix (within the length limit)
+    /// when `exists` reports a collision.
+    pub fn branch_for(&self, task: &Task, exists: impl Fn(&str) -> bool) -> String {
+        if let Some(branch) = &task.branch {
+            return branch.clone();
+        }
+        let name = self.render(&task.id, &task.title);
+        if self.template.contains("{id}") || !exists(&name) {
+            return name;
+        }
+        (2..)
+            .map(|n| {
+                let suffix = format!("-{}", n);
+                let limit = self.max_length.saturating_sub(suffix.len());
+                format!("{}{}", self.render_within(&task.id, &task.title, limit), suffix)
+            })
+            .find(|candidate| !exists(candidate))
+            .expect("unbounded suffixes")
+    }
+
+    /// Id of the task `branch` was generated for, if the branch looks like one of ours
+    /// (rendered from the template, or the `hive/{id}` fallback)
+    pub fn task_id_in(&self, branch: &str, tasks: &[Task]) -> Option<String> {
+        let rendered = self.template.replace("{user}", &self.user);
+        let prefix = &rendered[..rendered.find('{').unwrap_or(rendered.len())];
+        let rest = branch
+            .strip_prefix(prefix)
+            .or_else(|| branch.strip_prefix("hive/"))?;
+        tasks
+            .iter()
+            .find(|task| contains_token(rest, &task.id))
+            .map(|task| task.id.clone())
+            .or_else(|| find_task_id(rest))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn naming_for(template: &str, max_length: usize) -> BranchNaming {
+        BranchNaming {
+            template: template.into(),
+            max_length,
+            user: "alice".into(),
+        }
+    }
+
+    #[test]
+    fn test_slugify() {
+        assert_eq!(slugify("Fix OAuth login!", 50), "fix-oauth-login");
+        assert_eq!(slugify("  --Hello,   World--  ", 50), "hello-world");
+        assert_eq!(slugify("日本語のタスク", 50), "");
+        assert_eq!(slugify("add rate limiting to api", 12), "add-rate");
+        assert_eq!(slugify("add rate limiting", 8), "add-rate");
+        assert_eq!(slugify("supercalifragilistic", 5), "super");
+    }
+
+    #[test]
+    fn test_change_type() {
+        assert_eq!(change_type("Fix crash on start"), "fix");
+        assert_eq!(change_type("[docs] Update README"), "docs");
+        assert_eq!(change_type("Refactor runner"), "refactor");
+        assert_eq!(change_type("Add login"), "feat");
+        assert_eq!(change_type(""), "feat");
+    }
+
+    #[test]
+    fn test_render() {
+        let naming = naming_for("{user}/{type}/{id}-{title-slug}", 60);
+        assert_eq!(
+            naming.render("task-1a2b3c4d", "Fix OAuth login"),
+            "alice/fix/task-1a2b3c4d-fix-oauth-login"
+        );
+
+        // Empty slug leaves no dangling separator
+        assert_eq!(naming.render("task-1a2b3c4d", "日本語"), "alice/feat/task-1a2b3c4d");
+
+        // Unsafe literals are cleaned up
+        let naming = naming_for("hive..//{id}.lock", 60);
+        assert_eq!(naming.render("task-1", ""), "hive/task-1");
+    }
+
+    #[test]
+    fn test_render_length_limit() {
+        let naming = naming_for("hive/{id}-{title-slug}", 30);
+        let name = naming.render("task-1a2b3c4d", "Add rate limiting to the public API");
+        assert_eq!(name, "hive/task-1a2b3c4d-add-rate");
+        assert!(name.len() <= 30);
+
+        // Literal parts longer than the limit are truncated too
+        let naming = naming_for("{id}-{title-slug}-averyveryverylongsuffix", 20);
+        assert!(naming.render("task-1a2b3c4d", "Title").len() <= 20);
+    }
+
+    #[test]
+    fn test_render_length_limit_keeps_id() {
+        let tasks = vec![{
+            let mut task = Task::new("Title", "");
+            task.id = "task-1a2b3c4d".into();
+            task
+        }];
+
+        // Literal text after the id is cut first
+        let naming = naming_for("{id}-averyveryverylongsuffix", 20);
+        assert_eq!(naming.render("task-1a2b3c4d", "Title"), "task-1a2b3c4d-averyv");
+
+        // Then the rest of the template; the id stays findable for GC
+        let naming = naming_for("{user}/{type}/{id}-{title-slug}", 20);
+        let name = naming.render("task-1a2b3c4d", "Fix OAuth login");
+        assert_eq!(name, "hive/task-1a2b3c4d");
+        assert!(name.len() <= 20);
+        assert_eq!(naming.task_id_in(&name, &tasks).as_deref(), Some("task-1a2b3c4d"));
+    }
+
+    #[test]
+    fn test_branch_for_collisions() {
+        let mut task = Task::new("Fix login", "");
+        let taken = ["alice/fix-login", "alice/fix-login-2"];
+
+        let naming = naming_for("{user}/{title-slug}", 60);
+        assert_eq!(naming.branch_for(&task, |b| taken.contains(&b)), "alice/fix-login-3");
+
+        // Names with the task id are unique; an existing branch is reused
+        let naming = naming_for("{user}/{title-slug}-{id}", 60);
+        let name = naming.branch_for(&task, |_| true);
+        assert_eq!(name, format!("alice/fix-login-{}", task.id));
+
+        // Suffixes stay within the length limit
+        let naming = naming_for("{user}/{title-slug}", 15);
+        let taken = ["alice/fix-login"];
+        let name = naming.branch_for(&task, |b| taken.contains(&b));
+        assert_eq!(name, "alice/fix-2");
+        assert!(name.len() <= 15);
+
+        // Recorded branches win
+        task.branch = Some("custom".into());
+        assert_eq!(naming.branch_for(&task, |_| true), "custom");
+    }
+
+    #[test]
+    fn test_task_id_in() {
+        let mut task = Task::new("Live", "");
+        task.id = "task-live".into();
+        let tasks = vec![task];
+        let naming = naming_for("{user}/{type}/{id}-{title-slug}", 60);
+
+        assert_eq!(
+            naming.task_id_in("alice/feat/task-live-add-login", &tasks).as_deref(),
+            Some("task-live")
+        );
+        assert_eq!(
+            naming.task_id_in("alice/fix/task-0a1b2c3d-old-rerun-20260101", &tasks).as_deref(),
+            Some("task-0a1b2c3d")
+        );
+        assert_eq!(naming.task_id_in("bob/feat/task-0a1b2c3d", &tasks), None);
+        assert_eq!(naming.task_id_in("alice/feat/no-id-here", &tasks), None);
+        assert_eq!(naming.task_id_in("alice/feat/task-livery", &tasks), None);
+    }
+}
@@ -0,0 +1,512 @@
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+
+/// How a task branch is brought up to date with its base
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SyncStrategy {
+    /// Rebase task commits onto the base branch
+    #[default]
+    Rebase,
+    /// Merge the base branch into the task branch
+    Merge,
+}
+
+/// Sync with base settings
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct SyncConfig {
+    /// Rebase or merge
+    #[serde(default)]
+    pub strategy: SyncStrategy,
+    /// Sync automatically before merging a task
+    #[serde(default)]
+    pub before_merge: bool,
+}
+
+/// How a task branch is merged into its target branch
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MergeStrategy {
+    /// Always create a merge commit (--no-ff)
+    #[default]
+    Merge,
+    /// Squash all task commits into a single commit
+    Squash,
+    /// Rebase task commits onto the target, then fast-forward
+    Rebase,
+    /// Fast-forward only; fail if the branches diverged
+    FfOnly,
+}
+
+impl MergeStrategy {
+    /// Get display name for the strategy
+    pub fn display_name(&self) -> &'static str {
+        match self {
+            Self::Merge => "merge commit",
+            Self::Squash => "squash",
+            Self::Rebase => "rebase",
+            Self::FfOnly => "fast-forward",
+        }
+    }
+}
+
+/// Default commit message template for merge/squash commits
+pub const DEFAULT_MERGE_MESSAGE: &str = "{title} ({id})\n\n{overview}\n\n{pr_url}";
+
+/// Merge settings
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct MergeConfig {
+    /// Merge strategy
+    #[serde(default)]
+    pub strategy: MergeStrategy,
+    /// Commit message template ({title}, {id}, {overview}, {pr_url}, {branch}, {base})
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub message_template: Option<String>,
+}
+
+impl MergeConfig {
+    /// Commit message template in effect
+    pub fn message_template(&self) -> &str {
+        self.message_template
+            .as_deref()
+            .unwrap_or(DEFAULT_MERGE_MESSAGE)
+    }
+}
+
+/// Default branch name template
+pub const DEFAULT_BRANCH_TEMPLATE: &str = "hive/{id}-{title-slug}";
+
+/// Default maximum branch name length
+pub const DEFAULT_BRANCH_MAX_LENGTH: usize = 60;
+
+/// Task branch naming settings
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct BranchConfig {
+    /// Name template ({user}, {type}, {id}, {title-slug})
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub template: Option<String>,
+    /// Maximum length; the title slug is shortened first
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_length: Option<usize>,
+    /// Value for {user} (defaults to git `github.user`, `user.name`, then $USER)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub user: Option<String>,
+}
+
+impl BranchConfig {
+    /// Name template in effect
+    pub fn template(&self) -> &str {
+        self.template.as_deref().unwrap_or(DEFAULT_BRANCH_TEMPLATE)
+    }
+
+    /// Length limit in effect
+    pub fn max_length(&self) -> usize {
+        self.max_length.unwrap_or(DEFAULT_BRANCH_MAX_LENGTH)
+    }
+}
+
+/// Default commit message template for changes the executor left uncommitted
+pub const DEFAULT_COMMIT_MESSAGE: &str = "{title}\n\n{overview}\n\n{files}\n\nTask: {id}";
+
+/// Settings for committing changes an executor left uncommitted
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AutoCommitConfig {
+    /// Commit leftovers after a successful executor run
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+    /// Commit message template ({title}, {id}, {overview}, {files}, {branch}, {base})
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub message_template: Option<String>,
+    /// Agent asked to summarize the diff into a message (template is the fallback)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub agent: Option<String>,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+impl Default for AutoCommitConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            message_template: None,
+            agent: None,
+        }
+    }
+}
+
+impl AutoCommitConfig {
+    /// Commit message template in effect
+    pub fn message_template(&self) -> &str {
+        self.message_template
+            .as_deref()
+            .unwrap_or(DEFAULT_COMMIT_MESSAGE)
+    }
+}
+
+/// Worktree bootstrap settings (applied when a task worktree is created)
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct BootstrapConfig {
+    /// Untracked files or directories copied from the repository root (e.g. `.env`)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub copy: Vec<String>,
+    /// Paths symlinked to the repository root's copy (e.g. `node_modules`)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub symlink: Vec<String>,
+    /// Environment for setup commands and agent runs ({repo}, {worktree} placeholders)
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub env: BTreeMap<String, String>,
+    /// Shell commands run in the new worktree, in order (stops at the first failure)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub setup: Vec<String>,
+}
+
+/// Default minimum entropy (bits per character) of a token flagged as a secret
+pub const DEFAULT_ENTROPY_THRESHOLD: f64 = 4.0;
+
+/// Secret scanning of task diffs (before PRs and merges)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SecretScanConfig {
+    /// Scan before opening PRs and merging
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+    /// Regexes flagged in added lines (in addition to the built-in key formats)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub deny_patterns: Vec<String>,
+    /// Regexes of lines never flagged (e.g. placeholders)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub allow_patterns: Vec<String>,
+    /// Path prefixes not scanned (e.g. test fixtures)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub ignore_paths: Vec<String>,
+    /// Minimum entropy (bits per character) of a token flagged as a secret
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub entropy_threshold: Option<f64>,
+}
+
+impl Default for SecretScanConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            deny_patterns: Vec::new(),
+            allow_patterns: Vec::new(),
+            ignore_paths: Vec::new(),
+            entropy_threshold: None,
+        }
+    }
+}
+
+impl SecretScanConfig {
+    /// Entropy threshold in effect
+    pub fn entropy_threshold(&self) -> f64 {
+        self.entropy_threshold.unwrap_or(DEFAULT_ENTROPY_THRESHOLD)
+    }
+}
+
+/// Policy rule (see `PolicyConfig`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PolicyRule {
+    ProtectedPaths,
+    MaxFileSize,
+    NoNewBinaries,
+    MaxChangedFiles,
+    RequireTests,
+}
+
+/// Default paths of source files for `require_tests`
+pub const DEFAULT_SOURCE_PATHS: &[&str] = &["src/"];
+
+/// Default paths of test files for `require_tests`
+pub const DEFAULT_TEST_PATHS: &[&str] = &[
+    "tests/",
+    "test/",
+    "*_test.*",
+    "test_*",
+    "*.test.*",
+    "*.spec.*",
+];
+
+/// Rules task changes are checked against before Review and merge.
+/// Paths are gitignore-style globs (`*`, `**`; without a slash they match at any depth,
+/// a trailing slash matches a directory).
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct PolicyConfig {
+    /// Paths the executor must not change (e.g. CI config, migrations, lockfiles)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub protected_paths: Vec<String>,
+    /// Largest allowed size of an added or modified file in bytes
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_file_size: Option<u64>,
+    /// Reject newly added binary files
+    #[serde(default)]
+    pub no_new_binaries: bool,
+    /// Largest allowed number of changed files
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_changed_files: Option<usize>,
+    /// Changes to source files must come with changes to test files
+    #[serde(default)]
+    pub require_tests: bool,
+    /// Source file paths for `require_tests` (default `src/`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source_paths: Option<Vec<String>>,
+    /// Test file paths for `require_tests` (default: `tests/` directories and test/spec file names)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub test_paths: Option<Vec<String>>,
+    /// Rules reported as warnings instead of errors
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub warn: Vec<PolicyRule>,
+}
+
+impl PolicyConfig {
+    /// Source paths in effect
+    pub fn source_paths(&self) -> Vec<String> {
+        self.source_paths
+            .clone()
+            .unwrap_or_else(|| DEFAULT_SOURCE_PATHS.iter().map(|p| p.to_string()).collect())
+    }
+
+    /// Test paths in effect
+    pub fn test_paths(&self) -> Vec<String> {
+        self.test_paths
+            .clone()
+            .unwrap_or_else(|| DEFAULT_TEST_PATHS.iter().map(|p| p.to_string()).collect())
+    }
+
+    /// Violations of `rule` are warnings rather than errors
+    pub fn is_warning(&self, rule: PolicyRule) -> bool {
+        self.warn.contains(&rule)
+    }
+}
+
+/// Replace `{name}` placeholders and drop blank lines left by empty values
+pub fn render_template(template: &str, vars: &[(&str, &str)]) -> String {
+    let mut rendered = template.to_string();
+    for (name, value) in vars {
+        rendered = rendered.replace(&format!("{{{}}}", name), value);
+    }
+
+    // Collapse runs of blank lines and trim
+    let mut lines: Vec<&str> = Vec::new();
+    for line in rendered.lines() {
+        let line = line.trim_end();
+        if line.is_empty() && lines.last().is_none_or(|l| l.is_empty()) {
+            continue;
+        }
+        lines.push(line);
+    }
+    while lines.last().is_some_and(|l| l.is_empty()) {
+        lines.pop();
+    }
+    lines.join("\n")
+}
+
+/// Default free space (in MiB) required to create a worktree
+pub const DEFAULT_MIN_FREE_SPACE_MB: u64 = 1024;
+
+/// Git configuration (`git` section of .hive/config.json)
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct GitConfig {
+    /// Base branch for new tasks (auto-detected when unset)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub base_branch: Option<String>,
+    /// Free space (in MiB) required to create a worktree
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min_free_space_mb: Option<u64>,
+    /// Sync with base settings
+    #[serde(default)]
+    pub sync: SyncConfig,
+    /// Merge settings
+    #[serde(default)]
+    pub merge: MergeConfig,
+    /// Auto-commit of executor leftovers
+    #[serde(default)]
+    pub auto_commit: AutoCommitConfig,
+    /// Task branch naming
+    #[serde(default)]
+    pub branch: BranchConfig,
+    /// Worktree bootstrap
+    #[serde(default)]
+    pub bootstrap: BootstrapConfig,
+    /// Secret scanning of task diffs
+    #[serde(default)]
+    pub secrets: SecretScanConfig,
+    /// Policy checks on task changes
+    #[serde(default)]
+    pub policy: PolicyConfig,
+}
+
+impl GitConfig {
+    /// Load from config file
+    pub fn load(hive_dir: &Path) -> Result<Self> {
+        let config_path = hive_dir.join("config.json");
+        if config_path.exists() {
+            let content = std::fs::read_to_string(&config_path)
+                .context("Failed to read config.json")?;
+            let config: serde_json::Value = serde_json::from_str(&content)
+                .context("Failed to parse config.json")?;
+
+            // Load git section if exists
+            if let Some(git) = config.get("git") {
+                return serde_json::from_value(git.clone())
+                    .context("Failed to parse git config");
+            }
+        }
+        Ok(Self::default())
+    }
+
+    /// Free space (in bytes) required to create a worktree
+    pub fn min_free_space(&self) -> u64 {
+        self.min_free_space_mb.unwrap_or(DEFAULT_MIN_FREE_SPACE_MB) * 1024 * 1024
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_load_missing_config() {
+        let temp_dir = TempDir::new().unwrap();
+        let config = GitConfig::load(temp_dir.path()).unwrap();
+        assert_eq!(config, GitConfig::default());
+    }
+
+    #[test]
+    fn test_load_base_branch() {
+        let temp_dir = TempDir::new().unwrap();
+        let config_json = r#"{
+            "orchestrator": { "default_planner": "gemini" },
+            "git": { "base_branch": "develop" }
+        }"#;
+        std::fs::write(temp_dir.path().join("config.json"), config_json).unwrap();
+
+        let config = GitConfig::load(temp_dir.path()).unwrap();
+        assert_eq!(config.base_branch.as_deref(), Some("develop"));
+        assert_eq!(config.sync, SyncConfig::default());
+        assert_eq!(config.min_free_space(), DEFAULT_MIN_FREE_SPACE_MB * 1024 * 1024);
+    }
+
+    #[test]
+    fn test_load_min_free_space() {
+        let temp_dir = TempDir::new().unwrap();
+        let config_json = r#"{ "git": { "min_free_space_mb": 256 } }"#;
+        std::fs::write(temp_dir.path().join("config.json"), config_json).unwrap();
+
+        let config = GitConfig::load(temp_dir.path()).unwrap();
+        assert_eq!(config.min_free_space(), 256 * 1024 * 1024);
+    }
+
+    #[test]
+    fn test_load_merge_config() {
+        let temp_dir = TempDir::new().unwrap();
+        let config_json = r#"{ "git": { "merge": { "strategy": "ff_only", "message_template": "{id}" } } }"#;
+        std::fs::write(temp_dir.path().join("config.json"), config_json).unwrap();
+
+        let config = GitConfig::load(temp_dir.path()).unwrap();
+        assert_eq!(config.merge.strategy, MergeStrategy::FfOnly);
+        assert_eq!(config.merge.message_template(), "{id}");
+        assert_eq!(MergeConfig::default().message_template(), DEFAULT_MERGE_MESSAGE);
+    }
+
+    #[test]
+    fn test_load_bootstrap_config() {
+        let temp_dir = TempDir::new().unwrap();
+        let config_json = r#"{ "git": { "bootstrap": {
+            "copy": [".env"],
+            "symlink": ["node_modules"],
+            "env": { "CARGO_TARGET_DIR": "{repo}/target" },
+            "setup": ["npm ci"]
+        } } }"#;
+        std::fs::write(temp_dir.path().join("config.json"), config_json).unwrap();
+
+        let config = GitConfig::load(temp_dir.path()).unwrap();
+        assert_eq!(config.bootstrap.copy, vec![".env"]);
+        assert_eq!(config.bootstrap.symlink, vec!["node_modules"]);
+        assert_eq!(config.bootstrap.env["CARGO_TARGET_DIR"], "{repo}/target");
+        assert_eq!(config.bootstrap.setup, vec!["npm ci"]);
+        assert_eq!(GitConfig::default().bootstrap, BootstrapConfig::default());
+    }
+
+    #[test]
+    fn test_load_policy_config() {
+        let temp_dir = TempDir::new().unwrap();
+        let config_json = r#"{ "git": { "policy": {
+            "protected_paths": [".github/", "*.lock"],
+            "max_changed_files": 20,
+            "require_tests": true,
+            "warn": ["require_tests"]
+        } } }"#;
+        std::fs::write(temp_dir.path().join("config.json"), config_json).unwrap();
+
+        let config = GitConfig::load(temp_dir.path()).unwrap();
+        assert_eq!(config.policy.protected_paths, vec![".github/", "*.lock"]);
+        assert_eq!(config.policy.max_changed_files, Some(20));
+        assert!(config.policy.is_warning(PolicyRule::RequireTests));
+        assert!(!config.policy.is_warning(PolicyRule::ProtectedPaths));
+        assert_eq!(config.policy.source_paths(), vec!["src/"]);
+        assert_eq!(config.policy.test_paths().len(), DEFAULT_TEST_PATHS.len());
+    }
+
+    #[test]
+    fn test_render_template() {
+        let message = render_template(
+            DEFAULT_MERGE_MESSAGE,
+            &[
+                ("title", "Add login"),
+                ("id", "task-1"),
+                ("overview", "OAuth login flow"),
+                ("pr_url", ""),
+            ],
+        );
+        assert_eq!(message, "Add login (task-1)\n\nOAuth login flow");
+
+        let message = render_template("{title}\n\n\n{unknown}", &[("title", "T")]);
+        assert_eq!(message, "T\n\n{unknown}");
+    }
+
+    #[test]
+    fn test_load_auto_commit_config() {
+        let temp_dir = TempDir::new().unwrap();
+        let config_json = r#"{ "git": { "auto_commit": { "agent": "gemini" } } }"#;
+        std::fs::write(temp_dir.path().join("config.json"), config_json).unwrap();
+
+        let config = GitConfig::load(temp_dir.path()).unwrap();
+        assert!(config.auto_commit.enabled);
+        assert_eq!(config.auto_commit.agent.as_deref(), Some("gemini"));
+        assert_eq!(config.auto_commit.message_template(), DEFAULT_COMMIT_MESSAGE);
+
+        let config_json = r#"{ "git": { "auto_commit": { "enabled": false } } }"#;
+        std::fs::write(temp_dir.path().join("config.json"), config_json).unwrap();
+        assert!(!GitConfig::load(temp_dir.path()).unwrap().auto_commit.enabled);
+    }
+
+    #[test]
+    fn test_load_branch_config() {
+        let temp_dir = TempDir::new().unwrap();
+        let config_json = r#"{ "git": { "branch": { "template": "{user}/{type}/{id}-{title-slug}", "user": "paveg" } } }"#;
+        std::fs::write(temp_dir.path().join("config.json"), config_json).unwrap();
+
+        let config = GitConfig::load(temp_dir.path()).unwrap();
+        assert_eq!(config.branch.template(), "{user}/{type}/{id}-{title-slug}");
+        assert_eq!(config.branch.user.as_deref(), Some("paveg"));
+        assert_eq!(config.branch.max_length(), DEFAULT_BRANCH_MAX_LENGTH);
+        assert_eq!(BranchConfig::default().template(), DEFAULT_BRANCH_TEMPLATE);
+    }
+
+    #[test]
+    fn test_load_sync_config() {
+        let temp_dir = TempDir::new().unwrap();
+        let config_json = r#"{ "git": { "sync": { "strategy": "merge", "before_merge": true } } }"#;
+        std::fs::write(temp_dir.path().join("config.json"), config_json).unwrap();
+
+        let config = GitConfig::load(temp_dir.path()).unwrap();
+        assert_eq!(config.sync.strategy, SyncStrategy::Merge);
+        assert!(config.sync.before_merge);
+    }
+}
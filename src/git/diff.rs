@@ -0,0 +1,340 @@
+use std::ops::Range;
+use std::path::Path;
+
+use git2::{Delta, DiffFindOptions, Patch};
+
+use super::error::Result;
+use super::validator::{merge_base_tree, open_repo};
+use super::worktree::WorktreeManager;
+
+/// How a file changed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChangeKind {
+    Added,
+    Modified,
+    Deleted,
+    Renamed,
+}
+
+impl ChangeKind {
+    /// One-letter marker (as in `git status --short`)
+    pub fn symbol(&self) -> char {
+        match self {
+            Self::Added => 'A',
+            Self::Modified => 'M',
+            Self::Deleted => 'D',
+            Self::Renamed => 'R',
+        }
+    }
+}
+
+/// Kind of a line within a hunk
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LineKind {
+    Context,
+    Added,
+    Removed,
+}
+
+/// Single diff line with its old/new line numbers
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DiffLine {
+    pub kind: LineKind,
+    pub old_line: Option<u32>,
+    pub new_line: Option<u32>,
+    pub content: String,
+}
+
+/// Hunk (`@@ ... @@` header and its lines)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DiffHunk {
+    pub header: String,
+    pub lines: Vec<DiffLine>,
+}
+
+/// Changes to one file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileDiff {
+    pub path: String,
+    /// Previous path of a renamed file
+    pub old_path: Option<String>,
+    pub kind: ChangeKind,
+    pub additions: usize,
+    pub deletions: usize,
+    pub binary: bool,
+    pub hunks: Vec<DiffHunk>,
+}
+
+impl FileDiff {
+    /// Path for display ("old → new" for renames)
+    pub fn display_path(&self) -> String {
+        match &self.old_path {
+            Some(old_path) => format!("{} → {}", old_path, self.path),
+            None => self.path.clone(),
+        }
+    }
+}
+
+/// Lines longer than this (in tokens) are not compared word by word
+const MAX_WORD_DIFF_TOKENS: usize = 200;
+
+/// Split a line into words, whitespace runs and single punctuation characters (byte ranges)
+fn tokenize(line: &str) -> Vec<Range<usize>> {
+    let mut tokens: Vec<Range<usize>> = Vec::new();
+    let class = |c: char| {
+        if c.is_alphanumeric() || c == '_' {
+            0
+        } else if c.is_whitespace() {
+            1
+        } else {
+            2
+        }
+    };
+    let mut previous = None;
+    for (start, c) in line.char_indices() {
+        let current = class(c);
+        match tokens.last_mut() {
+            Some(token) if previous == Some(current) && current != 2 => token.end = start + c.len_utf8(),
+            _ => tokens.push(start..start + c.len_utf8()),
+        }
+        previous = Some(current);
+    }
+    tokens
+}
+
+/// Merge adjacent byte ranges
+fn merge_ranges(ranges: Vec<Range<usize>>) -> Vec<Range<usize>> {
+    let mut merged: Vec<Range<usize>> = Vec::new();
+    for range in ranges {
+        match merged.last_mut() {
+            Some(last) if last.end == range.start => last.end = range.end,
+            _ => merged.push(range),
+        }
+    }
+    merged
+}
+
+/// Byte ranges that changed between two versions of a line, at word granularity.
+/// Both are empty when the lines have no words in common (the whole line changed).
+pub fn word_diff(old: &str, new: &str) -> (Vec<Range<usize>>, Vec<Range<usize>>) {
+    let old_tokens = tokenize(old);
+    let new_tokens = tokenize(new);
+    if old_tokens.len() > MAX_WORD_DIFF_TOKENS || new_tokens.len() > MAX_WORD_DIFF_TOKENS {
+        return (Vec::new(), Vec::new());
+    }
+
+    // Longest common subsequence of tokens
+    let (n, m) = (old_tokens.len(), new_tokens.len());
+    let mut lcs = vec![vec![0u16; m + 1]; n + 1];
+    for i in (0..n).rev() {
+        for j in (0..m).rev() {
+            lcs[i][j] = if old[old_tokens[i].clone()] == new[new_tokens[j].clone()] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+
+    let (mut i, mut j) = (0, 0);
+    let (mut removed, mut added) = (Vec::new(), Vec::new());
+    let mut common_words = false;
+    while i < n || j < m {
+        if i < n && j < m && old[old_tokens[i].clone()] == new[new_tokens[j].clone()] {
+            common_words |= !old[old_tokens[i].clone()].trim().is_empty();
+            i += 1;
+            j += 1;
+        } else if j < m && (i == n || lcs[i][j + 1] >= lcs[i + 1][j]) {
+            added.push(new_tokens[j].clone());
+            j += 1;
+        } else {
+            removed.push(old_tokens[i].clone());
+            i += 1;
+        }
+    }
+
+    if !common_words {
+        return (Vec::new(), Vec::new());
+    }
+    (merge_ranges(removed), merge_ranges(added))
+}
+
+impl WorktreeManager {
+    /// Per-file diff of a worktree (tracked files) since it branched off `base_branch`,
+    /// with renames detected
+    pub fn file_diffs(&self, worktree_path: &Path, base_branch: &str) -> Result<Vec<FileDiff>> {
+        let repo = open_repo(worktree_path)?;
+        let base_tree = merge_base_tree(&repo, base_branch)?;
+        let mut diff = repo.diff_tree_to_workdir_with_index(Some(&base_tree), None)?;
+        diff.find_similar(Some(DiffFindOptions::new().renames(true)))?;
+
+        let mut files = Vec::new();
+        for index in 0..diff.deltas().len() {
+            let Some(delta) = diff.get_delta(index) else {
+                continue;
+            };
+            let kind = match delta.status() {
+                Delta::Added | Delta::Untracked => ChangeKind::Added,
+                Delta::Deleted => ChangeKind::Deleted,
+                Delta::Renamed => ChangeKind::Renamed,
+                _ => ChangeKind::Modified,
+            };
+            let new_path = delta.new_file().path().map(|p| p.to_string_lossy().to_string());
+            let old_path = delta.old_file().path().map(|p| p.to_string_lossy().to_string());
+            let path = new_path.clone().or(old_path.clone()).unwrap_or_default();
+            let mut file = FileDiff {
+                old_path: (kind == ChangeKind::Renamed).then_some(old_path).flatten(),
+                path,
+                kind,
+                additions: 0,
+                deletions: 0,
+                binary: delta.flags().is_binary(),
+                hunks: Vec::new(),
+            };
+
+            if let Some(patch) = Patch::from_diff(&diff, index)? {
+                let (_, additions, deletions) = patch.line_stats()?;
+                file.additions = additions;
+                file.deletions = deletions;
+                for hunk_index in 0..patch.num_hunks() {
+                    let (hunk, line_count) = patch.hunk(hunk_index)?;
+                    let mut lines = Vec::with_capacity(line_count);
+                    for line_index in 0..line_count {
+                        let line = patch.line_in_hunk(hunk_index, line_index)?;
+                        let kind = match line.origin() {
+                            '+' => LineKind::Added,
+                            '-' => LineKind::Removed,
+                            ' ' => LineKind::Context,
+                            // "\ No newline at end of file" markers
+                            _ => continue,
+                        };
+                        lines.push(DiffLine {
+                            kind,
+                            old_line: line.old_lineno(),
+                            new_line: line.new_lineno(),
+                            content: String::from_utf8_lossy(line.content())
+                                .trim_end_matches(['\n', '\r'])
+                                .to_string(),
+                        });
+                    }
+                    file.hunks.push(DiffHunk {
+                        header: String::from_utf8_lossy(hunk.header()).trim_end().to_string(),
+                        lines,
+                    });
+                }
+            }
+            files.push(file);
+        }
+        Ok(files)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::git::test_support::{git, init_repo};
+    use crate::git::GitError;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_word_diff() {
+        let (removed, added) = word_diff("let count = items.len();", "let total = items.len() + 1;");
+        assert_eq!(&"let count = items.len();"[removed[0].clone()], "count");
+        assert_eq!(removed.len(), 1);
+        assert_eq!(
+            added.iter().map(|r| &"let total = items.len() + 1;"[r.clone()]).collect::<Vec<_>>(),
+            vec!["total", " + 1"]
+        );
+
+        // Nothing in common: no word-level emphasis
+        assert_eq!(word_diff("foo", "bar"), (Vec::new(), Vec::new()));
+        // Identical lines
+        assert_eq!(word_diff("same", "same"), (Vec::new(), Vec::new()));
+        // Multi-byte characters keep valid boundaries
+        let (removed, added) = word_diff("名前 = 1", "名前 = 2");
+        assert_eq!((&"名前 = 1"[removed[0].clone()], &"名前 = 2"[added[0].clone()]), ("1", "2"));
+    }
+
+    #[test]
+    fn test_file_diffs() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        init_repo(root);
+        let body: String = (1..=20).map(|n| format!("line {}\n", n)).collect();
+        std::fs::write(root.join("edit.txt"), &body).unwrap();
+        std::fs::write(root.join("gone.txt"), "bye\n").unwrap();
+        std::fs::write(root.join("old.txt"), "same content for rename detection\n".repeat(5)).unwrap();
+        git(root, &["add", "."]);
+        git(root, &["commit", "-m", "Add files"]);
+
+        let edited = body.replace("line 2\n", "line two\n").replace("line 19\n", "");
+        std::fs::write(root.join("edit.txt"), edited).unwrap();
+        std::fs::write(root.join("new.txt"), "hello\n").unwrap();
+        git(root, &["add", "new.txt"]);
+        git(root, &["rm", "-q", "gone.txt"]);
+        git(root, &["mv", "old.txt", "renamed.txt"]);
+
+        let manager = WorktreeManager::new(root.to_path_buf(), root.join(".hive"));
+        let files = manager.file_diffs(root, "HEAD").unwrap();
+        let summary: Vec<(char, String, usize, usize)> = files
+            .iter()
+            .map(|f| (f.kind.symbol(), f.display_path(), f.additions, f.deletions))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ('M', "edit.txt".to_string(), 1, 2),
+                ('D', "gone.txt".to_string(), 0, 1),
+                ('A', "new.txt".to_string(), 1, 0),
+                ('R', "old.txt → renamed.txt".to_string(), 0, 0),
+            ]
+        );
+
+        // Two separate hunks with line numbers
+        let edit = &files[0];
+        assert_eq!(edit.hunks.len(), 2);
+        assert!(edit.hunks[0].header.starts_with("@@ -1,"));
+        let added = edit.hunks[0]
+            .lines
+            .iter()
+            .find(|l| l.kind == LineKind::Added)
+            .unwrap();
+        assert_eq!(added.content, "line two");
+        assert_eq!(added.new_line, Some(2));
+        let removed = edit.hunks[1]
+            .lines
+            .iter()
+            .find(|l| l.kind == LineKind::Removed)
+            .unwrap();
+        assert_eq!((removed.content.as_str(), removed.old_line), ("line 19", Some(19)));
+
+        assert!(matches!(
+            manager.file_diffs(root, "missing"),
+            Err(GitError::RevisionNotFound(_))
+        ));
+    }
+
+    #[test]
+    fn test_file_diffs_ignore_later_base_commits() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        let base = init_repo(root);
+        git(root, &["checkout", "-q", "-b", "task"]);
+        std::fs::write(root.join("task.txt"), "task\n").unwrap();
+        git(root, &["add", "."]);
+        git(root, &["commit", "-m", "Task change"]);
+
+        // The base moves on after the task branched off
+        git(root, &["checkout", "-q", &base]);
+        std::fs::write(root.join("base.txt"), "base\n").unwrap();
+        git(root, &["add", "."]);
+        git(root, &["commit", "-m", "Base change"]);
+        git(root, &["checkout", "-q", "task"]);
+
+        let manager = WorktreeManager::new(root.to_path_buf(), root.join(".hive"));
+        let files = manager.file_diffs(root, &base).unwrap();
+        let paths: Vec<String> = files.iter().map(|f| f.display_path()).collect();
+        assert_eq!(paths, vec!["task.txt".to_string()]);
+        assert!(!manager.get_diff(root, &base).unwrap().contains("base"));
+    }
+}
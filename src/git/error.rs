@@ -0,0 +1,39 @@
+use std::path::PathBuf;
+
+use thiserror::Error;
+
+/// Git operation error
+#[derive(Debug, Error)]
+pub enum GitError {
+    #[error("Not a git repository: {}", .0.display())]
+    NotARepository(PathBuf),
+    #[error("Branch '{0}' already exists")]
+    BranchExists(String),
+    #[error("Revision '{0}' not found")]
+    RevisionNotFound(String),
+    #[error("HEAD is detached")]
+    DetachedHead,
+    #[error("Worktree already exists: {}", .0.display())]
+    WorktreeExists(PathBuf),
+    #[error("No worktree registered at {}", .0.display())]
+    WorktreeNotFound(PathBuf),
+    #[error("Worktree has uncommitted changes: {}", .0.display())]
+    DirtyWorktree(PathBuf),
+    #[error("Merge conflicts in: {}", .0.join(", "))]
+    MergeConflicts(Vec<String>),
+    #[error("Cannot fast-forward '{0}': branches have diverged")]
+    NotFastForward(String),
+    #[error("Invalid pattern '{pattern}': {message}")]
+    InvalidPattern { pattern: String, message: String },
+    #[error("git {command} failed: {stderr}")]
+    Command { command: String, stderr: String },
+    #[error("git {command} timed out after {secs}s")]
+    Timeout { command: String, secs: u64 },
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+    #[error(transparent)]
+    Git(#[from] git2::Error),
+}
+
+/// Result of git operations
+pub type Result<T> = std::result::Result<T, GitError>;
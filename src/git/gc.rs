@@ -0,0 +1,419 @@
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+use git2::{BranchType, Repository, WorktreePruneOptions};
+
+use super::error::Result;
+use super::validator::{open_repo, GitValidator};
+use super::worktree::WorktreeManager;
+use crate::task::{RunRecord, RunStatus, Task, TaskStatus};
+
+/// Leftover worktree or branch no active task uses
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Orphan {
+    /// Directory in .hive/worktrees (registered or not) without an active task
+    Worktree {
+        path: PathBuf,
+        /// `git worktree` name if registered
+        admin_name: Option<String>,
+        reason: String,
+    },
+    /// `git worktree` entry whose directory no longer exists
+    StaleWorktree { admin_name: String, path: PathBuf },
+    /// Task branch that is no longer needed
+    Branch {
+        name: String,
+        reason: String,
+        /// Whether the branch tip is contained in its base branch
+        merged: bool,
+    },
+}
+
+impl Orphan {
+    /// One-line description
+    pub fn describe(&self) -> String {
+        match self {
+            Self::Worktree { path, reason, .. } => {
+                format!("worktree {} ({})", path.display(), reason)
+            }
+            Self::StaleWorktree { admin_name, path } => {
+                format!("stale worktree entry {} ({} missing)", admin_name, path.display())
+            }
+            Self::Branch {
+                name,
+                reason,
+                merged: true,
+            } => format!("branch {} ({})", name, reason),
+            Self::Branch { name, reason, .. } => {
+                format!("branch {} ({}, not merged)", name, reason)
+            }
+        }
+    }
+
+    /// Branches with commits their base does not contain are only deleted on request
+    pub fn is_unmerged(&self) -> bool {
+        matches!(self, Self::Branch { merged: false, .. })
+    }
+}
+
+/// Canonicalize when possible (paths of removed directories stay as they are)
+fn canonical(path: &Path) -> PathBuf {
+    path.canonicalize().unwrap_or_else(|_| path.to_path_buf())
+}
+
+/// Task a worktree/branch is named after
+fn owner<'t>(tasks: &'t [Task], name: &str) -> Option<&'t Task> {
+    tasks
+        .iter()
+        .find(|t| name == t.id || name.starts_with(&format!("{}-", t.id)))
+}
+
+/// Why a worktree/branch named after a task is no longer needed
+fn orphan_reason(tasks: &[Task], name: &str) -> String {
+    match owner(tasks, name) {
+        None => "task deleted".into(),
+        Some(task) if task.status == TaskStatus::Done => "task done".into(),
+        Some(_) => "finished rerun".into(),
+    }
+}
+
+/// Whether `base` contains `branch`: its tip, or (after a squash or rebase merge) all of
+/// its changes, so merging it would leave the base tree as it is. False when either
+/// does not resolve.
+fn is_merged(repo: &Repository, branch: &str, base: &str) -> bool {
+    let tip = |name: &str| repo.revparse_single(name).and_then(|object| object.peel_to_commit());
+    let (Ok(branch), Ok(base)) = (tip(branch), tip(base)) else {
+        return false;
+    };
+    if branch.id() == base.id() || repo.graph_descendant_of(base.id(), branch.id()).unwrap_or(false) {
+        return true;
+    }
+    repo.merge_commits(&base, &branch, None)
+        .ok()
+        .filter(|index| !index.has_conflicts())
+        .and_then(|mut index| index.write_tree_to(repo).ok())
+        .is_some_and(|tree| tree == base.tree_id())
+}
+
+impl WorktreeManager {
+    /// Cross-reference registered worktrees, .hive/worktrees and task branches with `tasks`.
+    /// Worktrees and branches of tasks that are not Done, and the branches they are based
+    /// on, are kept. Branches are checked against their task's base (`default_base` when
+    /// the task has none or was deleted) to tell whether they were merged.
+    pub fn find_orphans(&self, tasks: &[Task], default_base: &str) -> Result<Vec<Orphan>> {
+        let repo = open_repo(&self.repo_root)?;
+        let worktree_dir = canonical(&self.worktree_dir);
+
+        let active: Vec<&Task> = tasks.iter().filter(|t| t.status != TaskStatus::Done).collect();
+        // Reruns still running keep their own worktree and branch
+        let running_reruns: Vec<&RunRecord> = tasks
+            .iter()
+            .flat_map(|t| &t.runs)
+            .filter(|r| r.status == RunStatus::Running && r.rerun_of.is_some())
+            .collect();
+        let live_worktrees: HashSet<PathBuf> = active
+            .iter()
+            .filter_map(|t| t.worktree.as_deref())
+            .chain(running_reruns.iter().map(|r| r.working_dir.as_str()))
+            .map(|w| canonical(&self.repo_root.join(w)))
+            .collect();
+        // Stacked tasks are based on their parent's branch
+        let live_branches: HashSet<&str> = active
+            .iter()
+            .flat_map(|t| [t.branch.as_deref(), t.base_branch.as_deref()])
+            .chain(running_reruns.iter().map(|r| r.branch.as_deref()))
+            .flatten()
+            .collect();
+
+        let mut orphans = Vec::new();
+        let mut registered = HashSet::new();
+        let mut checked_out = HashSet::new();
+
+        for worktree in GitValidator::new(self.repo_root.clone()).list_worktrees()? {
+            let path = canonical(&worktree.path);
+            registered.insert(path.clone());
+
+            if let (true, Some(admin_name)) = (worktree.prunable, &worktree.name) {
+                orphans.push(Orphan::StaleWorktree {
+                    admin_name: admin_name.clone(),
+                    path,
+                });
+                continue;
+            }
+
+            if worktree.name.is_some()
+                && path.starts_with(&worktree_dir)
+                && !live_worktrees.contains(&path)
+            {
+                let dir_name = path.file_name().unwrap_or_default().to_string_lossy().to_string();
+                orphans.push(Orphan::Worktree {
+                    reason: orphan_reason(tasks, &dir_name),
+                    path,
+                    admin_name: worktree.name.clone(),
+                });
+            } else if let Some(branch) = worktree.branch {
+                checked_out.insert(branch);
+            }
+        }
+
+        // Directories left behind without a `git worktree` entry
+        if let Ok(entries) = std::fs::read_dir(&worktree_dir) {
+            for entry in entries.flatten() {
+                let path = canonical(&entry.path());
+                if path.is_dir() && !registered.contains(&path) && !live_worktrees.contains(&path) {
+                    orphans.push(Orphan::Worktree {
+                        path,
+                        admin_name: None,
+                        reason: "not a registered worktree".into(),
+                    });
+                }
+            }
+        }
+
+        let mut branches = Vec::new();
+        for branch in repo.branches(Some(BranchType::Local))? {
+            let (branch, _) = branch?;
+            if let Some(name) = branch.name()? {
+                branches.push(name.to_string());
+            }
+        }
+        branches.sort();
+        for name in branches {
+            // Only branches named after a task (by the naming template) are candidates
+            let Some(task_id) = self.naming.task_id_in(&name, tasks) else {
+                continue;
+            };
+            if live_branches.contains(name.as_str()) || checked_out.contains(&name) {
+                continue;
+            }
+            let base = owner(tasks, &task_id)
+                .and_then(|t| t.base_branch.as_deref())
+                .unwrap_or(default_base);
+            orphans.push(Orphan::Branch {
+                reason: orphan_reason(tasks, &task_id),
+                merged: is_merged(&repo, &name, base),
+                name,
+            });
+        }
+
+        Ok(orphans)
+    }
+
+    /// Remove an orphan (worktrees must be pruned before their branches)
+    pub fn prune_orphan(&self, orphan: &Orphan) -> Result<()> {
+        let repo = open_repo(&self.repo_root)?;
+        match orphan {
+            Orphan::Worktree {
+                admin_name: Some(name),
+                ..
+            } => {
+                repo.find_worktree(name)?.prune(Some(
+                    WorktreePruneOptions::new()
+                        .valid(true)
+                        .locked(false)
+                        .working_tree(true),
+                ))?;
+            }
+            Orphan::Worktree { path, .. } => std::fs::remove_dir_all(path)?,
+            Orphan::StaleWorktree { admin_name, .. } => {
+                repo.find_worktree(admin_name)?.prune(None)?;
+            }
+            Orphan::Branch { name, .. } => {
+                repo.find_branch(name, BranchType::Local)?.delete()?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::git::config::MergeStrategy;
+    use crate::git::test_support::{git, init_repo};
+    use tempfile::TempDir;
+
+    fn create_git_manager() -> (TempDir, WorktreeManager, String) {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        let base = init_repo(root);
+        let manager = WorktreeManager::new(root.to_path_buf(), root.join(".hive"));
+        (temp_dir, manager, base)
+    }
+
+    fn task_with_worktree(manager: &WorktreeManager, id: &str, base: &str, status: TaskStatus) -> Task {
+        let mut task = Task::new(id, "");
+        task.id = id.to_string();
+        let branch = manager.get_branch_name(id, "");
+        task.worktree = Some(manager.create(id, &branch, base).unwrap().0.to_string_lossy().to_string());
+        task.branch = Some(branch);
+        task.set_status(status);
+        task
+    }
+
+    // ========================================
+    // Orphan Detection Tests
+    // ========================================
+
+    #[test]
+    fn test_no_orphans_for_active_tasks() {
+        let (_temp, manager, base) = create_git_manager();
+        let tasks = vec![task_with_worktree(&manager, "task-live", &base, TaskStatus::Review)];
+
+        assert!(manager.find_orphans(&tasks, &base).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_squash_and_rebase_merged_branches_are_merged() {
+        for strategy in [MergeStrategy::Squash, MergeStrategy::Rebase] {
+            let (temp_dir, manager, base) = create_git_manager();
+            let root = temp_dir.path();
+            let task = task_with_worktree(&manager, "task-done", &base, TaskStatus::Done);
+            let worktree = PathBuf::from(task.worktree.clone().unwrap());
+            std::fs::write(worktree.join("feature.txt"), "feature\n").unwrap();
+            git(&worktree, &["add", "feature.txt"]);
+            git(&worktree, &["commit", "-m", "Add feature"]);
+            // The base moves on, so the rebased commit is a copy too
+            std::fs::write(root.join("base.txt"), "base\n").unwrap();
+            git(root, &["add", "base.txt"]);
+            git(root, &["commit", "-m", "Base change"]);
+            manager.merge("hive/task-done", &base, strategy, "Task (task-done)").unwrap();
+
+            let orphans = manager.find_orphans(&[task], &base).unwrap();
+            assert!(
+                orphans.contains(&Orphan::Branch {
+                    name: "hive/task-done".into(),
+                    reason: "task done".into(),
+                    merged: true,
+                }),
+                "{:?}: {:?}",
+                strategy,
+                orphans
+            );
+        }
+    }
+
+    #[test]
+    fn test_running_rerun_is_kept() {
+        let (_temp, manager, base) = create_git_manager();
+        let mut task = task_with_worktree(&manager, "task-live", &base, TaskStatus::Review);
+        let branch = "hive/task-live-rerun-20260101000000";
+        let worktree = manager.create("task-live-rerun-20260101000000", branch, &base).unwrap().0;
+        let mut run = RunRecord::new(
+            crate::agent::AgentRole::Executor,
+            "claude",
+            "claude",
+            vec![],
+            "p",
+            worktree.to_string_lossy(),
+        );
+        run.rerun_of = Some("run-1".into());
+        run.branch = Some(branch.into());
+        task.start_run(run);
+
+        let tasks = vec![task];
+        assert!(manager.find_orphans(&tasks, &base).unwrap().is_empty());
+
+        // Once finished, the rerun's worktree is left for GC
+        let mut tasks = tasks;
+        tasks[0].runs[0].status = RunStatus::Succeeded;
+        let orphans = manager.find_orphans(&tasks, &base).unwrap();
+        assert!(orphans.iter().any(|o| matches!(o, Orphan::Worktree { reason, .. } if reason == "finished rerun")));
+    }
+
+    #[test]
+    fn test_find_and_prune_orphans() {
+        let (temp_dir, manager, base) = create_git_manager();
+        let root = temp_dir.path();
+
+        let live = task_with_worktree(&manager, "task-live", &base, TaskStatus::InProgress);
+        let done = task_with_worktree(&manager, "task-done", &base, TaskStatus::Done);
+        // Deleted task: worktree and branch without task
+        manager.create("task-0000dead", "hive/task-0000dead-old", &base).unwrap();
+        // Stale entry: directory removed behind git's back
+        let stale = manager.create("task-0000beef", "hive/task-0000beef", &base).unwrap().0;
+        std::fs::remove_dir_all(&stale).unwrap();
+        // Junk directory
+        std::fs::create_dir_all(root.join(".hive/worktrees/junk")).unwrap();
+        // Unrelated branch is never touched
+        git(root, &["branch", "feature/mine"]);
+        git(root, &["branch", "hive/experiment"]);
+        // Branch left behind by a rerun of a live task
+        git(root, &["branch", "hive/task-live-rerun-20260101000000"]);
+
+        let tasks = vec![live, done];
+        let orphans = manager.find_orphans(&tasks, &base).unwrap();
+        let descriptions: Vec<String> = orphans.iter().map(|o| o.describe()).collect();
+
+        assert!(orphans.iter().any(|o| matches!(o, Orphan::StaleWorktree { admin_name, .. } if admin_name == "task-0000beef")));
+        assert!(orphans.iter().any(|o| matches!(o, Orphan::Worktree { reason, admin_name: Some(_), .. } if reason == "task done")));
+        assert!(orphans.iter().any(|o| matches!(o, Orphan::Worktree { reason, .. } if reason == "task deleted")));
+        assert!(orphans.iter().any(|o| matches!(o, Orphan::Worktree { reason, admin_name: None, .. } if reason == "not a registered worktree")));
+        let branch = |name: &str, reason: &str| Orphan::Branch {
+            name: name.into(),
+            reason: reason.into(),
+            merged: true,
+        };
+        assert!(orphans.contains(&branch("hive/task-done", "task done")));
+        assert!(orphans.contains(&branch("hive/task-0000dead-old", "task deleted")));
+        assert!(orphans.contains(&branch("hive/task-0000beef", "task deleted")));
+        assert!(orphans.contains(&branch("hive/task-live-rerun-20260101000000", "finished rerun")));
+        assert!(!descriptions.iter().any(|d| d.contains("task-live (") || d.contains("feature/mine")));
+        assert!(!descriptions.iter().any(|d| d.contains("hive/experiment")));
+
+        for orphan in &orphans {
+            manager.prune_orphan(orphan).unwrap();
+        }
+
+        assert!(manager.find_orphans(&tasks, &base).unwrap().is_empty());
+        let branches = git(root, &["branch", "--format=%(refname:short)"]);
+        assert!(branches.contains("hive/task-live"));
+        assert!(branches.contains("feature/mine"));
+        assert!(branches.contains("hive/experiment"));
+        assert!(!branches.contains("hive/task-done"));
+        assert_eq!(git(root, &["worktree", "list"]).lines().count(), 2);
+    }
+
+    #[test]
+    fn test_unmerged_branches_and_stacked_bases() {
+        let (_temp, manager, base) = create_git_manager();
+
+        // Done task whose commit never reached the base
+        let done = task_with_worktree(&manager, "task-done", &base, TaskStatus::Done);
+        let worktree = PathBuf::from(done.worktree.clone().unwrap());
+        std::fs::write(worktree.join("feature.txt"), "feature").unwrap();
+        git(&worktree, &["add", "feature.txt"]);
+        git(&worktree, &["commit", "-m", "Add feature"]);
+
+        // Done parent whose branch an active stacked task is still based on
+        let parent = task_with_worktree(&manager, "task-parent", &base, TaskStatus::Done);
+        let parent_branch = parent.branch.clone().unwrap();
+        let mut child = task_with_worktree(&manager, "task-child", &parent_branch, TaskStatus::Review);
+        child.base_branch = Some(parent_branch.clone());
+
+        let orphans = manager.find_orphans(&[done, parent, child], &base).unwrap();
+        let unmerged: Vec<String> = orphans
+            .iter()
+            .filter(|o| o.is_unmerged())
+            .map(|o| o.describe())
+            .collect();
+        assert_eq!(unmerged, vec!["branch hive/task-done (task done, not merged)"]);
+        assert!(!orphans
+            .iter()
+            .any(|o| matches!(o, Orphan::Branch { name, .. } if *name == parent_branch)));
+    }
+
+    #[test]
+    fn test_orphan_reason() {
+        let mut done = Task::new("Done", "");
+        done.id = "task-a".into();
+        done.set_status(TaskStatus::Done);
+        let mut active = Task::new("Active", "");
+        active.id = "task-b".into();
+        let tasks = vec![done, active];
+
+        assert_eq!(orphan_reason(&tasks, "task-a"), "task done");
+        assert_eq!(orphan_reason(&tasks, "task-b-rerun-20260101000000"), "finished rerun");
+        assert_eq!(orphan_reason(&tasks, "task-c"), "task deleted");
+    }
+}
@@ -1,5 +1,19 @@
+mod bootstrap;
+mod branch;
+mod config;
+mod diff;
+mod error;
+mod gc;
+mod stack;
+#[cfg(test)]
+mod test_support;
 mod validator;
 mod worktree;
 
-pub use validator::{GitValidator, WorktreeValidator};
-pub use worktree::WorktreeManager;
+pub use branch::BranchNaming;
+pub use config::{render_template, GitConfig};
+pub use diff::{word_diff, ChangeKind, DiffHunk, DiffLine, FileDiff, LineKind};
+pub use error::GitError;
+pub use gc::Orphan;
+pub use validator::{GitValidator, SecretFinding, ValidationResult, WorktreeValidator};
+pub use worktree::{SyncOutcome, WorktreeManager};
@@ -0,0 +1,215 @@
+use std::path::Path;
+
+use super::error::{GitError, Result};
+use super::validator::{open_repo, WorktreeValidator};
+use super::worktree::{SyncOutcome, WorktreeManager};
+
+impl WorktreeManager {
+    /// Commit `branch` currently points to
+    pub fn branch_tip(&self, branch: &str) -> Result<String> {
+        let repo = open_repo(&self.repo_root)?;
+        let commit = repo
+            .revparse_single(branch)
+            .and_then(|object| object.peel_to_commit())
+            .map_err(|_| GitError::RevisionNotFound(branch.to_string()))?;
+        Ok(commit.id().to_string())
+    }
+
+    /// Fetch `branch` from origin (e.g. a base a PR was merged into on the forge).
+    /// Returns the remote-tracking ref ("origin/<branch>").
+    /// Uses the git CLI: libgit2 knows nothing of the user's credential helpers, SSH config
+    /// or proxies, so fetching through it would need hive to handle authentication itself.
+    pub fn fetch_base(&self, branch: &str) -> Result<String> {
+        let output = self.run_git(&self.repo_root, &["fetch", "origin", branch])?;
+        if !output.status.success() {
+            return Err(GitError::Command {
+                command: "fetch".into(),
+                stderr: String::from_utf8_lossy(&output.stderr).trim().to_string(),
+            });
+        }
+        Ok(format!("origin/{}", branch))
+    }
+
+    /// Move the commits of the stacked task branch in `worktree_path` onto `onto`.
+    /// `upstream` is the parent commit the branch was stacked on: only commits after it are
+    /// replayed (`git rebase --onto`), so rewritten or squash-merged parent commits are dropped.
+    /// Without it the branch is rebased onto `onto` directly. Conflicts abort the rebase.
+    /// Rebases through the CLI like `sync` (libgit2 lacks hooks, rerere and autostash support).
+    pub fn restack(
+        &self,
+        worktree_path: &Path,
+        onto: &str,
+        upstream: Option<&str>,
+    ) -> Result<SyncOutcome> {
+        let validator = WorktreeValidator::new(worktree_path.to_path_buf());
+        if validator.has_tracked_changes()? {
+            return Err(GitError::DirtyWorktree(worktree_path.to_path_buf()));
+        }
+
+        let upstream = {
+            let repo = open_repo(worktree_path)?;
+            let onto_commit = repo
+                .revparse_single(onto)
+                .and_then(|object| object.peel_to_commit())
+                .map_err(|_| GitError::RevisionNotFound(onto.to_string()))?
+                .id();
+            let head = repo.head()?.peel_to_commit()?.id();
+            if head == onto_commit || repo.graph_descendant_of(head, onto_commit)? {
+                return Ok(SyncOutcome::UpToDate);
+            }
+            // A recorded upstream that is gone (e.g. garbage collected) falls back to a plain rebase
+            upstream.filter(|upstream| repo.revparse_single(upstream).is_ok())
+        };
+
+        match upstream {
+            Some(upstream) => self.run_or_abort(
+                worktree_path,
+                &["rebase", "--onto", onto, upstream],
+                &["rebase", "--abort"],
+            ),
+            None => self.run_or_abort(worktree_path, &["rebase", onto], &["rebase", "--abort"]),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::git::config::MergeStrategy;
+    use crate::git::test_support::{git, init_repo};
+    use std::path::PathBuf;
+    use tempfile::TempDir;
+
+    fn commit_file(dir: &Path, file: &str, content: &str, message: &str) {
+        std::fs::write(dir.join(file), content).unwrap();
+        git(dir, &["add", file]);
+        git(dir, &["commit", "-m", message]);
+    }
+
+    /// Parent task branch with one commit and a child stacked on it with one commit
+    fn create_stack() -> (TempDir, WorktreeManager, String, PathBuf, PathBuf, String) {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        let base = init_repo(root);
+        let manager = WorktreeManager::new(root.to_path_buf(), root.join(".hive"));
+
+        let parent = manager.create("task-parent", "hive/task-parent", &base).unwrap().0;
+        commit_file(&parent, "parent.txt", "parent\n", "Parent change");
+        let stacked_on = manager.branch_tip("hive/task-parent").unwrap();
+
+        let child = manager.create("task-child", "hive/task-child", "hive/task-parent").unwrap().0;
+        commit_file(&child, "child.txt", "child\n", "Child change");
+        (temp_dir, manager, base, parent, child, stacked_on)
+    }
+
+    fn subjects(dir: &Path) -> Vec<String> {
+        git(dir, &["log", "--format=%s"])
+            .lines()
+            .map(String::from)
+            .collect()
+    }
+
+    // ========================================
+    // Restack Tests
+    // ========================================
+
+    #[test]
+    fn test_branch_tip() {
+        let (_temp, manager, _base, parent, _child, stacked_on) = create_stack();
+        assert_eq!(stacked_on, git(&parent, &["rev-parse", "HEAD"]));
+        assert!(matches!(
+            manager.branch_tip("missing"),
+            Err(GitError::RevisionNotFound(_))
+        ));
+    }
+
+    #[test]
+    fn test_restack_picks_up_parent_commits() {
+        let (_temp, manager, _base, parent, child, stacked_on) = create_stack();
+        assert_eq!(
+            manager.restack(&child, "hive/task-parent", Some(&stacked_on)).unwrap(),
+            SyncOutcome::UpToDate
+        );
+
+        commit_file(&parent, "parent.txt", "parent v2\n", "Parent follow-up");
+        let outcome = manager.restack(&child, "hive/task-parent", Some(&stacked_on)).unwrap();
+        assert_eq!(outcome, SyncOutcome::Synced);
+        assert_eq!(
+            subjects(&child),
+            vec!["Child change", "Parent follow-up", "Parent change", "Initial commit"]
+        );
+    }
+
+    #[test]
+    fn test_restack_drops_rewritten_parent_commits() {
+        let (_temp, manager, _base, parent, child, stacked_on) = create_stack();
+        std::fs::write(parent.join("parent.txt"), "amended\n").unwrap();
+        git(&parent, &["commit", "-a", "--amend", "-m", "Parent change (amended)"]);
+
+        let outcome = manager.restack(&child, "hive/task-parent", Some(&stacked_on)).unwrap();
+        assert_eq!(outcome, SyncOutcome::Synced);
+        assert_eq!(
+            subjects(&child),
+            vec!["Child change", "Parent change (amended)", "Initial commit"]
+        );
+    }
+
+    #[test]
+    fn test_retarget_after_parent_squash_merge() {
+        let (temp, manager, base, _parent, child, _stacked_on) = create_stack();
+        let parent_tip = manager.branch_tip("hive/task-parent").unwrap();
+        manager
+            .merge("hive/task-parent", &base, MergeStrategy::Squash, "Squashed parent")
+            .unwrap();
+
+        let outcome = manager.restack(&child, &base, Some(&parent_tip)).unwrap();
+        assert_eq!(outcome, SyncOutcome::Synced);
+        assert_eq!(
+            subjects(&child),
+            vec!["Child change", "Squashed parent", "Initial commit"]
+        );
+        assert!(child.join("parent.txt").exists());
+        assert_eq!(git(temp.path(), &["rev-parse", &base]), git(&child, &["rev-parse", "HEAD~1"]));
+    }
+
+    #[test]
+    fn test_retarget_after_parent_merged_on_remote() {
+        let (temp, manager, base, _parent, child, _stacked_on) = create_stack();
+        let root = temp.path();
+        let remote = TempDir::new().unwrap();
+        git(root, &["clone", "-q", "--bare", ".", remote.path().to_str().unwrap()]);
+        git(root, &["remote", "add", "origin", remote.path().to_str().unwrap()]);
+
+        // The parent PR is squash-merged on the forge; the local base does not move
+        let parent_tip = manager.branch_tip("hive/task-parent").unwrap();
+        let tree = format!("{}^{{tree}}", parent_tip);
+        let squashed = git(root, &["commit-tree", &tree, "-p", &base, "-m", "Squashed on forge"]);
+        git(root, &["push", "-q", "origin", &format!("{}:refs/heads/{}", squashed, base)]);
+
+        let onto = manager.fetch_base(&base).unwrap();
+        assert_eq!(onto, format!("origin/{}", base));
+        let outcome = manager.restack(&child, &onto, Some(&parent_tip)).unwrap();
+        assert_eq!(outcome, SyncOutcome::Synced);
+        assert_eq!(
+            subjects(&child),
+            vec!["Child change", "Squashed on forge", "Initial commit"]
+        );
+        assert!(matches!(
+            manager.fetch_base("missing"),
+            Err(GitError::Command { .. })
+        ));
+    }
+
+    #[test]
+    fn test_restack_conflict_aborts() {
+        let (_temp, manager, _base, parent, child, stacked_on) = create_stack();
+        commit_file(&child, "parent.txt", "child edit\n", "Child edits parent file");
+        commit_file(&parent, "parent.txt", "parent edit\n", "Parent edits its file");
+        let head_before = git(&child, &["rev-parse", "HEAD"]);
+
+        let outcome = manager.restack(&child, "hive/task-parent", Some(&stacked_on)).unwrap();
+        assert_eq!(outcome, SyncOutcome::Conflicts(vec!["parent.txt".into()]));
+        assert_eq!(git(&child, &["rev-parse", "HEAD"]), head_before);
+        assert!(git(&child, &["status", "--porcelain", "--untracked-files=no"]).is_empty());
+    }
+}
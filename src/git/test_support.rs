@@ -0,0 +1,21 @@
+use std::path::Path;
+use std::process::Command;
+
+/// Run git in `dir`, panicking on failure; returns trimmed stdout
+pub fn git(dir: &Path, args: &[&str]) -> String {
+    let output = Command::new("git").args(args).current_dir(dir).output().unwrap();
+    assert!(output.status.success(), "git {:?}: {}", args, String::from_utf8_lossy(&output.stderr));
+    String::from_utf8_lossy(&output.stdout).trim().to_string()
+}
+
+/// Initialize a repository in `root` with a test identity and an initial commit
+/// ignoring `.hive/`; returns the checked-out branch
+pub fn init_repo(root: &Path) -> String {
+    git(root, &["init"]);
+    git(root, &["config", "user.email", "test@example.com"]);
+    git(root, &["config", "user.name", "Test User"]);
+    std::fs::write(root.join(".gitignore"), ".hive/\n").unwrap();
+    git(root, &["add", "."]);
+    git(root, &["commit", "-m", "Initial commit"]);
+    git(root, &["rev-parse", "--abbrev-ref", "HEAD"])
+}
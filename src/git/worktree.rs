@@ -1,16 +1,46 @@
-use std::path::PathBuf;
-use std::process::Command;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output};
+use std::time::Duration;
 
-use anyhow::{bail, Context, Result};
+use git2::build::CheckoutBuilder;
+use git2::{
+    BranchType, Commit, DiffFormat, ErrorCode, Index, Oid, RebaseOptions, Repository, RepositoryState,
+    Signature, WorktreeAddOptions, WorktreePruneOptions,
+};
+
+use super::branch::BranchNaming;
+use super::config::{BootstrapConfig, MergeStrategy, SyncStrategy};
+use super::error::{GitError, Result};
+use super::validator::{merge_base_tree, open_repo, GitValidator, WorktreeValidator};
+use crate::process::output_with_timeout;
+
+/// Longest a git subprocess (rebase, merge, fetch) may run before it is killed
+const GIT_TIMEOUT: Duration = Duration::from_secs(300);
+
+/// File in a worktree's git directory listing untracked files hive wrote into the worktree
+const GENERATED_LIST: &str = "hive-generated";
+
+/// Result of syncing a task branch with its base
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SyncOutcome {
+    /// Base is already contained in the task branch
+    UpToDate,
+    /// Task branch now contains the latest base
+    Synced,
+    /// Sync stopped on conflicts (aborted, worktree left unchanged)
+    Conflicts(Vec<String>),
+}
 
 /// Git worktree manager
 pub struct WorktreeManager {
     /// Repository root path
-    repo_root: PathBuf,
+    pub(super) repo_root: PathBuf,
     /// Directory for creating worktrees
-    worktree_dir: PathBuf,
-    /// Branch name prefix
-    branch_prefix: String,
+    pub(super) worktree_dir: PathBuf,
+    /// Task branch naming
+    pub(super) naming: BranchNaming,
+    /// Worktree bootstrap rules
+    pub(super) bootstrap: BootstrapConfig,
 }
 
 impl WorktreeManager {
@@ -21,81 +51,133 @@ impl WorktreeManager {
         Self {
             repo_root,
             worktree_dir,
-            branch_prefix: "hive".into(),
+            naming: BranchNaming::default(),
+            bootstrap: BootstrapConfig::default(),
         }
     }
 
-    /// Create worktree for a task
-    pub fn create(&self, task_id: &str) -> Result<PathBuf> {
-        let branch_name = format!("{}/{}", self.branch_prefix, task_id);
+    /// Use `naming` for task branches
+    pub fn with_branch_naming(mut self, naming: BranchNaming) -> Self {
+        self.naming = naming;
+        self
+    }
+
+    /// Task branch naming
+    pub fn branch_naming(&self) -> &BranchNaming {
+        &self.naming
+    }
+
+    /// Create worktree for a task on `branch_name`, branching from `base_branch`, and
+    /// bootstrap it. Returns the path and the bootstrap failures.
+    pub fn create(
+        &self,
+        task_id: &str,
+        branch_name: &str,
+        base_branch: &str,
+    ) -> Result<(PathBuf, Vec<String>)> {
         let worktree_path = self.worktree_dir.join(task_id);
 
         // Return existing path if already exists
         if worktree_path.exists() {
-            return Ok(worktree_path);
+            return Ok((worktree_path, Vec::new()));
         }
 
-        // Create worktree with a new branch
-        let output = Command::new("git")
-            .args(["worktree", "add", "-b", &branch_name])
-            .arg(&worktree_path)
-            .current_dir(&self.repo_root)
-            .output()
-            .context("Failed to execute git worktree add")?;
+        // Reuse the branch if it already exists, otherwise create it from the base branch
+        let repo = open_repo(&self.repo_root)?;
+        if repo.find_branch(branch_name, BranchType::Local).is_err() {
+            self.create_branch(&repo, branch_name, base_branch)?;
+        }
+        self.add_worktree(&repo, task_id, &worktree_path, branch_name)?;
 
-        if !output.status.success() {
-            let stderr = String::from_utf8_lossy(&output.stderr);
-            // If branch already exists, try to create with existing branch
-            if stderr.contains("already exists") {
-                let output = Command::new("git")
-                    .args(["worktree", "add"])
-                    .arg(&worktree_path)
-                    .arg(&branch_name)
-                    .current_dir(&self.repo_root)
-                    .output()
-                    .context("Failed to execute git worktree add with existing branch")?;
-
-                if !output.status.success() {
-                    bail!(
-                        "Failed to create worktree: {}",
-                        String::from_utf8_lossy(&output.stderr)
-                    );
-                }
-            } else {
-                bail!("Failed to create worktree: {}", stderr);
-            }
+        let failures = self.run_bootstrap(&worktree_path);
+        Ok((worktree_path, failures))
+    }
+
+    /// Create a fresh, bootstrapped worktree named `name` on a new branch starting at
+    /// `base_commit`. Returns the path and the bootstrap failures.
+    pub fn create_at(
+        &self,
+        name: &str,
+        branch_name: &str,
+        base_commit: &str,
+    ) -> Result<(PathBuf, Vec<String>)> {
+        let worktree_path = self.worktree_dir.join(name);
+        if worktree_path.exists() {
+            return Err(GitError::WorktreeExists(worktree_path));
         }
 
-        // Set up .claude/settings.json (plansDirectory)
-        self.setup_claude_settings(&worktree_path)?;
+        let repo = open_repo(&self.repo_root)?;
+        if repo.find_branch(branch_name, BranchType::Local).is_ok() {
+            return Err(GitError::BranchExists(branch_name.to_string()));
+        }
+        self.create_branch(&repo, branch_name, base_commit)?;
+        self.add_worktree(&repo, name, &worktree_path, branch_name)?;
+
+        let failures = self.run_bootstrap(&worktree_path);
+        Ok((worktree_path, failures))
+    }
+
+    /// Create a local branch at `start_point`
+    fn create_branch(&self, repo: &Repository, branch_name: &str, start_point: &str) -> Result<()> {
+        let commit = repo
+            .revparse_single(start_point)
+            .and_then(|object| object.peel_to_commit())
+            .map_err(|_| GitError::RevisionNotFound(start_point.to_string()))?;
+        repo.branch(branch_name, &commit, false)?;
+        Ok(())
+    }
 
-        Ok(worktree_path)
+    /// Register a worktree at `path` with `branch_name` checked out
+    fn add_worktree(
+        &self,
+        repo: &Repository,
+        name: &str,
+        path: &Path,
+        branch_name: &str,
+    ) -> Result<()> {
+        let reference = repo
+            .find_branch(branch_name, BranchType::Local)?
+            .into_reference();
+        let mut options = WorktreeAddOptions::new();
+        options.reference(Some(&reference));
+        repo.worktree(name, path, Some(&options))?;
+        Ok(())
     }
 
     /// Remove worktree
+    #[allow(dead_code)]
     pub fn remove(&self, task_id: &str) -> Result<()> {
-        let worktree_path = self.worktree_dir.join(task_id);
+        self.remove_path(&self.worktree_dir.join(task_id))
+    }
 
+    /// Remove worktree at path
+    pub fn remove_path(&self, worktree_path: &Path) -> Result<()> {
         if !worktree_path.exists() {
             return Ok(());
         }
 
-        // Remove worktree
-        let output = Command::new("git")
-            .args(["worktree", "remove", "--force"])
-            .arg(&worktree_path)
-            .current_dir(&self.repo_root)
-            .output()
-            .context("Failed to execute git worktree remove")?;
-
-        if !output.status.success() {
-            bail!(
-                "Failed to remove worktree: {}",
-                String::from_utf8_lossy(&output.stderr)
-            );
+        let repo = open_repo(&self.repo_root)?;
+        let target = worktree_path.canonicalize()?;
+        for name in repo.worktrees()?.iter().flatten() {
+            let worktree = repo.find_worktree(name)?;
+            let matches = worktree
+                .path()
+                .canonicalize()
+                .map(|path| path == target)
+                .unwrap_or(false);
+            if matches {
+                // Remove the working tree and its administrative files, even with local changes
+                worktree.prune(Some(
+                    WorktreePruneOptions::new()
+                        .valid(true)
+                        .locked(false)
+                        .working_tree(true),
+                ))?;
+                return Ok(());
+            }
         }
 
-        Ok(())
+        Err(GitError::WorktreeNotFound(worktree_path.to_path_buf()))
     }
 
     /// Get worktree path
@@ -105,74 +187,412 @@ impl WorktreeManager {
     }
 
     /// Check if worktree exists
+    #[allow(dead_code)]
     pub fn exists(&self, task_id: &str) -> bool {
         self.worktree_dir.join(task_id).exists()
     }
 
-    /// Get branch name
-    pub fn get_branch_name(&self, task_id: &str) -> String {
-        format!("{}/{}", self.branch_prefix, task_id)
+    /// Get branch name rendered from the naming template
+    pub fn get_branch_name(&self, task_id: &str, title: &str) -> String {
+        self.naming.render(task_id, title)
     }
 
-    /// Create Claude Code settings file
-    fn setup_claude_settings(&self, worktree_path: &PathBuf) -> Result<()> {
-        let claude_dir = worktree_path.join(".claude");
-        std::fs::create_dir_all(&claude_dir).context("Failed to create .claude directory")?;
+    /// Record files hive wrote into a worktree so they are never committed on the task's
+    /// behalf. Tracked files are marked skip-worktree (their changes stay invisible to status,
+    /// sync and commits); untracked ones are listed in the worktree's git directory.
+    pub fn mark_generated(&self, worktree_path: &Path, paths: &[String]) -> Result<()> {
+        let repo = open_repo(worktree_path)?;
+        let index = repo.index()?;
+        let (tracked, untracked): (Vec<&String>, Vec<&String>) = paths
+            .iter()
+            .partition(|path| index.get_path(Path::new(path.as_str()), 0).is_some());
 
-        // Point plansDirectory to .hive/plans (relative path)
-        let settings = serde_json::json!({
-            "plansDirectory": "../../plans"
-        });
+        if !tracked.is_empty() {
+            let output = Command::new("git")
+                .args(["update-index", "--skip-worktree", "--"])
+                .args(&tracked)
+                .current_dir(worktree_path)
+                .output()?;
+            if !output.status.success() {
+                return Err(GitError::Command {
+                    command: "update-index".into(),
+                    stderr: String::from_utf8_lossy(&output.stderr).trim().to_string(),
+                });
+            }
+        }
 
-        let settings_path = claude_dir.join("settings.json");
-        std::fs::write(
-            &settings_path,
-            serde_json::to_string_pretty(&settings).unwrap(),
-        )
-        .context("Failed to write .claude/settings.json")?;
+        let mut listed = self.generated_files(worktree_path)?;
+        let before = listed.len();
+        for path in untracked {
+            if !listed.contains(path) {
+                listed.push(path.clone());
+            }
+        }
+        if listed.len() > before {
+            std::fs::write(repo.path().join(GENERATED_LIST), listed.join("\n") + "\n")?;
+        }
+        Ok(())
+    }
 
+    /// Untracked files hive wrote into a worktree
+    fn generated_files(&self, worktree_path: &Path) -> Result<Vec<String>> {
+        let list = open_repo(worktree_path)?.path().join(GENERATED_LIST);
+        Ok(std::fs::read_to_string(list)
+            .unwrap_or_default()
+            .lines()
+            .map(String::from)
+            .collect())
+    }
+
+    /// Get diff of a worktree since it branched off base branch (tracked files, unified format)
+    pub fn get_diff(&self, worktree_path: &Path, base_branch: &str) -> Result<String> {
+        let repo = open_repo(worktree_path)?;
+        let base_tree = merge_base_tree(&repo, base_branch)?;
+        let diff = repo.diff_tree_to_workdir_with_index(Some(&base_tree), None)?;
+
+        let mut patch = String::new();
+        diff.print(DiffFormat::Patch, |_delta, _hunk, line| {
+            if matches!(line.origin(), '+' | '-' | ' ') {
+                patch.push(line.origin());
+            }
+            patch.push_str(&String::from_utf8_lossy(line.content()));
+            true
+        })?;
+
+        Ok(patch)
+    }
+
+    /// Stage every change in the worktree, including untracked and deleted files.
+    /// Files hive writes into worktrees (generated or bootstrapped) are left out.
+    pub fn stage_all(&self, worktree_path: &Path) -> Result<()> {
+        let generated = self.generated_files(worktree_path)?;
+        let repo = open_repo(worktree_path)?;
+        let mut index = repo.index()?;
+        let mut skip_generated = |path: &Path, _: &[u8]| -> i32 {
+            i32::from(generated.iter().any(|f| path == Path::new(f)) || self.is_bootstrapped(path))
+        };
+        index.add_all(["*"], git2::IndexAddOption::DEFAULT, Some(&mut skip_generated))?;
+        index.update_all(["*"], Some(&mut skip_generated))?;
+        index.write()?;
         Ok(())
     }
 
-    /// Get diff from main branch
-    pub fn get_diff(&self, task_id: &str, base_branch: &str) -> Result<String> {
-        let worktree_path = self.worktree_dir.join(task_id);
+    /// Stage and commit every change in the worktree on its current branch.
+    /// Returns the new commit id, or None when there was nothing to commit.
+    pub fn commit_all(&self, worktree_path: &Path, message: &str) -> Result<Option<String>> {
+        self.stage_all(worktree_path)?;
+        let repo = open_repo(worktree_path)?;
+        let mut index = repo.index()?;
+        let tree = repo.find_tree(index.write_tree()?)?;
+        let parent = repo.head()?.peel_to_commit()?;
+        if tree.id() == parent.tree_id() {
+            return Ok(None);
+        }
+        let signature = repo
+            .signature()
+            .or_else(|_| Signature::now("Hive", "hive@localhost"))?;
+        let oid = repo.commit(Some("HEAD"), &signature, &signature, message, &tree, &[&parent])?;
+        Ok(Some(oid.to_string()))
+    }
 
-        let output = Command::new("git")
-            .args(["diff", base_branch])
-            .current_dir(&worktree_path)
-            .output()
-            .context("Failed to execute git diff")?;
+    /// Bring the task branch in `worktree_path` up to date with `base_branch`.
+    /// On conflicts the rebase/merge is aborted and the conflicted files are returned.
+    pub fn sync(
+        &self,
+        worktree_path: &Path,
+        base_branch: &str,
+        strategy: SyncStrategy,
+    ) -> Result<SyncOutcome> {
+        let validator = WorktreeValidator::new(worktree_path.to_path_buf());
+        if validator.has_tracked_changes()? {
+            return Err(GitError::DirtyWorktree(worktree_path.to_path_buf()));
+        }
 
-        Ok(String::from_utf8_lossy(&output.stdout).to_string())
+        {
+            let repo = open_repo(worktree_path)?;
+            let base = repo
+                .revparse_single(base_branch)
+                .and_then(|object| object.peel_to_commit())
+                .map_err(|_| GitError::RevisionNotFound(base_branch.to_string()))?
+                .id();
+            let head = repo.head()?.peel_to_commit()?.id();
+            if head == base || repo.graph_descendant_of(head, base)? {
+                return Ok(SyncOutcome::UpToDate);
+            }
+        }
+
+        // Rebase/merge through the CLI (libgit2 lacks hooks, rerere and autostash support)
+        match strategy {
+            SyncStrategy::Rebase => {
+                self.run_or_abort(worktree_path, &["rebase", base_branch], &["rebase", "--abort"])
+            }
+            SyncStrategy::Merge => self.run_or_abort(
+                worktree_path,
+                &["merge", "--no-edit", base_branch],
+                &["merge", "--abort"],
+            ),
+        }
+    }
+
+    /// Run git `args` in `dir`, killing it after `GIT_TIMEOUT`. Git never prompts for
+    /// credentials (there is no terminal to answer), so a missing login fails instead of hanging.
+    pub(super) fn run_git(&self, dir: &Path, args: &[&str]) -> Result<Output> {
+        let mut command = Command::new("git");
+        command.args(args).current_dir(dir).env("GIT_TERMINAL_PROMPT", "0");
+        output_with_timeout(&mut command, GIT_TIMEOUT).map_err(|e| match e.kind() {
+            std::io::ErrorKind::TimedOut => GitError::Timeout {
+                command: args[0].into(),
+                secs: GIT_TIMEOUT.as_secs(),
+            },
+            _ => e.into(),
+        })
+    }
+
+    /// Run a rebase/merge `args` in the worktree; on conflicts (or a timeout) run `abort`
+    /// and return the files
+    pub(super) fn run_or_abort(
+        &self,
+        worktree_path: &Path,
+        args: &[&str],
+        abort: &[&str],
+    ) -> Result<SyncOutcome> {
+        let output = match self.run_git(worktree_path, args) {
+            Ok(output) if output.status.success() => return Ok(SyncOutcome::Synced),
+            Ok(output) => output,
+            Err(e) => {
+                let _ = self.run_git(worktree_path, abort);
+                return Err(e);
+            }
+        };
+
+        let conflicts = WorktreeValidator::new(worktree_path.to_path_buf())
+            .conflicted_files()
+            .unwrap_or_default();
+        self.run_git(worktree_path, abort)?;
+
+        if conflicts.is_empty() {
+            return Err(GitError::Command {
+                command: args[0].into(),
+                stderr: String::from_utf8_lossy(&output.stderr).trim().to_string(),
+            });
+        }
+        Ok(SyncOutcome::Conflicts(conflicts))
     }
 
-    /// Merge changes
-    pub fn merge(&self, task_id: &str, _target_branch: &str) -> Result<()> {
-        let branch_name = self.get_branch_name(task_id);
+    /// Recreate conflicts with `base_branch` inside the worktree by merging the base in.
+    /// Returns the conflicted files (empty if the merge went through cleanly).
+    pub fn recreate_conflict(&self, worktree_path: &Path, base_branch: &str) -> Result<Vec<String>> {
+        let validator = WorktreeValidator::new(worktree_path.to_path_buf());
+
+        // Conflict from an earlier attempt is still in place
+        if open_repo(worktree_path)?.state() == RepositoryState::Merge {
+            let conflicts = validator.conflicted_files()?;
+            if !conflicts.is_empty() {
+                return Ok(conflicts);
+            }
+        }
+
+        if validator.has_tracked_changes()? {
+            return Err(GitError::DirtyWorktree(worktree_path.to_path_buf()));
+        }
 
-        // Merge in main repository
         let output = Command::new("git")
-            .args(["merge", &branch_name, "--no-ff", "-m"])
-            .arg(format!("Merge {} via Hive", task_id))
-            .current_dir(&self.repo_root)
-            .output()
-            .context("Failed to execute git merge")?;
+            .args(["merge", "--no-edit", base_branch])
+            .current_dir(worktree_path)
+            .output()?;
+        if output.status.success() {
+            return Ok(Vec::new());
+        }
 
-        if !output.status.success() {
-            bail!(
-                "Failed to merge: {}",
-                String::from_utf8_lossy(&output.stderr)
-            );
+        let conflicts = validator.conflicted_files()?;
+        if conflicts.is_empty() {
+            Command::new("git")
+                .args(["merge", "--abort"])
+                .current_dir(worktree_path)
+                .output()?;
+            return Err(GitError::Command {
+                command: "merge".into(),
+                stderr: String::from_utf8_lossy(&output.stderr).trim().to_string(),
+            });
+        }
+        Ok(conflicts)
+    }
+
+    /// Stage resolved `files` and conclude an in-progress merge in the worktree
+    pub fn conclude_merge(&self, worktree_path: &Path, files: &[String]) -> Result<()> {
+        let repo = open_repo(worktree_path)?;
+        if repo.state() != RepositoryState::Merge {
+            // Already committed (e.g., by the agent)
+            return Ok(());
+        }
+
+        let mut index = repo.index()?;
+        for file in files {
+            if worktree_path.join(file).exists() {
+                index.add_path(Path::new(file))?;
+            } else {
+                index.remove_path(Path::new(file))?;
+            }
         }
+        index.write()?;
 
+        let output = Command::new("git")
+            .args(["commit", "--no-edit"])
+            .current_dir(worktree_path)
+            .output()?;
+        if !output.status.success() {
+            return Err(GitError::Command {
+                command: "commit".into(),
+                stderr: String::from_utf8_lossy(&output.stderr).trim().to_string(),
+            });
+        }
         Ok(())
     }
+
+    /// Merge `branch_name` into `target_branch` with `strategy` without checking the target out.
+    /// Returns the new tip of the target branch.
+    pub fn merge(
+        &self,
+        branch_name: &str,
+        target_branch: &str,
+        strategy: MergeStrategy,
+        message: &str,
+    ) -> Result<String> {
+        let repo = open_repo(&self.repo_root)?;
+        let target_ref = repo
+            .find_branch(target_branch, BranchType::Local)
+            .map_err(|_| GitError::RevisionNotFound(target_branch.to_string()))?
+            .into_reference();
+        let target_ref_name = target_ref.name().unwrap_or_default().to_string();
+        let target = target_ref.peel_to_commit()?;
+        let source = repo
+            .find_branch(branch_name, BranchType::Local)
+            .map_err(|_| GitError::RevisionNotFound(branch_name.to_string()))?
+            .into_reference()
+            .peel_to_commit()?;
+
+        // The working tree that has the target checked out (the main checkout or a linked
+        // worktree, e.g. a stacked child's base) is refreshed afterwards, so it must not have
+        // local changes
+        let target_checkout = GitValidator::new(self.repo_root.clone())
+            .list_worktrees()?
+            .into_iter()
+            .find(|worktree| !worktree.prunable && worktree.branch.as_deref() == Some(target_branch))
+            .map(|worktree| worktree.path);
+        if let Some(path) = &target_checkout {
+            if WorktreeValidator::new(path.clone()).has_tracked_changes()? {
+                return Err(GitError::DirtyWorktree(path.clone()));
+            }
+        }
+
+        let signature = repo
+            .signature()
+            .or_else(|_| Signature::now("Hive", "hive@localhost"))?;
+        let can_fast_forward =
+            source.id() == target.id() || repo.graph_descendant_of(source.id(), target.id())?;
+
+        let new_tip = match strategy {
+            MergeStrategy::FfOnly => {
+                if !can_fast_forward {
+                    return Err(GitError::NotFastForward(target_branch.to_string()));
+                }
+                source.id()
+            }
+            MergeStrategy::Merge | MergeStrategy::Squash => {
+                let mut index = repo.merge_commits(&target, &source, None)?;
+                if index.has_conflicts() {
+                    return Err(GitError::MergeConflicts(index_conflicts(&index)?));
+                }
+                let tree = repo.find_tree(index.write_tree_to(&repo)?)?;
+                let parents = if strategy == MergeStrategy::Merge {
+                    vec![&target, &source]
+                } else {
+                    vec![&target]
+                };
+                repo.commit(None, &signature, &signature, message, &tree, &parents)?
+            }
+            MergeStrategy::Rebase => {
+                if can_fast_forward {
+                    source.id()
+                } else {
+                    self.rebase_in_memory(&repo, &source, &target, &signature)?
+                }
+            }
+        };
+
+        // Update the working tree first, while HEAD still describes its current contents
+        if let Some(path) = &target_checkout {
+            let checkout_repo = open_repo(path)?;
+            let new_commit = checkout_repo.find_commit(new_tip)?;
+            checkout_repo.checkout_tree(new_commit.as_object(), Some(CheckoutBuilder::new().safe()))?;
+        }
+
+        repo.reference(
+            &target_ref_name,
+            new_tip,
+            true,
+            &format!("hive: merge {} ({})", branch_name, strategy.display_name()),
+        )?;
+
+        Ok(new_tip.to_string())
+    }
+
+    /// Replay `source` commits onto `target` without touching any working tree
+    fn rebase_in_memory(
+        &self,
+        repo: &Repository,
+        source: &Commit,
+        target: &Commit,
+        signature: &Signature,
+    ) -> Result<Oid> {
+        let branch = repo.find_annotated_commit(source.id())?;
+        let onto = repo.find_annotated_commit(target.id())?;
+        let mut options = RebaseOptions::new();
+        options.inmemory(true);
+        let mut rebase = repo.rebase(Some(&branch), None, Some(&onto), Some(&mut options))?;
+
+        let mut tip = target.id();
+        while let Some(operation) = rebase.next() {
+            operation?;
+            let index = rebase.inmemory_index()?;
+            if index.has_conflicts() {
+                let conflicts = index_conflicts(&index)?;
+                rebase.abort()?;
+                return Err(GitError::MergeConflicts(conflicts));
+            }
+            match rebase.commit(None, signature, None) {
+                Ok(id) => tip = id,
+                // Already in the target (e.g. cherry-picked): skip it like `git rebase` does
+                Err(e) if e.code() == ErrorCode::Applied => {}
+                Err(e) => {
+                    rebase.abort()?;
+                    return Err(e.into());
+                }
+            }
+        }
+        rebase.finish(Some(signature))?;
+        Ok(tip)
+    }
+}
+
+/// Paths with conflicts in an index
+fn index_conflicts(index: &Index) -> Result<Vec<String>> {
+    let mut files = Vec::new();
+    for conflict in index.conflicts()? {
+        let conflict = conflict?;
+        if let Some(entry) = conflict.our.or(conflict.their).or(conflict.ancestor) {
+            files.push(String::from_utf8_lossy(&entry.path).to_string());
+        }
+    }
+    files.sort();
+    files.dedup();
+    Ok(files)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::git::test_support::{git, init_repo};
     use tempfile::TempDir;
 
     fn create_test_manager() -> (TempDir, WorktreeManager) {
@@ -190,17 +610,20 @@ mod tests {
     fn test_get_branch_name() {
         let (_temp, manager) = create_test_manager();
 
-        let branch = manager.get_branch_name("task-abc123");
+        let branch = manager.get_branch_name("task-abc123", "");
         assert_eq!(branch, "hive/task-abc123");
+
+        let branch = manager.get_branch_name("task-abc123", "Add login page");
+        assert_eq!(branch, "hive/task-abc123-add-login-page");
     }
 
     #[test]
     fn test_get_branch_name_various_ids() {
         let (_temp, manager) = create_test_manager();
 
-        assert_eq!(manager.get_branch_name("task-1"), "hive/task-1");
-        assert_eq!(manager.get_branch_name("feature-xyz"), "hive/feature-xyz");
-        assert_eq!(manager.get_branch_name("123"), "hive/123");
+        assert_eq!(manager.get_branch_name("task-1", ""), "hive/task-1");
+        assert_eq!(manager.get_branch_name("feature-xyz", ""), "hive/feature-xyz");
+        assert_eq!(manager.get_branch_name("123", ""), "hive/123");
     }
 
     // ========================================
@@ -235,6 +658,357 @@ mod tests {
         assert!(!manager.exists("nonexistent-task"));
     }
 
+    // ========================================
+    // Create / Merge Tests
+    // ========================================
+
+    fn create_git_manager() -> (TempDir, WorktreeManager) {
+        let (temp_dir, manager) = create_test_manager();
+        init_repo(temp_dir.path());
+        (temp_dir, manager)
+    }
+
+    #[test]
+    fn test_create_and_merge_into_base_branch() {
+        let (temp_dir, manager) = create_git_manager();
+        let root = temp_dir.path();
+        let base = git(root, &["rev-parse", "--abbrev-ref", "HEAD"]);
+
+        let worktree = manager.create("task-1", "hive/task-1", &base).unwrap().0;
+        std::fs::write(worktree.join("feature.txt"), "feature").unwrap();
+        git(&worktree, &["add", "feature.txt"]);
+        git(&worktree, &["commit", "-m", "Add feature"]);
+
+        // Checked-out target: branch and working tree are updated
+        let tip = manager
+            .merge("hive/task-1", &base, MergeStrategy::Merge, "Merge task-1")
+            .unwrap();
+        assert_eq!(git(root, &["rev-parse", "HEAD"]), tip);
+        assert_eq!(git(root, &["log", "-1", "--format=%s"]), "Merge task-1");
+        assert_eq!(git(root, &["rev-list", "--parents", "-n1", "HEAD"]).split(' ').count(), 3);
+        assert!(root.join("feature.txt").exists());
+        assert!(git(root, &["status", "--porcelain", "--untracked-files=no"]).is_empty());
+    }
+
+    /// Task branch and a "release" target that diverged (non-conflicting)
+    fn create_release_target() -> (TempDir, WorktreeManager) {
+        let (temp_dir, manager, _worktree, base) = create_diverged(false);
+        let root = temp_dir.path().to_path_buf();
+        git(&root, &["branch", "release", &base]);
+        // Keep something else checked out so "release" is not
+        git(&root, &["checkout", "-q", "-b", "elsewhere"]);
+        (temp_dir, manager)
+    }
+
+    #[test]
+    fn test_merge_strategies_without_checkout() {
+        for strategy in [MergeStrategy::Merge, MergeStrategy::Squash, MergeStrategy::Rebase] {
+            let (temp_dir, manager) = create_release_target();
+            let root = temp_dir.path();
+            let head_before = git(root, &["rev-parse", "HEAD"]);
+
+            let tip = manager
+                .merge("hive/task-sync", "release", strategy, "Task (task-sync)")
+                .unwrap();
+            assert_eq!(git(root, &["rev-parse", "release"]), tip);
+            // Checked-out branch is untouched
+            assert_eq!(git(root, &["rev-parse", "HEAD"]), head_before);
+            assert_eq!(git(root, &["show", "release:shared.txt"]), "task");
+            assert_eq!(git(root, &["show", "release:other.txt"]), "base");
+
+            let parents = git(root, &["rev-list", "--parents", "-n1", "release"]);
+            let subject = git(root, &["log", "-1", "--format=%s", "release"]);
+            match strategy {
+                MergeStrategy::Merge => assert_eq!(parents.split(' ').count(), 3),
+                MergeStrategy::Squash => {
+                    assert_eq!(parents.split(' ').count(), 2);
+                    assert_eq!(subject, "Task (task-sync)");
+                }
+                MergeStrategy::Rebase => {
+                    assert_eq!(parents.split(' ').count(), 2);
+                    assert_eq!(subject, "Task change");
+                }
+                MergeStrategy::FfOnly => unreachable!(),
+            }
+        }
+    }
+
+    #[test]
+    fn test_rebase_skips_commits_already_in_target() {
+        let (temp_dir, manager) = create_release_target();
+        let root = temp_dir.path();
+        let worktree = manager.get_path("task-sync");
+        std::fs::write(worktree.join("extra.txt"), "extra\n").unwrap();
+        git(&worktree, &["add", "extra.txt"]);
+        git(&worktree, &["commit", "-m", "Extra"]);
+        let extra = git(&worktree, &["rev-parse", "HEAD"]);
+        git(root, &["checkout", "-q", "release"]);
+        git(root, &["cherry-pick", &extra]);
+        git(root, &["checkout", "-q", "elsewhere"]);
+
+        manager
+            .merge("hive/task-sync", "release", MergeStrategy::Rebase, "")
+            .unwrap();
+        let subjects = git(root, &["log", "--format=%s", "release"]);
+        assert_eq!(subjects.lines().filter(|s| *s == "Extra").count(), 1);
+        assert_eq!(subjects.lines().next(), Some("Task change"));
+    }
+
+    #[test]
+    fn test_merge_ff_only() {
+        let (temp_dir, manager) = create_release_target();
+        let err = manager
+            .merge("hive/task-sync", "release", MergeStrategy::FfOnly, "")
+            .unwrap_err();
+        assert!(matches!(err, GitError::NotFastForward(_)));
+
+        // After syncing, fast-forward succeeds
+        let root = temp_dir.path();
+        let task_tip = git(root, &["rev-parse", "hive/task-sync"]);
+        git(root, &["branch", "-f", "release", "hive/task-sync~1"]);
+        let tip = manager
+            .merge("hive/task-sync", "release", MergeStrategy::FfOnly, "")
+            .unwrap();
+        assert_eq!(tip, task_tip);
+    }
+
+    #[test]
+    fn test_create_at_existing_branch_is_typed_error() {
+        let (temp_dir, manager) = create_git_manager();
+        let head = git(temp_dir.path(), &["rev-parse", "HEAD"]);
+        git(temp_dir.path(), &["branch", "hive/taken"]);
+
+        let err = manager.create_at("task-2", "hive/taken", &head).unwrap_err();
+        assert!(matches!(err, GitError::BranchExists(ref b) if b == "hive/taken"));
+
+        let err = manager.create_at("task-2", "hive/new", "no-such-rev").unwrap_err();
+        assert!(matches!(err, GitError::RevisionNotFound(_)));
+    }
+
+    #[test]
+    fn test_get_diff_and_remove() {
+        let (temp_dir, manager) = create_git_manager();
+        let base = git(temp_dir.path(), &["rev-parse", "--abbrev-ref", "HEAD"]);
+
+        let worktree = manager.create("task-3", "hive/task-3", &base).unwrap().0;
+        std::fs::write(worktree.join(".gitignore"), ".hive/\nchanged\n").unwrap();
+
+        let diff = manager.get_diff(&worktree, &base).unwrap();
+        assert!(diff.contains("diff --git a/.gitignore b/.gitignore"));
+        assert!(diff.contains("+changed"));
+
+        manager.remove_path(&worktree).unwrap();
+        assert!(!worktree.exists());
+        assert!(git(temp_dir.path(), &["worktree", "list"]).lines().count() == 1);
+    }
+
+    #[test]
+    fn test_commit_all() {
+        let (temp_dir, manager) = create_git_manager();
+        let base = git(temp_dir.path(), &["rev-parse", "--abbrev-ref", "HEAD"]);
+
+        let worktree = manager.create("task-commit", "hive/task-commit", &base).unwrap().0;
+        std::fs::create_dir_all(worktree.join(".claude")).unwrap();
+        std::fs::write(worktree.join(".claude/settings.local.json"), "{}\n").unwrap();
+        manager
+            .mark_generated(&worktree, &[".claude/settings.local.json".into()])
+            .unwrap();
+        std::fs::write(worktree.join(".gitignore"), ".hive/\nchanged\n").unwrap();
+        std::fs::create_dir_all(worktree.join("src")).unwrap();
+        std::fs::write(worktree.join("src/new.rs"), "fn main() {}\n").unwrap();
+
+        let validator = WorktreeValidator::new(worktree.clone());
+        manager.stage_all(&worktree).unwrap();
+        assert_eq!(validator.staged_files().unwrap(), vec![".gitignore", "src/new.rs"]);
+
+        let oid = manager.commit_all(&worktree, "Leftovers\n\nTask: task-commit").unwrap();
+        assert_eq!(Some(git(&worktree, &["rev-parse", "HEAD"])), oid);
+        assert_eq!(git(&worktree, &["log", "-1", "--format=%s"]), "Leftovers");
+        assert!(validator.has_new_commits(&base).unwrap());
+        // Generated settings stay untracked
+        assert_eq!(git(&worktree, &["status", "--porcelain"]), "?? .claude/");
+        assert_eq!(manager.commit_all(&worktree, "Nothing").unwrap(), None);
+        assert_eq!(
+            git(&worktree, &["show", "--name-only", "--format=", "HEAD"]),
+            ".gitignore\nsrc/new.rs"
+        );
+    }
+
+    #[test]
+    fn test_generated_tracked_file_stays_out_of_changes() {
+        let (temp_dir, manager) = create_git_manager();
+        let base = git(temp_dir.path(), &["rev-parse", "--abbrev-ref", "HEAD"]);
+        let worktree = manager.create("task-gen", "hive/task-gen", &base).unwrap().0;
+
+        // Merged into the committed .gitignore, like agent settings merged into committed files
+        std::fs::write(worktree.join(".gitignore"), ".hive/\ngenerated\n").unwrap();
+        manager.mark_generated(&worktree, &[".gitignore".into()]).unwrap();
+        let validator = WorktreeValidator::new(worktree.clone());
+        assert!(!validator.has_tracked_changes().unwrap());
+
+        std::fs::write(worktree.join("task.txt"), "work\n").unwrap();
+        manager.commit_all(&worktree, "Task change").unwrap();
+        assert_eq!(git(&worktree, &["show", "--name-only", "--format=", "HEAD"]), "task.txt");
+    }
+
+    // ========================================
+    // Sync Tests
+    // ========================================
+
+    /// Task worktree with one commit, base advanced by one commit
+    fn create_diverged(conflicting: bool) -> (TempDir, WorktreeManager, PathBuf, String) {
+        let (temp_dir, manager) = create_git_manager();
+        let root = temp_dir.path().to_path_buf();
+        let base = git(&root, &["rev-parse", "--abbrev-ref", "HEAD"]);
+        std::fs::write(root.join("shared.txt"), "original\n").unwrap();
+        git(&root, &["add", "shared.txt"]);
+        git(&root, &["commit", "-m", "Add shared"]);
+
+        let worktree = manager.create("task-sync", "hive/task-sync", &base).unwrap().0;
+        std::fs::write(worktree.join("shared.txt"), "task\n").unwrap();
+        git(&worktree, &["commit", "-am", "Task change"]);
+
+        if conflicting {
+            std::fs::write(root.join("shared.txt"), "base\n").unwrap();
+        } else {
+            std::fs::write(root.join("other.txt"), "base\n").unwrap();
+            git(&root, &["add", "other.txt"]);
+        }
+        git(&root, &["commit", "-am", "Base change"]);
+        (temp_dir, manager, worktree, base)
+    }
+
+    #[test]
+    fn test_sync_up_to_date() {
+        let (temp_dir, manager) = create_git_manager();
+        let base = git(temp_dir.path(), &["rev-parse", "--abbrev-ref", "HEAD"]);
+        let worktree = manager.create("task-sync", "hive/task-sync", &base).unwrap().0;
+
+        let outcome = manager.sync(&worktree, &base, SyncStrategy::Rebase).unwrap();
+        assert_eq!(outcome, SyncOutcome::UpToDate);
+    }
+
+    #[test]
+    fn test_sync_rebase_and_merge() {
+        for strategy in [SyncStrategy::Rebase, SyncStrategy::Merge] {
+            let (_temp, manager, worktree, base) = create_diverged(false);
+
+            let outcome = manager.sync(&worktree, &base, strategy).unwrap();
+            assert_eq!(outcome, SyncOutcome::Synced);
+            assert!(worktree.join("other.txt").exists());
+            assert_eq!(
+                manager.sync(&worktree, &base, strategy).unwrap(),
+                SyncOutcome::UpToDate
+            );
+        }
+    }
+
+    #[test]
+    fn test_sync_conflict_aborts_and_reports_files() {
+        for strategy in [SyncStrategy::Rebase, SyncStrategy::Merge] {
+            let (_temp, manager, worktree, base) = create_diverged(true);
+            let head_before = git(&worktree, &["rev-parse", "HEAD"]);
+
+            let outcome = manager.sync(&worktree, &base, strategy).unwrap();
+            assert_eq!(outcome, SyncOutcome::Conflicts(vec!["shared.txt".into()]));
+
+            // Aborted: HEAD and files unchanged, no conflict left behind
+            assert_eq!(git(&worktree, &["rev-parse", "HEAD"]), head_before);
+            let content = std::fs::read_to_string(worktree.join("shared.txt")).unwrap();
+            assert_eq!(content, "task\n");
+            assert!(WorktreeValidator::new(worktree.clone())
+                .conflicted_files()
+                .unwrap()
+                .is_empty());
+        }
+    }
+
+    #[test]
+    fn test_recreate_and_conclude_conflict() {
+        let (_temp, manager, worktree, base) = create_diverged(true);
+        let validator = WorktreeValidator::new(worktree.clone());
+
+        let conflicts = manager.recreate_conflict(&worktree, &base).unwrap();
+        assert_eq!(conflicts, vec!["shared.txt".to_string()]);
+        // Recreating again reuses the in-progress conflict
+        assert_eq!(manager.recreate_conflict(&worktree, &base).unwrap(), conflicts);
+        assert_eq!(
+            validator.files_with_conflict_markers(&conflicts).unwrap(),
+            conflicts
+        );
+        assert_eq!(validator.incoming_commits(&base, 10).unwrap().len(), 1);
+
+        // Resolve and conclude
+        std::fs::write(worktree.join("shared.txt"), "task and base\n").unwrap();
+        assert!(validator.files_with_conflict_markers(&conflicts).unwrap().is_empty());
+        manager.conclude_merge(&worktree, &conflicts).unwrap();
+
+        assert!(validator.conflicted_files().unwrap().is_empty());
+        assert!(!validator.has_tracked_changes().unwrap());
+        assert!(validator.incoming_commits(&base, 10).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_merge_conflict_leaves_repository_untouched() {
+        for strategy in [MergeStrategy::Merge, MergeStrategy::Squash, MergeStrategy::Rebase] {
+            let (temp_dir, manager, _worktree, base) = create_diverged(true);
+            let root = temp_dir.path();
+            let tip_before = git(root, &["rev-parse", &base]);
+
+            let err = manager
+                .merge("hive/task-sync", &base, strategy, "Merge")
+                .unwrap_err();
+            assert!(matches!(err, GitError::MergeConflicts(ref files) if files == &["shared.txt"]));
+
+            assert_eq!(git(root, &["rev-parse", &base]), tip_before);
+            assert!(git(root, &["status", "--porcelain", "--untracked-files=no"]).is_empty());
+            assert_eq!(std::fs::read_to_string(root.join("shared.txt")).unwrap(), "base\n");
+        }
+    }
+
+    #[test]
+    fn test_merge_updates_target_checked_out_in_linked_worktree() {
+        let (temp_dir, manager) = create_release_target();
+        let root = temp_dir.path();
+        let release = root.join(".hive").join("release");
+        git(root, &["worktree", "add", "-q", release.to_str().unwrap(), "release"]);
+
+        let tip = manager
+            .merge("hive/task-sync", "release", MergeStrategy::Merge, "Merge")
+            .unwrap();
+        assert_eq!(git(&release, &["rev-parse", "HEAD"]), tip);
+        assert_eq!(std::fs::read_to_string(release.join("shared.txt")).unwrap(), "task\n");
+        assert!(git(&release, &["status", "--porcelain", "--untracked-files=no"]).is_empty());
+        assert_ne!(git(root, &["rev-parse", "HEAD"]), tip);
+
+        // Local changes in that worktree block the merge
+        std::fs::write(release.join("other.txt"), "local edit\n").unwrap();
+        let err = manager
+            .merge("hive/task-sync", "release", MergeStrategy::Merge, "Merge again")
+            .unwrap_err();
+        assert!(matches!(err, GitError::DirtyWorktree(_)));
+    }
+
+    #[test]
+    fn test_merge_refuses_dirty_checked_out_target() {
+        let (temp_dir, manager, _worktree, base) = create_diverged(false);
+        std::fs::write(temp_dir.path().join("other.txt"), "local edit\n").unwrap();
+
+        let err = manager
+            .merge("hive/task-sync", &base, MergeStrategy::Merge, "Merge")
+            .unwrap_err();
+        assert!(matches!(err, GitError::DirtyWorktree(_)));
+    }
+
+    #[test]
+    fn test_sync_refuses_dirty_worktree() {
+        let (_temp, manager, worktree, base) = create_diverged(false);
+        std::fs::write(worktree.join("shared.txt"), "dirty\n").unwrap();
+
+        let err = manager.sync(&worktree, &base, SyncStrategy::Rebase).unwrap_err();
+        assert!(matches!(err, GitError::DirtyWorktree(_)));
+    }
+
     // ========================================
     // Edge Cases
     // ========================================
@@ -244,10 +1018,10 @@ mod tests {
         let (_temp, manager) = create_test_manager();
 
         // These should work as branch names
-        let branch = manager.get_branch_name("task-with-dashes");
+        let branch = manager.get_branch_name("task-with-dashes", "");
         assert_eq!(branch, "hive/task-with-dashes");
 
-        let branch = manager.get_branch_name("task_with_underscores");
+        let branch = manager.get_branch_name("task_with_underscores", "");
         assert_eq!(branch, "hive/task_with_underscores");
     }
 }
@@ -0,0 +1,87 @@
+use std::path::Path;
+use std::sync::OnceLock;
+
+use ratatui::style::Color;
+use syntect::easy::HighlightLines;
+use syntect::highlighting::{Theme, ThemeSet};
+use syntect::parsing::SyntaxSet;
+
+/// Theme used for diff hunks
+const THEME: &str = "base16-ocean.dark";
+
+/// Colored pieces of one line
+pub type Segments = Vec<(Color, String)>;
+
+struct Assets {
+    syntaxes: SyntaxSet,
+    theme: Theme,
+}
+
+/// Syntax definitions and theme, loaded on first use
+fn assets() -> &'static Assets {
+    static ASSETS: OnceLock<Assets> = OnceLock::new();
+    ASSETS.get_or_init(|| Assets {
+        syntaxes: SyntaxSet::load_defaults_newlines(),
+        theme: ThemeSet::load_defaults()
+            .themes
+            .remove(THEME)
+            .unwrap_or_default(),
+    })
+}
+
+/// Highlight consecutive lines of `path` (parser state carries across lines).
+/// Returns None when the language is not recognized.
+pub fn highlight_lines<'a>(
+    path: &str,
+    lines: impl IntoIterator<Item = &'a str>,
+) -> Option<Vec<Segments>> {
+    let assets = assets();
+    let path = Path::new(path);
+    let syntax = path
+        .extension()
+        .and_then(|ext| assets.syntaxes.find_syntax_by_extension(ext.to_str()?))
+        .or_else(|| {
+            let name = path.file_name()?.to_str()?;
+            assets.syntaxes.find_syntax_by_extension(name)
+        })?;
+
+    let mut highlighter = HighlightLines::new(syntax, &assets.theme);
+    let mut highlighted = Vec::new();
+    for line in lines {
+        let line = format!("{}\n", line);
+        let ranges = highlighter.highlight_line(&line, &assets.syntaxes).ok()?;
+        let segments = ranges
+            .into_iter()
+            .map(|(style, text)| {
+                let color = Color::Rgb(style.foreground.r, style.foreground.g, style.foreground.b);
+                (color, text.trim_end_matches('\n').to_string())
+            })
+            .filter(|(_, text)| !text.is_empty())
+            .collect();
+        highlighted.push(segments);
+    }
+    Some(highlighted)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_highlight_known_language() {
+        let lines = highlight_lines("src/main.rs", ["fn main() {", "    let x = 1;", "}"]).unwrap();
+        assert_eq!(lines.len(), 3);
+
+        // Text is preserved and keywords get their own color
+        let text: String = lines[1].iter().map(|(_, t)| t.as_str()).collect();
+        assert_eq!(text, "    let x = 1;");
+        let colors: std::collections::HashSet<_> = lines[0].iter().map(|(c, _)| *c).collect();
+        assert!(colors.len() > 1);
+    }
+
+    #[test]
+    fn test_highlight_unknown_language() {
+        assert!(highlight_lines("notes.unknownext", ["text"]).is_none());
+        assert!(highlight_lines("LICENSE", ["text"]).is_none());
+    }
+}
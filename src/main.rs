@@ -1,11 +1,17 @@
 mod agent;
+mod diff_view;
+mod doctor;
 mod git;
+mod highlight;
+mod pr;
+mod process;
 mod task;
 
 use std::io;
 use std::path::PathBuf;
 use std::sync::Arc;
 
+use clap::{Parser, Subcommand};
 use tokio::sync::Mutex;
 
 use crossterm::{
@@ -19,9 +25,40 @@ use ratatui::{
 };
 use tokio::sync::mpsc;
 
-use agent::{AgentRunner, AgentStatus, OrchestratorConfig, PlanManager};
-use git::{GitValidator, WorktreeManager, WorktreeValidator};
-use task::{Task, TaskStatus, TaskStore};
+use agent::{
+    AgentConfig, AgentRole, AgentRunner, AgentStatus, BudgetLimits, OrchestratorConfig,
+    PlanManager, Spend, TokenUsage, UsageLedger, write_settings,
+};
+use diff_view::{DiffRow, DiffView};
+use git::{
+    render_template, BranchNaming, ChangeKind, GitConfig, GitError, GitValidator, LineKind, Orphan, SyncOutcome,
+    ValidationResult, WorktreeManager, WorktreeValidator,
+};
+use pr::{NewPullRequest, PrBody, PrConfig, PrState, PrSummary, PullRequestProvider, ReviewDecision};
+use task::{ReviewComment, RunRecord, RunStatus, Task, TaskStatus, TaskStore};
+
+/// Command line interface
+#[derive(Parser)]
+#[command(version, about)]
+struct Cli {
+    #[command(subcommand)]
+    command: Option<Commands>,
+}
+
+#[derive(Subcommand)]
+enum Commands {
+    /// Check agent CLIs, git, gh and repository state
+    Doctor,
+    /// Find and prune worktrees and branches no active task uses
+    Gc {
+        /// Prune without asking for confirmation
+        #[arg(long)]
+        yes: bool,
+        /// Also delete task branches that are not merged into their base
+        #[arg(long)]
+        unmerged: bool,
+    },
+}
 
 /// Events from agents
 #[derive(Debug, Clone)]
@@ -32,6 +69,25 @@ enum AgentEvent {
     Failed { task_id: String, error: String },
     /// Output line
     Output { task_id: String, line: String },
+    /// Commit message generated for executor leftovers
+    CommitMessage {
+        task_id: String,
+        message: Result<String, String>,
+    },
+    /// Polled pull request states (task id, PR URL, status or error)
+    PullRequests {
+        results: Vec<(String, String, Result<PrSummary, String>)>,
+    },
+    /// Run finished (sent before Completed/Failed)
+    RunFinished {
+        task_id: String,
+        run_id: String,
+        status: RunStatus,
+        exit_code: Option<i32>,
+        error: Option<String>,
+        usage: TokenUsage,
+        duration_secs: u64,
+    },
 }
 
 /// Input mode
@@ -50,8 +106,18 @@ enum InputMode {
     TaskDetail,
     /// Viewing diff
     ViewDiff,
+    /// Writing a review comment on the diff line under the cursor
+    ReviewComment,
+    /// Entering a follow-up instruction for the executor
+    FollowUp,
     /// Confirming merge
     ConfirmMerge,
+    /// Confirming rerun of a recorded run
+    ConfirmRerun,
+    /// Confirming pruning of orphaned worktrees and branches
+    ConfirmGc,
+    /// Confirming waiver of a task's secret findings
+    ConfirmWaiveSecrets,
     /// Showing help
     Help,
     /// Settings screen
@@ -80,14 +146,32 @@ struct App {
     input_buffer: String,
     /// Pending task title (temporary storage)
     pending_title: String,
+    /// Parent of the task being created (stacked task)
+    pending_parent: Option<String>,
     /// Status message
     status_message: Option<String>,
     /// Worktree manager
     worktree_manager: WorktreeManager,
     /// Git validator
     git_validator: GitValidator,
+    /// Forge the project's pull requests live on
+    pr_provider: Arc<dyn PullRequestProvider>,
+    /// PR config (description template and options)
+    pr_config: PrConfig,
+    /// Interval between PR status polls (None when disabled)
+    pr_poll_interval: Option<std::time::Duration>,
+    /// When PR statuses were last polled
+    last_pr_poll: Option<std::time::Instant>,
+    /// A PR status poll is running
+    pr_poll_in_flight: bool,
+    /// Error of the last PR status poll (shown once)
+    pr_poll_error: Option<String>,
     /// Orchestrator config
     orchestrator: OrchestratorConfig,
+    /// Git config
+    git_config: GitConfig,
+    /// Default branch detected from the repository
+    detected_base_branch: String,
     /// Plan manager
     plan_manager: PlanManager,
     /// Selection list (shared for Planner/Executor)
@@ -100,10 +184,8 @@ struct App {
     agent_event_rx: mpsc::Receiver<AgentEvent>,
     /// Agent event sender (for cloning)
     agent_event_tx: mpsc::Sender<AgentEvent>,
-    /// Diff content (for ViewDiff mode)
-    diff_content: String,
-    /// Scroll offset for diff view
-    diff_scroll: usize,
+    /// Per-file diff (for ViewDiff mode)
+    diff_view: DiffView,
     /// Running agent count (cached)
     running_count: usize,
     /// Agent log buffer (recent output lines)
@@ -112,6 +194,16 @@ struct App {
     spinner_frame: usize,
     /// Settings focus: 0 = planner, 1 = executor
     settings_focus: usize,
+    /// Agents whose command was not found on PATH
+    unavailable_agents: std::collections::HashSet<String>,
+    /// Path to .hive directory
+    hive_dir: PathBuf,
+    /// Selected run index in task detail view
+    detail_run_index: usize,
+    /// Orphans pending GC confirmation
+    gc_orphans: Vec<Orphan>,
+    /// Usage of all runs, including those of deleted tasks (daily budget)
+    usage_ledger: UsageLedger,
 }
 
 /// Spinner animation frames
@@ -122,12 +214,41 @@ impl App {
         let repo_root = PathBuf::from(".");
         let hive_dir = PathBuf::from(".hive");
         let store = TaskStore::new(&repo_root)?;
-        let tasks = store.load()?;
-        let worktree_manager = WorktreeManager::new(repo_root.clone(), hive_dir.clone());
-        let git_validator = GitValidator::new(repo_root);
-        let orchestrator = OrchestratorConfig::load(&hive_dir).unwrap_or_default();
+        let mut tasks = store.load()?;
+        // No agent survives a restart: runs still marked running were interrupted
+        let mut interrupted = false;
+        for task in &mut tasks {
+            interrupted |= task.interrupt_stale_runs();
+        }
+        if interrupted {
+            store.save(&tasks)?;
+        }
+        let usage_ledger = UsageLedger::load(&hive_dir, tasks.iter().flat_map(|t| &t.usage))?;
+        // A broken orchestrator section falls back to the defaults, but says so
+        let (orchestrator, config_error) = match OrchestratorConfig::load(&hive_dir) {
+            Ok(orchestrator) => (orchestrator, None),
+            Err(e) => (
+                OrchestratorConfig::default(),
+                Some(format!("⚠️ {:#} (using default agents)", e)),
+            ),
+        };
+        let git_config = GitConfig::load(&hive_dir).unwrap_or_default();
+        let worktree_manager = WorktreeManager::new(repo_root.clone(), hive_dir.clone())
+            .with_branch_naming(BranchNaming::new(&git_config.branch, &repo_root))
+            .with_bootstrap(git_config.bootstrap.clone());
+        let pr_config = PrConfig::load(&hive_dir).unwrap_or_default();
+        let pr_provider = Arc::from(pr_config.provider(&repo_root, &hive_dir));
+        let git_validator = GitValidator::new(repo_root).with_min_free_space(git_config.min_free_space());
+        let detected_base_branch = git_validator.default_branch().unwrap_or_else(|_| "main".into());
+        let unavailable_agents = orchestrator
+            .planners
+            .keys()
+            .chain(orchestrator.executors.keys())
+            .filter(|name| !doctor::is_agent_available(&orchestrator, name))
+            .cloned()
+            .collect();
         let plan_manager = PlanManager::new(hive_dir.clone());
-        let agent_runner = Arc::new(Mutex::new(AgentRunner::new(hive_dir)));
+        let agent_runner = Arc::new(Mutex::new(AgentRunner::new(hive_dir.clone())));
         let (agent_event_tx, agent_event_rx) = mpsc::channel(100);
 
         Ok(Self {
@@ -138,22 +259,35 @@ impl App {
             input_mode: InputMode::Normal,
             input_buffer: String::new(),
             pending_title: String::new(),
-            status_message: None,
+            pending_parent: None,
+            status_message: config_error,
             worktree_manager,
             git_validator,
+            pr_provider,
+            pr_poll_interval: pr_config.poll_interval(),
+            pr_config,
+            last_pr_poll: None,
+            pr_poll_in_flight: false,
+            pr_poll_error: None,
             orchestrator,
+            git_config,
+            detected_base_branch,
             plan_manager,
             selection_list: vec![],
             selected_index: 0,
             agent_runner,
             agent_event_rx,
             agent_event_tx,
-            diff_content: String::new(),
-            diff_scroll: 0,
+            diff_view: DiffView::default(),
             running_count: 0,
             agent_logs: std::collections::VecDeque::with_capacity(100),
             spinner_frame: 0,
             settings_focus: 0,
+            unavailable_agents,
+            hive_dir,
+            detail_run_index: 0,
+            gc_orphans: Vec::new(),
+            usage_ledger,
         })
     }
 
@@ -225,9 +359,25 @@ impl App {
     fn start_new_task(&mut self) {
         self.input_mode = InputMode::NewTaskTitle;
         self.input_buffer.clear();
+        self.pending_parent = None;
         self.status_message = Some("Enter task title (ESC to cancel)".into());
     }
 
+    /// Start creating a task stacked on the selected task's branch
+    fn start_stacked_task(&mut self) {
+        let Some(parent) = self.selected_task() else {
+            return;
+        };
+        if let Err(e) = Task::new("", "").stack_on(parent) {
+            self.status_message = Some(format!("❌ {}", e));
+            return;
+        }
+        let (parent_id, title) = (parent.id.clone(), parent.title.clone());
+        self.start_new_task();
+        self.pending_parent = Some(parent_id);
+        self.status_message = Some(format!("Enter title of the task stacked on '{}' (ESC to cancel)", title));
+    }
+
     /// Start agent selection (select Planner/Executor based on status)
     fn start_assign_agent(&mut self) {
         if let Some(task) = self.selected_task() {
@@ -280,31 +430,61 @@ impl App {
         planner_name: &str,
     ) -> anyhow::Result<()> {
         // Get task info
-        let (task_title, task_description) = {
-            let task = self
-                .tasks
-                .iter()
-                .find(|t| t.id == task_id)
-                .ok_or_else(|| anyhow::anyhow!("Task not found"))?;
-            (task.title.clone(), task.description.clone())
+        let task = self
+            .tasks
+            .iter()
+            .find(|t| t.id == task_id)
+            .cloned()
+            .ok_or_else(|| anyhow::anyhow!("Task not found"))?;
+        let (task_title, task_description) = (task.title.clone(), task.description.clone());
+        let base_branch = self.task_base_branch(&task);
+
+        if let Err(msg) = self.check_agent_available(planner_name) {
+            self.status_message = Some(format!("❌ {}", msg));
+            return Ok(());
+        }
+
+        // Check budget before starting
+        let limits = match self.run_budget(task_id, planner_name) {
+            Ok(limits) => limits,
+            Err(msg) => {
+                self.status_message = Some(format!("💸 {}", msg));
+                return Ok(());
+            }
         };
 
         // Run git validation
-        let validation = self.git_validator.validate_for_task_start(task_id, "hive")?;
+        let naming = self.worktree_manager.branch_naming();
+        let validation = self
+            .git_validator
+            .validate_for_task_start(&task, naming, &base_branch)?;
         if !validation.is_valid {
             self.status_message = Some(format!("❌ {}", validation.errors.join(", ")));
             return Ok(());
         }
 
         // Create worktree
-        let worktree_path = self.worktree_manager.create(task_id)?;
-        let branch_name = self.worktree_manager.get_branch_name(task_id);
+        let branch_name = naming.branch_for(&task, |b| self.git_validator.branch_exists(b).unwrap_or(false));
+        let (worktree_path, bootstrap_errors) =
+            self.worktree_manager.create(task_id, &branch_name, &base_branch)?;
+        let bootstrap_failed = !bootstrap_errors.is_empty();
+
+        // Remember which parent commit a stacked branch starts from (for restacking)
+        let parent_head = task
+            .parent
+            .as_ref()
+            .and_then(|_| self.worktree_manager.branch_tip(&base_branch).ok());
 
         // Update task
         if let Some(task) = self.tasks.iter_mut().find(|t| t.id == task_id) {
             task.assign_planner(planner_name);
+            if parent_head.is_some() {
+                task.parent_head = parent_head;
+            }
             task.branch = Some(branch_name.clone());
+            task.base_branch = Some(base_branch);
             task.worktree = Some(worktree_path.to_string_lossy().to_string());
+            task.bootstrap_errors = bootstrap_errors;
             task.set_status(TaskStatus::Planning);
         }
 
@@ -316,17 +496,22 @@ impl App {
             .create_planning_prompt(task_id, &task_title, &task_description);
 
         // Start agent in background
-        self.start_agent(
-            task_id.to_string(),
-            planner_name,
-            worktree_path,
-            prompt,
-        );
+        let config = self.agent_config(planner_name)?;
+        self.begin_run(task_id, AgentRole::Planner, config, worktree_path, prompt, limits)?;
 
-        self.status_message = Some(format!(
-            "🧠 Planner '{}' started for '{}' (branch: {})",
-            planner_name, task_title, branch_name
-        ));
+        let message = if bootstrap_failed {
+            format!(
+                "⚠️ Planner '{}' started for '{}', but worktree bootstrap failed (see task detail)",
+                planner_name, task_title
+            )
+        } else {
+            format!(
+                "🧠 Planner '{}' started for '{}' (branch: {})",
+                planner_name, task_title, branch_name
+            )
+        };
+        // Repository state warnings (with remediation hints)
+        self.status_message = Some(with_notes(message, &validation.warnings));
 
         Ok(())
     }
@@ -367,6 +552,20 @@ impl App {
             )
         };
 
+        if let Err(msg) = self.check_agent_available(executor_name) {
+            self.status_message = Some(format!("❌ {}", msg));
+            return Ok(());
+        }
+
+        // Check budget before starting
+        let limits = match self.run_budget(task_id, executor_name) {
+            Ok(limits) => limits,
+            Err(msg) => {
+                self.status_message = Some(format!("💸 {}", msg));
+                return Ok(());
+            }
+        };
+
         // Create execution prompt
         let prompt = self.plan_manager.create_execution_prompt(task_id)?;
 
@@ -379,12 +578,8 @@ impl App {
         self.store.save(&self.tasks)?;
 
         // Start agent in background
-        self.start_agent(
-            task_id.to_string(),
-            executor_name,
-            worktree_path,
-            prompt,
-        );
+        let config = self.agent_config(executor_name)?;
+        self.begin_run(task_id, AgentRole::Executor, config, worktree_path, prompt, limits)?;
 
         self.status_message = Some(format!(
             "🔨 Executor '{}' started for '{}'",
@@ -394,6 +589,229 @@ impl App {
         Ok(())
     }
 
+    /// Project base branch (configured, otherwise detected)
+    fn default_base_branch(&self) -> String {
+        self.git_config
+            .base_branch
+            .clone()
+            .unwrap_or_else(|| self.detected_base_branch.clone())
+    }
+
+    /// Base branch of a task (falls back to the project base branch)
+    fn task_base_branch(&self, task: &Task) -> String {
+        task.base_branch
+            .clone()
+            .unwrap_or_else(|| self.default_base_branch())
+    }
+
+    /// Resolve agent configuration (configured spec first, then built-in defaults)
+    fn agent_config(&self, agent_name: &str) -> anyhow::Result<AgentConfig> {
+        self.orchestrator
+            .get_agent(agent_name)
+            .map(|spec| AgentConfig::from_spec(agent_name, spec))
+            .or_else(|| AgentConfig::from_name(agent_name))
+            .ok_or_else(|| anyhow::anyhow!("Unknown agent: {}", agent_name))
+    }
+
+    /// Record a new run on the task and start its agent. Returns the run ID.
+    fn begin_run(
+        &mut self,
+        task_id: &str,
+        role: AgentRole,
+        mut config: AgentConfig,
+        working_dir: PathBuf,
+        prompt: String,
+        limits: BudgetLimits,
+    ) -> anyhow::Result<String> {
+        config.env.extend(self.worktree_manager.bootstrap_env(&working_dir));
+        let settings = config.settings.files(&config.name, self.plan_manager.plans_dir());
+        let written = write_settings(&working_dir, &settings)?;
+        self.worktree_manager.mark_generated(&working_dir, &written)?;
+        let mut run = RunRecord::new(
+            role,
+            &config.name,
+            &config.command,
+            config.args.clone(),
+            &prompt,
+            working_dir.to_string_lossy(),
+        );
+        run.base_commit = WorktreeValidator::new(working_dir.clone()).head_commit().ok();
+        run.log_path = Some(
+            AgentRunner::log_path(&self.hive_dir, task_id, &run.id)
+                .to_string_lossy()
+                .to_string(),
+        );
+        let run_id = run.id.clone();
+
+        if let Some(task) = self.tasks.iter_mut().find(|t| t.id == task_id) {
+            task.start_run(run);
+        }
+        self.store.save(&self.tasks)?;
+
+        self.start_agent(task_id.to_string(), run_id.clone(), config, working_dir, prompt, limits);
+        Ok(run_id)
+    }
+
+    /// Start rerun confirmation for the run selected in task detail
+    fn start_rerun(&mut self) {
+        let Some(task) = self.selected_task() else {
+            return;
+        };
+        let Some(run) = task.runs.get(self.detail_run_index) else {
+            self.status_message = Some("No run selected".into());
+            return;
+        };
+        if task.has_running_run() {
+            self.status_message = Some("An agent is still running for this task".into());
+            return;
+        }
+        if let Some(reason) = task.rerun_blocked() {
+            self.status_message = Some(format!("❌ {}", reason));
+            return;
+        }
+        if run.base_commit.is_none() {
+            self.status_message = Some("Run has no recorded base commit".into());
+            return;
+        }
+        self.status_message = Some(format!("Rerun {} exactly? (y/n)", run.id));
+        self.input_mode = InputMode::ConfirmRerun;
+    }
+
+    /// Rerun a recorded run with the same command/args on a fresh worktree at its base commit.
+    /// The rerun's worktree and branch are kept on its run record; the task is left as it is.
+    fn rerun(&mut self, task_id: &str, run_id: &str) -> anyhow::Result<()> {
+        let (run, branch) = {
+            let task = self
+                .tasks
+                .iter()
+                .find(|t| t.id == task_id)
+                .ok_or_else(|| anyhow::anyhow!("Task not found"))?;
+            if let Some(reason) = task.rerun_blocked() {
+                anyhow::bail!(reason);
+            }
+            let run = task
+                .get_run(run_id)
+                .cloned()
+                .ok_or_else(|| anyhow::anyhow!("Run not found"))?;
+            let branch = task
+                .branch
+                .clone()
+                .unwrap_or_else(|| self.worktree_manager.get_branch_name(task_id, &task.title));
+            (run, branch)
+        };
+        let base_commit = run
+            .base_commit
+            .clone()
+            .ok_or_else(|| anyhow::anyhow!("Run has no recorded base commit"))?;
+
+        if doctor::resolve_command(&run.command).is_none() {
+            self.status_message = Some(format!("❌ '{}' not found on PATH", run.command));
+            return Ok(());
+        }
+
+        // Reproduce the exact invocation (usage parsing still follows the agent's config)
+        let mut config = self.agent_config(&run.agent)?;
+        config.name = run.agent.clone();
+        config.command = run.command.clone();
+        config.args = run.args.clone();
+
+        let limits = match self.run_budget(task_id, &run.agent) {
+            Ok(limits) => limits,
+            Err(msg) => {
+                self.status_message = Some(format!("💸 {}", msg));
+                return Ok(());
+            }
+        };
+
+        // Fresh worktree and branch at the recorded base commit
+        let stamp = chrono::Utc::now().format("%Y%m%d%H%M%S");
+        let rerun_branch = format!("{}-rerun-{}", branch, stamp);
+        let (worktree_path, bootstrap_errors) = self.worktree_manager.create_at(
+            &format!("{}-rerun-{}", task_id, stamp),
+            &rerun_branch,
+            &base_commit,
+        )?;
+
+        let new_run_id =
+            self.begin_run(task_id, run.role, config, worktree_path.clone(), run.prompt.clone(), limits)?;
+        if let Some(new_run) = self
+            .tasks
+            .iter_mut()
+            .find(|t| t.id == task_id)
+            .and_then(|t| t.runs.iter_mut().find(|r| r.id == new_run_id))
+        {
+            new_run.rerun_of = Some(run.id.clone());
+            new_run.follow_up = run.follow_up.clone();
+            new_run.branch = Some(rerun_branch);
+        }
+        self.store.save(&self.tasks)?;
+
+        self.input_mode = InputMode::Normal;
+        let bootstrap_note = if bootstrap_errors.is_empty() {
+            String::new()
+        } else {
+            format!(" (bootstrap: {})", bootstrap_errors.join("; "))
+        };
+        self.status_message = Some(format!(
+            "🔁 Rerunning {} ({} {}) at {} in {}{}",
+            run.id,
+            run.role.display_name(),
+            run.agent,
+            run.short_base_commit(),
+            worktree_path.display(),
+            bootstrap_note
+        ));
+        self.clamp_selection();
+        Ok(())
+    }
+
+    /// Check that the agent's command is installed
+    fn check_agent_available(&self, agent_name: &str) -> Result<(), String> {
+        if self.unavailable_agents.contains(agent_name) {
+            let command = self
+                .orchestrator
+                .get_agent(agent_name)
+                .map(|spec| spec.command.as_str())
+                .unwrap_or(agent_name);
+            return Err(format!(
+                "Agent '{}' is not available ('{}' not found on PATH). Run `hive doctor`",
+                agent_name, command
+            ));
+        }
+        Ok(())
+    }
+
+    /// Compute budget for a new run, or an error if the task or daily budget is exhausted
+    fn run_budget(&self, task_id: &str, agent_name: &str) -> Result<BudgetLimits, String> {
+        let task = self
+            .tasks
+            .iter()
+            .find(|t| t.id == task_id)
+            .ok_or_else(|| "Task not found".to_string())?;
+
+        let daily_limits = self.orchestrator.budget.daily;
+        let daily_spend = Spend::today(self.usage_ledger.records());
+        if let Some(reason) = daily_limits.exceeded(&daily_spend) {
+            return Err(format!("Daily budget exhausted ({})", reason));
+        }
+
+        let task_limits = task.budget.unwrap_or(self.orchestrator.budget.per_task);
+        let task_spend = Spend::from_records(&task.usage);
+        if let Some(reason) = task_limits.exceeded(&task_spend) {
+            return Err(format!("Task budget exhausted ({})", reason));
+        }
+
+        let agent_limits = self
+            .orchestrator
+            .get_agent(agent_name)
+            .map(|spec| spec.budget)
+            .unwrap_or_default();
+
+        Ok(agent_limits
+            .tightest(task_limits.remaining(&task_spend))
+            .tightest(daily_limits.remaining(&daily_spend)))
+    }
+
     /// Move task to next status (with strict validation)
     fn move_task_forward(&mut self) -> anyhow::Result<()> {
         // First validate in read-only mode
@@ -406,10 +824,11 @@ impl App {
             match task.can_advance() {
                 Ok(new_status) => {
                     // For Planning → PlanReview, check if plan file exists
-                    if task.status == TaskStatus::Planning {
-                        if !self.plan_manager.plan_file_exists(&task.id) {
-                            return Ok(self.status_message = Some("Plan has not been created".into()));
-                        }
+                    if task.status == TaskStatus::Planning
+                        && !self.plan_manager.plan_file_exists(&task.id)
+                    {
+                        self.status_message = Some("Plan has not been created".into());
+                        return Ok(());
                     }
                     Ok(new_status)
                 }
@@ -452,12 +871,16 @@ impl App {
         if let Some(task) = self.selected_task() {
             let id = task.id.clone();
             // Remove worktree if exists
-            if task.worktree.is_some() {
-                let _ = self.worktree_manager.remove(&id);
-            }
+            let removed = match &task.worktree {
+                Some(worktree) => self.worktree_manager.remove_path(std::path::Path::new(worktree)),
+                None => Ok(()),
+            };
             self.store.delete(&id)?;
             self.tasks = self.store.load()?;
-            self.status_message = Some("Task deleted".into());
+            self.status_message = Some(match removed {
+                Ok(()) => "Task deleted".into(),
+                Err(e) => format!("Task deleted, but its worktree was not removed: {} (run G to clean up)", e),
+            });
             self.clamp_selection();
         }
         Ok(())
@@ -465,9 +888,12 @@ impl App {
 
     /// Show task detail view
     fn show_task_detail(&mut self) {
-        if self.selected_task().is_some() {
+        if let Some(task) = self.selected_task() {
+            self.detail_run_index = task.runs.len().saturating_sub(1);
             self.input_mode = InputMode::TaskDetail;
-            self.status_message = Some("Task Detail (ESC to close, s to stop agent, d for diff)".into());
+            self.status_message = Some(
+                "Task Detail (ESC close, s stop agent, d diff, j/k select run, r rerun)".into(),
+            );
         } else {
             self.status_message = Some("No task selected".into());
         }
@@ -502,12 +928,7 @@ impl App {
         };
 
         // Update orchestrator section
-        config["orchestrator"] = serde_json::json!({
-            "default_planner": self.orchestrator.default_planner,
-            "default_executor": self.orchestrator.default_executor,
-            "planners": self.orchestrator.planners,
-            "executors": self.orchestrator.executors,
-        });
+        config["orchestrator"] = serde_json::to_value(&self.orchestrator)?;
 
         std::fs::write(&config_path, serde_json::to_string_pretty(&config)?)?;
         Ok(())
@@ -541,17 +962,20 @@ impl App {
     /// Show diff view for selected task
     fn show_diff(&mut self) -> anyhow::Result<()> {
         if let Some(task) = self.selected_task() {
-            if task.worktree.is_some() {
+            if let Some(worktree) = &task.worktree {
                 // Check if worktree exists
-                if self.worktree_manager.exists(&task.id) {
-                    let diff = self.worktree_manager.get_diff(&task.id, "main")?;
-                    if diff.is_empty() {
+                let worktree_path = PathBuf::from(worktree);
+                if worktree_path.exists() {
+                    let base_branch = self.task_base_branch(task);
+                    let files = self.worktree_manager.file_diffs(&worktree_path, &base_branch)?;
+                    if files.is_empty() {
                         self.status_message = Some("No changes found".into());
                     } else {
-                        self.diff_content = diff;
-                        self.diff_scroll = 0;
+                        self.diff_view = DiffView::new(files);
                         self.input_mode = InputMode::ViewDiff;
-                        self.status_message = Some("Diff View (j/k scroll, ESC close)".into());
+                        self.status_message = Some(
+                            "Diff View (j/k move, n/N hunk, Tab/S-Tab file, v layout, c comment, R request changes, ESC close)".into(),
+                        );
                     }
                 } else {
                     self.status_message = Some("Worktree not found".into());
@@ -563,70 +987,345 @@ impl App {
         Ok(())
     }
 
-    /// Create PR for a specific task and return the PR URL
-    fn create_pr_for_task(&mut self, task_id: &str) -> Result<String, String> {
-        // Get task info (immutable borrow)
-        let task_info = self.tasks.iter().find(|t| t.id == task_id).map(|t| {
-            (
-                t.branch.clone(),
-                t.worktree.clone(),
-                t.title.clone(),
-                t.description.clone(),
-            )
-        });
+    /// File, line number, side and content of the diff line under the cursor
+    fn diff_cursor_anchor(&self) -> Option<(String, u32, bool, String)> {
+        let (hunk, line) = self.diff_view.cursor_line()?;
+        let diff_line = self.diff_view.line(hunk, line)?;
+        let removed = diff_line.kind == LineKind::Removed;
+        let number = if removed { diff_line.old_line } else { diff_line.new_line }?;
+        let path = self.diff_view.selected_file()?.path.clone();
+        Some((path, number, removed, diff_line.content.clone()))
+    }
+
+    /// Start writing a review comment on the diff line under the cursor
+    fn start_review_comment(&mut self) {
+        if self.diff_cursor_anchor().is_none() {
+            self.status_message = Some("Move the cursor to a line to comment on it".into());
+            return;
+        }
+        self.input_buffer.clear();
+        self.input_mode = InputMode::ReviewComment;
+    }
 
-        let (branch, worktree, title, description) = match task_info {
-            Some((Some(b), Some(w), t, d)) => (b, w, t, d),
-            Some((None, _, _, _)) => return Err("No branch for this task".into()),
-            Some((_, None, _, _)) => return Err("No worktree for this task".into()),
-            None => return Err("Task not found".into()),
+    /// Attach the typed comment to the diff line under the cursor
+    fn save_review_comment(&mut self) -> anyhow::Result<()> {
+        let body = self.input_buffer.trim().to_string();
+        self.input_buffer.clear();
+        self.input_mode = InputMode::ViewDiff;
+        let (Some((path, line, removed, code)), false) = (self.diff_cursor_anchor(), body.is_empty()) else {
+            return Ok(());
+        };
+        let comment = ReviewComment::new(path, line, removed, code, body);
+        let location = comment.location();
+        let count = match self.selected_task_mut() {
+            Some(task) => {
+                task.add_review_comment(comment);
+                task.review_comments.len()
+            }
+            None => return Ok(()),
         };
+        self.store.save(&self.tasks)?;
+        self.status_message = Some(format!(
+            "💬 Commented on {} ({} pending, R to request changes)",
+            location, count
+        ));
+        Ok(())
+    }
 
-        // Push branch first
-        let push_output = std::process::Command::new("git")
-            .args(["push", "-u", "origin", &branch])
-            .current_dir(&worktree)
-            .output();
+    /// Delete the review comments on the diff line under the cursor
+    fn delete_review_comments(&mut self) -> anyhow::Result<()> {
+        let Some((path, line, removed, _)) = self.diff_cursor_anchor() else {
+            return Ok(());
+        };
+        let deleted = self
+            .selected_task_mut()
+            .map(|task| task.remove_review_comments(&path, line, removed))
+            .unwrap_or(0);
+        if deleted > 0 {
+            self.store.save(&self.tasks)?;
+            self.status_message = Some(format!("🗑️ Deleted {} comments on {}:{}", deleted, path, line));
+        }
+        Ok(())
+    }
 
-        match push_output {
-            Ok(result) if !result.status.success() => {
-                let stderr = String::from_utf8_lossy(&result.stderr);
-                return Err(format!("Push failed: {}", stderr.trim()));
-            }
-            Err(e) => {
-                return Err(format!("Failed to run git push: {}", e));
+    /// Submit pending review comments: move the task back to InProgress and rerun
+    /// its executor in the same worktree with the comments
+    fn request_changes(&mut self) -> anyhow::Result<()> {
+        let Some(task) = self.selected_task() else {
+            return Ok(());
+        };
+        if task.status != TaskStatus::Review {
+            self.status_message = Some("Task must be in Review status to request changes".into());
+            return Ok(());
+        }
+        if task.review_comments.is_empty() {
+            self.status_message = Some("No review comments (press c on a diff line to add one)".into());
+            return Ok(());
+        }
+        let task_id = task.id.clone();
+        let count = task.review_comments.len();
+        let comments: Vec<String> = task.review_comments.iter().map(ReviewComment::to_prompt).collect();
+        let prompt = self.plan_manager.create_review_prompt(&task_id, &comments);
+
+        match self.start_revision(&task_id, prompt) {
+            Ok((executor, _)) => {
+                if let Some(task) = self.tasks.iter_mut().find(|t| t.id == task_id) {
+                    task.review_comments.clear();
+                }
+                self.store.save(&self.tasks)?;
+                self.input_mode = InputMode::Normal;
+                self.diff_view = DiffView::default();
+                self.clamp_selection();
+                self.status_message = Some(format!(
+                    "🔁 Requested changes ({} comments); '{}' is revising the task",
+                    count, executor
+                ));
             }
-            _ => {}
+            Err(e) => self.status_message = Some(format!("❌ {}", e)),
         }
+        Ok(())
+    }
 
-        // Create PR using gh command
-        let pr_body = format!(
-            "## Summary\n{}\n\n## Task\nCreated via Hive AI Agent Orchestration\n\n---\n🤖 Generated with Hive",
-            if description.is_empty() { &title } else { &description }
-        );
+    /// Start entering a follow-up instruction for the selected task
+    fn start_follow_up(&mut self) {
+        let Some(task) = self.selected_task() else {
+            return;
+        };
+        if !matches!(task.status, TaskStatus::Review | TaskStatus::InProgress) {
+            self.status_message = Some("Follow-ups need a task in Progress or Review".into());
+            return;
+        }
+        if task.has_running_run() {
+            self.status_message = Some("Wait for the running agent to finish (or stop it) first".into());
+            return;
+        }
+        self.input_buffer.clear();
+        self.input_mode = InputMode::FollowUp;
+        self.status_message = Some("Enter a follow-up instruction for the executor".into());
+    }
+
+    /// Rerun the executor with the original plan plus the entered follow-up instruction
+    fn submit_follow_up(&mut self) -> anyhow::Result<()> {
+        let instruction = self.input_buffer.trim().to_string();
+        self.input_buffer.clear();
+        self.input_mode = InputMode::Normal;
+        let Some(task) = self.selected_task() else {
+            return Ok(());
+        };
+        if instruction.is_empty() {
+            self.status_message = None;
+            return Ok(());
+        }
+        let task_id = task.id.clone();
+        let prompt = self
+            .plan_manager
+            .create_follow_up_prompt(&task_id, &instruction, &task.follow_ups());
+
+        match self.start_revision(&task_id, prompt) {
+            Ok((executor, run_id)) => {
+                if let Some(run) = self
+                    .tasks
+                    .iter_mut()
+                    .find(|t| t.id == task_id)
+                    .and_then(|t| t.runs.iter_mut().find(|r| r.id == run_id))
+                {
+                    run.follow_up = Some(instruction);
+                }
+                self.store.save(&self.tasks)?;
+                self.clamp_selection();
+                self.status_message = Some(format!("➕ '{}' is working on the follow-up", executor));
+            }
+            Err(e) => self.status_message = Some(format!("❌ {}", e)),
+        }
+        Ok(())
+    }
 
-        let output = std::process::Command::new("gh")
-            .args(["pr", "create", "--title", &title, "--body", &pr_body, "--head", &branch])
-            .current_dir(&worktree)
-            .output();
+    /// Rerun the task's executor in its existing worktree with `prompt` and move the
+    /// task back to InProgress. Returns the executor name and the run ID.
+    fn start_revision(&mut self, task_id: &str, prompt: String) -> Result<(String, String), String> {
+        let task = self
+            .tasks
+            .iter()
+            .find(|t| t.id == task_id)
+            .ok_or("Task not found")?;
+        if task.has_running_run() {
+            return Err("An agent is already running for this task".into());
+        }
+        let worktree = task
+            .worktree
+            .clone()
+            .map(PathBuf::from)
+            .filter(|w| w.exists())
+            .ok_or("Worktree not found")?;
+        let executor = task
+            .executor
+            .clone()
+            .unwrap_or_else(|| self.orchestrator.default_executor.clone());
+
+        self.check_agent_available(&executor)?;
+        let limits = self.run_budget(task_id, &executor)?;
+        let config = self.agent_config(&executor).map_err(|e| e.to_string())?;
+
+        let prior_status = task.status;
+        if let Some(task) = self.tasks.iter_mut().find(|t| t.id == task_id) {
+            task.set_status(TaskStatus::InProgress);
+        }
+        let run_id = self
+            .begin_run(task_id, AgentRole::Executor, config, worktree, prompt, limits)
+            .map_err(|e| e.to_string())?;
+        // A failed revision returns the task to where it was, plan and executor intact
+        if let Some(run) = self
+            .tasks
+            .iter_mut()
+            .find(|t| t.id == task_id)
+            .and_then(|t| t.runs.iter_mut().find(|r| r.id == run_id))
+        {
+            run.prior_status = Some(prior_status);
+        }
+        Ok((executor, run_id))
+    }
 
-        match output {
-            Ok(result) => {
-                if result.status.success() {
-                    let url = String::from_utf8_lossy(&result.stdout).trim().to_string();
-                    // Save PR URL to task
-                    if let Some(task) = self.tasks.iter_mut().find(|t| t.id == task_id) {
-                        task.pr_url = Some(url.clone());
-                        let _ = self.store.save(&self.tasks);
-                    }
-                    Ok(url)
+    /// Scan the task diff for possible secrets. Errors while unwaived findings remain.
+    fn check_secrets(&mut self, task_id: &str) -> Result<(), String> {
+        if !self.git_config.secrets.enabled {
+            return Ok(());
+        }
+        let task = self
+            .tasks
+            .iter()
+            .find(|t| t.id == task_id)
+            .ok_or("Task not found")?;
+        let worktree = task.worktree.clone().ok_or("No worktree for this task")?;
+        let base_branch = self.task_base_branch(task);
+        let findings = WorktreeValidator::new(PathBuf::from(worktree))
+            .scan_secrets(&base_branch, &self.git_config.secrets)
+            .map_err(|e| format!("Secret scan failed: {}", e))?;
+
+        let task = self
+            .tasks
+            .iter_mut()
+            .find(|t| t.id == task_id)
+            .ok_or("Task not found")?;
+        task.record_secret_findings(findings);
+        let remaining: Vec<String> = task.secret_findings.iter().map(|f| f.describe()).collect();
+        let _ = self.store.save(&self.tasks);
+        match remaining.as_slice() {
+            [] => Ok(()),
+            [first, rest @ ..] => Err(format!(
+                "🔒 Possible secrets in the diff: {}{} (remove them or press W to waive)",
+                first,
+                if rest.is_empty() {
+                    String::new()
                 } else {
-                    let stderr = String::from_utf8_lossy(&result.stderr);
-                    Err(format!("PR failed: {}", stderr.trim()))
+                    format!(" and {} more", rest.len())
                 }
+            )),
+        }
+    }
+
+    /// Check the task diff against the configured policy and record the violations on the task
+    fn check_policy(&mut self, task_id: &str) -> Result<ValidationResult, String> {
+        let task = self
+            .tasks
+            .iter()
+            .find(|t| t.id == task_id)
+            .ok_or("Task not found")?;
+        let worktree = task.worktree.clone().ok_or("No worktree for this task")?;
+        let base_branch = self.task_base_branch(task);
+        let result = WorktreeValidator::new(PathBuf::from(worktree))
+            .check_policy(&base_branch, &self.git_config.policy)
+            .map_err(|e| format!("Policy check failed: {}", e))?;
+
+        if let Some(task) = self.tasks.iter_mut().find(|t| t.id == task_id) {
+            task.policy_errors = result.errors.clone();
+            task.policy_warnings = result.warnings.clone();
+            let _ = self.store.save(&self.tasks);
+        }
+        Ok(result)
+    }
+
+    /// Push a task branch to origin. Nothing is pushed while possible secrets are unresolved.
+    fn push_task_branch(&mut self, task_id: &str, force: bool) -> Result<(), String> {
+        self.check_secrets(task_id)?;
+        let task = self
+            .tasks
+            .iter()
+            .find(|t| t.id == task_id)
+            .ok_or("Task not found")?;
+        let branch = task.branch.as_ref().ok_or("No branch for this task")?;
+        let worktree = task.worktree.as_ref().ok_or("No worktree for this task")?;
+        push_branch(branch, worktree, force)
+    }
+
+    /// Open a PR for the task. Returns its URL and notes on options that could not be applied.
+    fn create_pr_for_task(&mut self, task_id: &str) -> Result<(String, Vec<String>), String> {
+        // Push branch first (and the parent's branch the PR of a stacked task targets)
+        self.push_task_branch(task_id, false)?;
+        let parent_id = self
+            .tasks
+            .iter()
+            .find(|t| t.id == task_id)
+            .and_then(|t| t.parent.clone());
+        if let Some(parent_id) = parent_id {
+            self.push_task_branch(&parent_id, false)?;
+        }
+
+        let task = self
+            .tasks
+            .iter()
+            .find(|t| t.id == task_id)
+            .ok_or("Task not found")?;
+        let branch = task.branch.clone().ok_or("No branch for this task")?;
+        let worktree = task.worktree.clone().ok_or("No worktree for this task")?;
+        let base_branch = self.task_base_branch(task);
+
+        let files = self
+            .worktree_manager
+            .file_diffs(std::path::Path::new(&worktree), &base_branch)
+            .unwrap_or_default();
+        let body = PrBody {
+            task,
+            tasks: &self.tasks,
+            overview: self.plan_manager.plan_overview(task_id),
+            steps: self.plan_manager.plan_section(task_id, "Implementation Steps"),
+            files: &files,
+            base: &base_branch,
+        }
+        .render(self.pr_config.body_template());
+        let options = self.pr_config.options.overridden_by(&task.pr_options);
+        let request = NewPullRequest {
+            title: task.title.clone(),
+            body,
+            head: branch,
+            base: base_branch,
+            draft: options.draft(),
+            assignees: options.assignees().to_vec(),
+        };
+
+        let url = self
+            .pr_provider
+            .create(&request)
+            .map_err(|e| format!("PR failed: {}", e))?;
+
+        // Labels and reviewers are added afterwards; failures leave the PR in place
+        let mut warnings = Vec::new();
+        if !options.labels().is_empty() {
+            if let Err(e) = self.pr_provider.add_labels(&url, options.labels()) {
+                warnings.push(format!("labels not added: {}", e));
+            }
+        }
+        if !options.reviewers().is_empty() {
+            if let Err(e) = self.pr_provider.add_reviewers(&url, options.reviewers()) {
+                warnings.push(format!("reviewers not requested: {}", e));
             }
-            Err(e) => Err(format!("Failed to run gh: {}", e)),
         }
+
+        // Save PR URL to task
+        if let Some(task) = self.tasks.iter_mut().find(|t| t.id == task_id) {
+            task.pr_url = Some(url.clone());
+            let _ = self.store.save(&self.tasks);
+        }
+        Ok((url, warnings))
     }
 
     /// Create PR for selected task (key binding 'p')
@@ -651,11 +1350,11 @@ impl App {
         }
 
         let task_id = task.id.clone();
-        self.status_message = Some("Creating PR...".into());
+        self.status_message = Some(format!("Creating PR on {}...", self.pr_provider.name()));
 
         match self.create_pr_for_task(&task_id) {
-            Ok(url) => {
-                self.status_message = Some(format!("✅ PR created: {}", url));
+            Ok((url, warnings)) => {
+                self.status_message = Some(with_notes(format!("✅ PR created: {}", url), &warnings));
             }
             Err(e) => {
                 self.status_message = Some(format!("❌ {}", e));
@@ -665,56 +1364,441 @@ impl App {
         Ok(())
     }
 
-    /// Start merge confirmation
-    fn start_merge(&mut self) {
-        if let Some(task) = self.selected_task() {
-            if task.status == TaskStatus::Review {
-                // Validate implementation before merge
-                if let Some(ref worktree) = task.worktree {
-                    let validator = WorktreeValidator::new(PathBuf::from(worktree));
-                    let validation = validator.validate_implementation("main");
+    /// Sync a task branch with its base and record conflicts on the task
+    fn sync_task(&mut self, task_id: &str) -> Result<SyncOutcome, String> {
+        let task = self
+            .tasks
+            .iter()
+            .find(|t| t.id == task_id)
+            .ok_or("Task not found")?;
+        if task.has_running_run() {
+            return Err("An agent is still running for this task".into());
+        }
+        let worktree = task.worktree.clone().ok_or("No worktree for this task")?;
+        let base_branch = self.task_base_branch(task);
+        let stacked = task.parent.is_some();
+
+        // Stacked tasks are restacked onto the parent branch by rebasing
+        let outcome = if stacked {
+            self.worktree_manager.restack(
+                std::path::Path::new(&worktree),
+                &base_branch,
+                task.parent_head.as_deref(),
+            )
+        } else {
+            self.worktree_manager.sync(
+                std::path::Path::new(&worktree),
+                &base_branch,
+                self.git_config.sync.strategy,
+            )
+        }
+        .map_err(|e| e.to_string())?;
+        let parent_head = match (&outcome, stacked) {
+            (SyncOutcome::Conflicts(_), _) | (_, false) => None,
+            _ => self.worktree_manager.branch_tip(&base_branch).ok(),
+        };
 
-                    match validation {
-                        Ok(result) => {
-                            if !result.is_valid {
-                                self.status_message = Some(format!("❌ {}", result.errors.join(", ")));
-                                return;
-                            }
-                            if !result.warnings.is_empty() {
-                                self.status_message = Some(format!("⚠️ {}", result.warnings.join(", ")));
-                            }
-                        }
-                        Err(e) => {
+        if let Some(task) = self.tasks.iter_mut().find(|t| t.id == task_id) {
+            task.conflicts = match &outcome {
+                SyncOutcome::Conflicts(files) => files.clone(),
+                _ => Vec::new(),
+            };
+            if parent_head.is_some() {
+                task.parent_head = parent_head;
+            }
+        }
+        let _ = self.store.save(&self.tasks);
+        Ok(outcome)
+    }
+
+    /// Sync selected task with its base branch (key binding 'u')
+    fn sync_selected_task(&mut self) {
+        let Some(task) = self.selected_task() else {
+            return;
+        };
+        let task_id = task.id.clone();
+        let base_branch = self.task_base_branch(task);
+
+        self.status_message = Some(match self.sync_task(&task_id) {
+            Ok(SyncOutcome::UpToDate) => format!("✅ Already up to date with {}", base_branch),
+            Ok(SyncOutcome::Synced) => with_notes(
+                format!("🔄 Synced with {}", base_branch),
+                &self.restack_children(&task_id),
+            ),
+            Ok(SyncOutcome::Conflicts(_)) => self.resolve_conflicts(&task_id),
+            Err(e) => format!("❌ Sync failed: {}", e),
+        });
+    }
+
+    /// Restack the tasks stacked on `parent_id` (and theirs, recursively) after its branch
+    /// changed. Returns a note per restacked or failed task.
+    fn restack_children(&mut self, parent_id: &str) -> Vec<String> {
+        let children: Vec<(String, String)> = self
+            .tasks
+            .iter()
+            .filter(|t| t.parent.as_deref() == Some(parent_id) && t.worktree.is_some())
+            .filter(|t| !matches!(t.status, TaskStatus::Done | TaskStatus::Cancelled))
+            .map(|t| (t.id.clone(), t.title.clone()))
+            .collect();
+
+        let mut notes = Vec::new();
+        for (child_id, title) in children {
+            match self.sync_task(&child_id) {
+                Ok(SyncOutcome::Synced) => {
+                    notes.push(format!("restacked '{}'", title));
+                    notes.extend(self.push_rewritten_pr(&child_id, None));
+                    notes.extend(self.restack_children(&child_id));
+                }
+                Ok(SyncOutcome::UpToDate) => {}
+                Ok(SyncOutcome::Conflicts(_)) => {
+                    notes.push(format!("'{}' conflicts with its parent (u to resolve)", title));
+                }
+                Err(e) => notes.push(format!("'{}' not restacked: {}", title, e)),
+            }
+        }
+        notes
+    }
+
+    /// Move the tasks stacked on a merged parent onto the parent's base branch.
+    /// `parent_tip` is the parent branch commit that was merged.
+    fn retarget_children(
+        &mut self,
+        parent_id: &str,
+        base_branch: &str,
+        onto: &str,
+        parent_tip: Option<String>,
+    ) -> Vec<String> {
+        let children: Vec<Task> = self
+            .tasks
+            .iter()
+            .filter(|t| t.parent.as_deref() == Some(parent_id))
+            .filter(|t| !matches!(t.status, TaskStatus::Done | TaskStatus::Cancelled))
+            .cloned()
+            .collect();
+
+        let mut notes = Vec::new();
+        for child in children {
+            let upstream = child.parent_head.clone().or_else(|| parent_tip.clone());
+            let outcome = match (&child.worktree, child.has_running_run()) {
+                (Some(worktree), false) => Some(
+                    self.worktree_manager
+                        .restack(std::path::Path::new(worktree), onto, upstream.as_deref())
+                        .map_err(|e| e.to_string()),
+                ),
+                (Some(_), true) => Some(Err("an agent is still running (u to sync later)".to_string())),
+                (None, _) => None,
+            };
+            if let Some(task) = self.tasks.iter_mut().find(|t| t.id == child.id) {
+                task.retarget(base_branch);
+                if let Some(Ok(SyncOutcome::Conflicts(files))) = &outcome {
+                    task.conflicts = files.clone();
+                }
+            }
+            let _ = self.store.save(&self.tasks);
+
+            match outcome {
+                Some(Ok(SyncOutcome::Conflicts(_))) => notes.push(format!(
+                    "'{}' retargeted to {} with conflicts (u to resolve)",
+                    child.title, base_branch
+                )),
+                Some(Err(e)) => notes.push(format!("'{}' retargeted to {} but not rebased: {}", child.title, base_branch, e)),
+                _ => {
+                    notes.push(format!("'{}' retargeted to {}", child.title, base_branch));
+                    notes.extend(self.push_rewritten_pr(&child.id, Some(base_branch)));
+                    notes.extend(self.restack_children(&child.id));
+                }
+            }
+        }
+        notes
+    }
+
+    /// Force-push a rebased task branch that has a PR (and move the PR to `new_base`).
+    /// Returns a note on failure.
+    fn push_rewritten_pr(&mut self, task_id: &str, new_base: Option<&str>) -> Option<String> {
+        let task = self.tasks.iter().find(|t| t.id == task_id)?;
+        let (url, title) = (task.pr_url.clone()?, task.title.clone());
+        let result = self.push_task_branch(task_id, true).and_then(|()| match new_base {
+            Some(base) => self
+                .pr_provider
+                .set_base(&url, base)
+                .map_err(|e| format!("PR retarget failed: {}", e)),
+            None => Ok(()),
+        });
+        result.err().map(|e| format!("PR of '{}' not updated: {}", title, e))
+    }
+
+    /// Launch conflict resolution and return a status message
+    fn resolve_conflicts(&mut self, task_id: &str) -> String {
+        match self.start_conflict_resolution(task_id) {
+            Ok(message) => message,
+            Err(e) => format!("❌ Conflict resolution failed: {}", e),
+        }
+    }
+
+    /// Recreate base conflicts in the task worktree and launch the executor to resolve them
+    fn start_conflict_resolution(&mut self, task_id: &str) -> anyhow::Result<String> {
+        let (worktree_path, base_branch, executor_name, title) = {
+            let task = self
+                .tasks
+                .iter()
+                .find(|t| t.id == task_id)
+                .ok_or_else(|| anyhow::anyhow!("Task not found"))?;
+            if task.has_running_run() {
+                anyhow::bail!("An agent is still running for this task");
+            }
+            let worktree = task
+                .worktree
+                .clone()
+                .ok_or_else(|| anyhow::anyhow!("No worktree"))?;
+            (
+                PathBuf::from(worktree),
+                self.task_base_branch(task),
+                task.executor
+                    .clone()
+                    .unwrap_or_else(|| self.orchestrator.default_executor.clone()),
+                task.title.clone(),
+            )
+        };
+
+        self.check_agent_available(&executor_name)
+            .map_err(|msg| anyhow::anyhow!(msg))?;
+        let limits = self
+            .run_budget(task_id, &executor_name)
+            .map_err(|msg| anyhow::anyhow!(msg))?;
+
+        let files = self
+            .worktree_manager
+            .recreate_conflict(&worktree_path, &base_branch)?;
+        if files.is_empty() {
+            if let Some(task) = self.tasks.iter_mut().find(|t| t.id == task_id) {
+                task.conflicts.clear();
+            }
+            self.store.save(&self.tasks)?;
+            return Ok(format!("🔄 Merged {} cleanly into '{}'", base_branch, title));
+        }
+
+        let incoming = WorktreeValidator::new(worktree_path.clone())
+            .incoming_commits(&base_branch, 20)
+            .unwrap_or_default();
+        let prompt =
+            self.plan_manager
+                .create_conflict_prompt(task_id, &base_branch, &files, &incoming);
+
+        if let Some(task) = self.tasks.iter_mut().find(|t| t.id == task_id) {
+            let branch = task.branch.clone().unwrap_or_default();
+            task.assign_executor(&executor_name, branch);
+            task.conflicts = files.clone();
+            task.set_status(TaskStatus::InProgress);
+        }
+        self.store.save(&self.tasks)?;
+
+        let config = self.agent_config(&executor_name)?;
+        self.begin_run(task_id, AgentRole::Executor, config, worktree_path, prompt, limits)?;
+
+        Ok(format!(
+            "🤝 Executor '{}' resolving conflicts with {} in {} file(s) for '{}'",
+            executor_name,
+            base_branch,
+            files.len(),
+            title
+        ))
+    }
+
+    /// Verify a finished conflict resolution run and return the task to Review
+    fn verify_conflict_resolution(&mut self, task_id: &str, worktree: &str) -> anyhow::Result<()> {
+        let Some(task) = self.tasks.iter().find(|t| t.id == task_id) else {
+            return Ok(());
+        };
+        let files = task.conflicts.clone();
+        let title = task.title.clone();
+        let worktree_path = PathBuf::from(worktree);
+
+        let remaining = WorktreeValidator::new(worktree_path.clone())
+            .files_with_conflict_markers(&files)?;
+        if !remaining.is_empty() {
+            let message = format!("Conflict markers remain in: {}", remaining.join(", "));
+            if let Some(task) = self.tasks.iter_mut().find(|t| t.id == task_id) {
+                task.last_error = Some(message.clone());
+            }
+            self.store.save(&self.tasks)?;
+            self.status_message = Some(format!("⚠️ {} ('{}')", message, title));
+            return Ok(());
+        }
+
+        if let Err(e) = self.worktree_manager.conclude_merge(&worktree_path, &files) {
+            self.status_message = Some(format!("❌ Failed to conclude merge: {}", e));
+            return Ok(());
+        }
+
+        if let Some(task) = self.tasks.iter_mut().find(|t| t.id == task_id) {
+            task.conflicts.clear();
+            task.set_status(TaskStatus::Review);
+        }
+        self.store.save(&self.tasks)?;
+        let notes = self.restack_children(task_id);
+        self.status_message = Some(with_notes(format!("✅ Conflicts resolved for '{}'", title), &notes));
+        Ok(())
+    }
+
+    /// Start merge confirmation
+    fn start_merge(&mut self) {
+        if let Some(message) = self.selected_task().and_then(|t| self.blocked_by_parent(t)) {
+            self.status_message = Some(message);
+            return;
+        }
+
+        // Sync with base first when configured
+        if self.git_config.sync.before_merge {
+            if let Some(task) = self
+                .selected_task()
+                .filter(|t| t.status == TaskStatus::Review)
+            {
+                let task_id = task.id.clone();
+                match self.sync_task(&task_id) {
+                    Ok(SyncOutcome::Conflicts(_)) => {
+                        self.status_message = Some(self.resolve_conflicts(&task_id));
+                        return;
+                    }
+                    Err(e) => {
+                        self.status_message = Some(format!("❌ Sync failed: {}", e));
+                        return;
+                    }
+                    Ok(_) => {}
+                }
+            }
+        }
+
+        if let Some(task) = self.selected_task() {
+            if task.status == TaskStatus::Review {
+                let task_id = task.id.clone();
+                let base_branch = self.task_base_branch(task);
+
+                // Validate implementation and policy before merge
+                let mut warnings = Vec::new();
+                if let Some(worktree) = task.worktree.clone() {
+                    let validator = WorktreeValidator::new(PathBuf::from(worktree));
+                    let validation = validator
+                        .validate_implementation(&base_branch)
+                        .map_err(|e| e.to_string())
+                        .and_then(|result| Ok(result.merge(self.check_policy(&task_id)?)));
+
+                    match validation {
+                        Ok(result) => {
+                            if !result.is_valid {
+                                self.status_message = Some(format!("❌ {}", result.errors.join(", ")));
+                                return;
+                            }
+                            warnings = result.warnings;
+                        }
+                        Err(e) => {
                             self.status_message = Some(format!("Validation error: {}", e));
                             return;
                         }
                     }
                 }
 
+                if let Err(e) = self.check_secrets(&task_id) {
+                    self.status_message = Some(e);
+                    return;
+                }
+
                 self.input_mode = InputMode::ConfirmMerge;
-                self.status_message = Some("Merge to main? (y/n)".into());
+                let warnings = if warnings.is_empty() {
+                    String::new()
+                } else {
+                    format!(" ⚠️ {}", warnings.join(", "))
+                };
+                self.status_message = Some(format!(
+                    "Merge to {} ({})?{} (y/n)",
+                    base_branch,
+                    self.git_config.merge.strategy.display_name(),
+                    warnings
+                ));
             } else {
                 self.status_message = Some("Can only merge from Review status".into());
             }
         }
     }
 
+    /// Stacked tasks merge after their parent (which retargets them to its base). Returns
+    /// why the task cannot be merged yet.
+    fn blocked_by_parent(&self, task: &Task) -> Option<String> {
+        let parent = self
+            .tasks
+            .iter()
+            .find(|t| Some(t.id.as_str()) == task.parent.as_deref())
+            .filter(|t| !matches!(t.status, TaskStatus::Done | TaskStatus::Cancelled))?;
+        Some(format!(
+            "❌ '{}' is stacked on '{}'. Merge the parent first",
+            task.title, parent.title
+        ))
+    }
+
     /// Execute merge
     fn execute_merge(&mut self) -> anyhow::Result<()> {
+        if let Some(message) = self.selected_task().and_then(|t| self.blocked_by_parent(t)) {
+            self.input_mode = InputMode::Normal;
+            self.status_message = Some(message);
+            return Ok(());
+        }
         if let Some(task) = self.selected_task() {
             let task_id = task.id.clone();
             let title = task.title.clone();
+            let branch = task
+                .branch
+                .clone()
+                .unwrap_or_else(|| self.worktree_manager.get_branch_name(&task_id, &title));
+            let worktree = task.worktree.clone();
+            let base_branch = self.task_base_branch(task);
+            let strategy = self.git_config.merge.strategy;
+            let overview = self.plan_manager.plan_overview(&task_id).unwrap_or_default();
+            let message = render_template(
+                self.git_config.merge.message_template(),
+                &[
+                    ("title", &title),
+                    ("id", &task_id),
+                    ("overview", &overview),
+                    ("pr_url", task.pr_url.as_deref().unwrap_or("")),
+                    ("branch", &branch),
+                    ("base", &base_branch),
+                ],
+            );
 
-            // Get changed file count for summary
-            let changed_files = if let Some(ref worktree) = task.worktree {
-                let validator = WorktreeValidator::new(PathBuf::from(worktree));
-                validator.changed_file_count("main").unwrap_or(0)
-            } else {
-                0
-            };
+            // Get diff stats for summary
+            let stat = task
+                .worktree
+                .as_ref()
+                .and_then(|w| WorktreeValidator::new(PathBuf::from(w)).diff_stat(&base_branch).ok())
+                .unwrap_or_default();
+
+            let merged_tip = self.worktree_manager.branch_tip(&branch).ok();
 
             // Execute merge
-            self.worktree_manager.merge(&task_id, "main")?;
+            if let Err(e) = self
+                .worktree_manager
+                .merge(&branch, &base_branch, strategy, &message)
+            {
+                self.input_mode = InputMode::Normal;
+                self.status_message = Some(match e {
+                    GitError::NotFastForward(_) => format!(
+                        "❌ Cannot fast-forward {}. Sync with base (u) first",
+                        base_branch
+                    ),
+                    GitError::DirtyWorktree(_) => format!(
+                        "❌ '{}' is checked out with local changes. Commit or stash them first",
+                        base_branch
+                    ),
+                    GitError::MergeConflicts(files) => {
+                        if let Some(task) = self.tasks.iter_mut().find(|t| t.id == task_id) {
+                            task.conflicts = files;
+                        }
+                        self.store.save(&self.tasks)?;
+                        self.resolve_conflicts(&task_id)
+                    }
+                    e => format!("❌ Merge failed: {}", e),
+                });
+                return Ok(());
+            }
 
             // Update task status
             if let Some(task) = self.tasks.iter_mut().find(|t| t.id == task_id) {
@@ -723,12 +1807,25 @@ impl App {
             self.store.save(&self.tasks)?;
 
             // Clean up worktree
-            let _ = self.worktree_manager.remove(&task_id);
+            if let Some(worktree) = worktree {
+                let _ = self.worktree_manager.remove_path(std::path::Path::new(&worktree));
+            }
+
+            // Tasks stacked on this one now build on the base branch
+            let notes = self.retarget_children(&task_id, &base_branch, &base_branch, merged_tip);
 
             self.input_mode = InputMode::Normal;
-            self.status_message = Some(format!(
-                "✅ Merged '{}' ({} files changed)",
-                title, changed_files
+            self.status_message = Some(with_notes(
+                format!(
+                    "✅ Merged '{}' into {} by {} ({} files changed, +{} -{})",
+                    title,
+                    base_branch,
+                    strategy.display_name(),
+                    stat.files_changed,
+                    stat.insertions,
+                    stat.deletions
+                ),
+                &notes,
             ));
             self.clamp_selection();
         }
@@ -757,7 +1854,22 @@ impl App {
                 }
             }
             InputMode::NewTaskDescription => {
-                let task = Task::new(&self.pending_title, &self.input_buffer);
+                // Re-detect so the branch checked out at creation time is used
+                if let Ok(branch) = self.git_validator.default_branch() {
+                    self.detected_base_branch = branch;
+                }
+                let mut task = Task::new(&self.pending_title, &self.input_buffer);
+                task.base_branch = Some(self.default_base_branch());
+                if let Some(parent) = self
+                    .pending_parent
+                    .take()
+                    .and_then(|id| self.tasks.iter().find(|t| t.id == id))
+                {
+                    if let Err(e) = task.stack_on(parent) {
+                        self.status_message = Some(format!("❌ {}", e));
+                        return Ok(());
+                    }
+                }
                 let task_id = task.id.clone();
                 self.store.add(task)?;
                 self.tasks = self.store.load()?;
@@ -779,31 +1891,147 @@ impl App {
                 // Enter confirms merge
                 self.execute_merge()?;
             }
+            InputMode::ConfirmRerun => {
+                self.confirm_rerun()?;
+            }
+            InputMode::ConfirmGc => {
+                self.confirm_gc(false);
+            }
+            InputMode::ConfirmWaiveSecrets => {
+                self.confirm_waive_secrets();
+            }
+            InputMode::ReviewComment => {
+                self.save_review_comment()?;
+            }
+            InputMode::FollowUp => {
+                self.submit_follow_up()?;
+            }
             InputMode::Normal | InputMode::TaskDetail | InputMode::ViewDiff | InputMode::Help | InputMode::Settings => {}
         }
         Ok(())
     }
 
+    /// Execute confirmed rerun
+    fn confirm_rerun(&mut self) -> anyhow::Result<()> {
+        let selected = self.selected_task().and_then(|t| {
+            t.runs
+                .get(self.detail_run_index)
+                .map(|r| (t.id.clone(), r.id.clone()))
+        });
+        self.input_mode = InputMode::Normal;
+        if let Some((task_id, run_id)) = selected {
+            if let Err(e) = self.rerun(&task_id, &run_id) {
+                self.status_message = Some(format!("❌ Rerun failed: {}", e));
+            }
+        }
+        Ok(())
+    }
+
+    /// Look for orphaned worktrees and branches and ask before pruning them
+    fn start_gc(&mut self) {
+        if self.tasks.iter().any(|t| t.has_running_run()) {
+            self.status_message = Some("Wait for running agents to finish before cleaning up".into());
+            return;
+        }
+        match self
+            .worktree_manager
+            .find_orphans(&self.tasks, &self.default_base_branch())
+        {
+            Ok(orphans) if orphans.is_empty() => {
+                self.status_message = Some("✨ Nothing to clean up".into());
+            }
+            Ok(orphans) => {
+                self.status_message = Some(if orphans.iter().any(Orphan::is_unmerged) {
+                    format!("Prune {} orphans? (y: merged only, a: all, n: cancel)", orphans.len())
+                } else {
+                    format!("Prune {} orphans? (y/n)", orphans.len())
+                });
+                self.gc_orphans = orphans;
+                self.input_mode = InputMode::ConfirmGc;
+            }
+            Err(e) => {
+                self.status_message = Some(format!("❌ Failed to find orphans: {}", e));
+            }
+        }
+    }
+
+    /// Prune the orphans confirmed in the GC popup (unmerged branches only with `include_unmerged`)
+    fn confirm_gc(&mut self, include_unmerged: bool) {
+        let orphans: Vec<Orphan> = std::mem::take(&mut self.gc_orphans)
+            .into_iter()
+            .filter(|o| include_unmerged || !o.is_unmerged())
+            .collect();
+        self.input_mode = InputMode::Normal;
+        let failed: Vec<String> = orphans
+            .iter()
+            .filter_map(|o| {
+                self.worktree_manager
+                    .prune_orphan(o)
+                    .err()
+                    .map(|e| format!("{}: {}", o.describe(), e))
+            })
+            .collect();
+        self.status_message = Some(match failed.first() {
+            None => format!("🧹 Pruned {} orphans", orphans.len()),
+            Some(first) => format!(
+                "⚠️ Pruned {} of {} orphans; {}",
+                orphans.len() - failed.len(),
+                orphans.len(),
+                first
+            ),
+        });
+    }
+
+    /// Ask before waiving the selected task's secret findings
+    fn start_waive_secrets(&mut self) {
+        match self.selected_task() {
+            Some(task) if !task.secret_findings.is_empty() => {
+                self.status_message = Some(format!(
+                    "Waive {} secret findings? (y/n)",
+                    task.secret_findings.len()
+                ));
+                self.input_mode = InputMode::ConfirmWaiveSecrets;
+            }
+            Some(_) => {
+                self.status_message = Some("No secret findings to waive".into());
+            }
+            None => {}
+        }
+    }
+
+    /// Waive the findings confirmed in the popup (the same secrets are not flagged again)
+    fn confirm_waive_secrets(&mut self) {
+        self.input_mode = InputMode::Normal;
+        let Some(task_id) = self.selected_task().map(|t| t.id.clone()) else {
+            return;
+        };
+        if let Some(task) = self.tasks.iter_mut().find(|t| t.id == task_id) {
+            let waived = task.waive_secret_findings();
+            let _ = self.store.save(&self.tasks);
+            self.status_message = Some(format!("🔓 Waived {} secret findings", waived));
+        }
+    }
+
+    /// Move run selection in task detail
+    fn select_run(&mut self, direction: i32) {
+        let count = self.selected_task().map(|t| t.runs.len()).unwrap_or(0);
+        if direction > 0 && self.detail_run_index + 1 < count {
+            self.detail_run_index += 1;
+        } else if direction < 0 && self.detail_run_index > 0 {
+            self.detail_run_index -= 1;
+        }
+    }
+
     /// Cancel input / close popup
     fn cancel_input(&mut self) {
         self.input_mode = InputMode::Normal;
         self.input_buffer.clear();
         self.pending_title.clear();
-        self.diff_content.clear();
-        self.diff_scroll = 0;
+        self.pending_parent = None;
+        self.diff_view = DiffView::default();
         self.status_message = None;
     }
 
-    /// Scroll diff view
-    fn scroll_diff(&mut self, direction: i32) {
-        let lines = self.diff_content.lines().count();
-        if direction > 0 && self.diff_scroll < lines.saturating_sub(20) {
-            self.diff_scroll += 1;
-        } else if direction < 0 && self.diff_scroll > 0 {
-            self.diff_scroll -= 1;
-        }
-    }
-
     /// Move selection up
     fn selection_up(&mut self) {
         if self.selected_index > 0 {
@@ -822,35 +2050,34 @@ impl App {
     fn start_agent(
         &self,
         task_id: String,
-        agent_name: &str,
+        run_id: String,
+        config: AgentConfig,
         working_dir: PathBuf,
         prompt: String,
+        limits: BudgetLimits,
     ) {
         let agent_runner = Arc::clone(&self.agent_runner);
         let event_tx = self.agent_event_tx.clone();
-        let agent_name = agent_name.to_string();
+        let log_path = AgentRunner::log_path(&self.hive_dir, &task_id, &run_id);
 
         tokio::spawn(async move {
-            // Get AgentConfig
-            let config = match agent::AgentConfig::from_name(&agent_name) {
-                Some(c) => c,
-                None => {
-                    let _ = event_tx
-                        .send(AgentEvent::Failed {
-                            task_id: task_id.clone(),
-                            error: format!("Unknown agent: {}", agent_name),
-                        })
-                        .await;
-                    return;
-                }
-            };
-
             // Start agent
             let rx = {
                 let mut runner = agent_runner.lock().await;
-                match runner.start(&task_id, config, working_dir, &prompt).await {
+                match runner.start(&task_id, config, working_dir, &prompt, limits, log_path).await {
                     Ok(rx) => rx,
                     Err(e) => {
+                        let _ = event_tx
+                            .send(AgentEvent::RunFinished {
+                                task_id: task_id.clone(),
+                                run_id: run_id.clone(),
+                                status: RunStatus::Failed,
+                                exit_code: None,
+                                error: Some(e.to_string()),
+                                usage: TokenUsage::default(),
+                                duration_secs: 0,
+                            })
+                            .await;
                         let _ = event_tx
                             .send(AgentEvent::Failed {
                                 task_id: task_id.clone(),
@@ -862,22 +2089,73 @@ impl App {
                 }
             };
 
-            // Forward output
+            // Forward output, checking the budget periodically
             let mut rx = rx;
-            while let Some(line) = rx.recv().await {
-                let _ = event_tx
-                    .send(AgentEvent::Output {
-                        task_id: task_id.clone(),
-                        line,
-                    })
-                    .await;
+            let mut budget_tick = tokio::time::interval(std::time::Duration::from_secs(1));
+            loop {
+                tokio::select! {
+                    line = rx.recv() => match line {
+                        Some(line) => {
+                            let _ = event_tx
+                                .send(AgentEvent::Output {
+                                    task_id: task_id.clone(),
+                                    line,
+                                })
+                                .await;
+                        }
+                        None => break,
+                    },
+                    _ = budget_tick.tick() => {
+                        let child = {
+                            let mut runner = agent_runner.lock().await;
+                            match runner.check_budget(&task_id) {
+                                Some(reason) => runner.halt(&task_id, AgentStatus::BudgetExceeded(reason)),
+                                None => None,
+                            }
+                        };
+                        // Terminate outside the lock; output ends when the process exits
+                        if let Some(child) = child {
+                            let _ = AgentRunner::terminate_gracefully(
+                                child,
+                                std::time::Duration::from_secs(10),
+                            )
+                            .await;
+                        }
+                    }
+                }
             }
 
             // Check completion
-            let status = {
+            let (status, usage, duration_secs, exit_code) = {
                 let mut runner = agent_runner.lock().await;
-                runner.check_task_completion(&task_id)
+                (
+                    runner.check_task_completion(&task_id),
+                    runner.get_usage(&task_id).unwrap_or_default(),
+                    runner.elapsed_secs(&task_id).unwrap_or(0),
+                    runner.get_exit_code(&task_id),
+                )
+            };
+
+            // Finish the run record before completion so usage is recorded on the task
+            let (run_status, run_error) = match &status {
+                Some(AgentStatus::Completed) => (RunStatus::Succeeded, None),
+                Some(AgentStatus::Failed(error)) => (RunStatus::Failed, Some(error.clone())),
+                Some(AgentStatus::BudgetExceeded(reason)) => {
+                    (RunStatus::Failed, Some(format!("Budget exceeded: {}", reason)))
+                }
+                _ => (RunStatus::Failed, Some("Unknown agent status".into())),
             };
+            let _ = event_tx
+                .send(AgentEvent::RunFinished {
+                    task_id: task_id.clone(),
+                    run_id: run_id.clone(),
+                    status: run_status,
+                    exit_code,
+                    error: run_error,
+                    usage,
+                    duration_secs,
+                })
+                .await;
 
             if let Some(status) = status {
                 match status {
@@ -896,6 +2174,14 @@ impl App {
                             })
                             .await;
                     }
+                    AgentStatus::BudgetExceeded(reason) => {
+                        let _ = event_tx
+                            .send(AgentEvent::Failed {
+                                task_id: task_id.clone(),
+                                error: format!("Budget exceeded: {}", reason),
+                            })
+                            .await;
+                    }
                     _ => {}
                 }
             }
@@ -907,26 +2193,27 @@ impl App {
         while let Ok(event) = self.agent_event_rx.try_recv() {
             match event {
                 AgentEvent::Completed { task_id } => {
-                    self.handle_agent_completed(&task_id)?;
+                    let mut rerun_dir = None;
+                    if let Some(task) = self.tasks.iter_mut().find(|t| t.id == task_id) {
+                        task.last_error = None;
+                        if task.last_run_is_rerun() {
+                            rerun_dir = task.runs.last().map(|r| r.working_dir.clone());
+                        }
+                    }
+                    // Reruns are compared by hand; they do not advance the task
+                    match rerun_dir {
+                        Some(dir) => self.status_message = Some(format!("✅ Rerun finished in {}", dir)),
+                        None => self.handle_agent_completed(&task_id)?,
+                    }
                 }
                 AgentEvent::Failed { task_id, error } => {
                     // Collect task info and update in a scope to end mutable borrow
                     let task_info = if let Some(task) =
                         self.tasks.iter_mut().find(|t| t.id == task_id)
                     {
-                        // Revert status and clear agent assignment on failure
-                        let (new_status, cleared) = match task.status {
-                            TaskStatus::Planning => {
-                                task.planner = None;
-                                (TaskStatus::Todo, "planner")
-                            }
-                            TaskStatus::InProgress => {
-                                task.executor = None;
-                                (TaskStatus::PlanReview, "executor")
-                            }
-                            _ => (task.status, ""),
-                        };
-                        task.set_status(new_status);
+                        // Revert status (and a first run's agent assignment) on failure
+                        let (new_status, cleared) = task.revert_failed_run();
+                        task.last_error = Some(error.clone());
                         Some((task.title.clone(), new_status, cleared))
                     } else {
                         None
@@ -937,13 +2224,37 @@ impl App {
                         if let Err(e) = self.store.save(&self.tasks) {
                             self.status_message = Some(format!("❌ Save error: {}", e));
                         } else {
+                            let cleared = cleared.map(|c| format!(", {} cleared", c)).unwrap_or_default();
                             self.status_message = Some(format!(
-                                "❌ Agent failed on '{}': {} (reverted to {}, {} cleared)",
+                                "❌ Agent failed on '{}': {} (reverted to {}{})",
                                 title, error, new_status.display_name(), cleared
                             ));
                         }
                     }
                 }
+                AgentEvent::CommitMessage { task_id, message } => {
+                    self.finish_auto_commit(&task_id, message)?;
+                }
+                AgentEvent::PullRequests { results } => {
+                    self.apply_pr_statuses(results)?;
+                }
+                AgentEvent::RunFinished {
+                    task_id,
+                    run_id,
+                    status,
+                    exit_code,
+                    error,
+                    usage,
+                    duration_secs,
+                } => {
+                    if let Some(task) = self.tasks.iter_mut().find(|t| t.id == task_id) {
+                        let record = task.finish_run(&run_id, status, exit_code, error, usage, duration_secs);
+                        self.store.save(&self.tasks)?;
+                        if let Some(record) = record {
+                            self.usage_ledger.record(record)?;
+                        }
+                    }
+                }
                 AgentEvent::Output { task_id, line } => {
                     // Store output in log buffer
                     let task_title = self
@@ -984,10 +2295,11 @@ impl App {
                 t.status,
                 t.title.clone(),
                 t.worktree.clone(),
+                self.task_base_branch(t),
             )
         });
 
-        let Some((status, title, worktree)) = task_info else {
+        let Some((status, title, worktree, base_branch)) = task_info else {
             return Ok(());
         };
 
@@ -1013,12 +2325,21 @@ impl App {
                 self.start_executor_for_task(task_id, &default_executor)?;
             }
             TaskStatus::InProgress => {
+                // Conflict resolution run: verify instead of the usual checks
+                let resolving = self
+                    .tasks
+                    .iter()
+                    .any(|t| t.id == task_id && t.needs_conflict_resolution());
+                if let (true, Some(worktree_path)) = (resolving, &worktree) {
+                    return self.verify_conflict_resolution(task_id, worktree_path);
+                }
+
                 // Validate: Changes or commits must exist
                 if let Some(worktree_path) = worktree {
                     let validator = WorktreeValidator::new(PathBuf::from(&worktree_path));
 
                     // Check for changes (commits or uncommitted)
-                    let has_commits = validator.has_new_commits("main").unwrap_or(false);
+                    let has_commits = validator.has_new_commits(&base_branch).unwrap_or(false);
                     let has_changes = validator.has_changes().unwrap_or(false);
 
                     if !has_commits && !has_changes {
@@ -1029,35 +2350,12 @@ impl App {
                         return Ok(());
                     }
 
-                    // Changes exist, advance to Review
-                    if let Some(task) = self.tasks.iter_mut().find(|t| t.id == task_id) {
-                        task.set_status(TaskStatus::Review);
-                        self.store.save(&self.tasks)?;
+                    // Commit whatever the executor left uncommitted
+                    if has_changes && self.git_config.auto_commit.enabled {
+                        return self.start_auto_commit(task_id, &worktree_path);
                     }
 
-                    // Auto-create PR if commits exist (not just uncommitted changes)
-                    if has_commits {
-                        match self.create_pr_for_task(task_id) {
-                            Ok(url) => {
-                                self.status_message = Some(format!(
-                                    "✅ Implementation completed & PR created: {}",
-                                    url
-                                ));
-                            }
-                            Err(e) => {
-                                self.status_message = Some(format!(
-                                    "✅ Implementation completed: {} (PR failed: {})",
-                                    title, e
-                                ));
-                            }
-                        }
-                    } else {
-                        // Only uncommitted changes - can't create PR yet
-                        self.status_message = Some(format!(
-                            "✅ Implementation completed (uncommitted): {}",
-                            title
-                        ));
-                    }
+                    self.complete_implementation(task_id, None)?;
                 } else {
                     self.status_message = Some(format!(
                         "⚠️ No worktree found for '{}'",
@@ -1065,12 +2363,311 @@ impl App {
                     ));
                 }
             }
-            _ => {}
+            _ => {}
+        }
+
+        Ok(())
+    }
+
+    /// Commit executor leftovers, asking the configured agent for the message first
+    fn start_auto_commit(&mut self, task_id: &str, worktree_path: &str) -> anyhow::Result<()> {
+        let worktree = PathBuf::from(worktree_path);
+        let agent = self.git_config.auto_commit.agent.clone();
+        let Some(agent) = agent else {
+            return self.finish_auto_commit(task_id, Err("no agent configured".into()));
+        };
+
+        let prepared = self.agent_config(&agent).and_then(|config| {
+            // Stage first so new files show up in the diff against HEAD
+            self.worktree_manager.stage_all(&worktree)?;
+            let diff = self.worktree_manager.get_diff(&worktree, "HEAD")?;
+            Ok((config, diff))
+        });
+        let (config, diff) = match prepared {
+            Ok(prepared) => prepared,
+            Err(e) => return self.finish_auto_commit(task_id, Err(e.to_string())),
+        };
+
+        let title = self
+            .tasks
+            .iter()
+            .find(|t| t.id == task_id)
+            .map(|t| t.title.clone())
+            .unwrap_or_default();
+        let prompt = self.plan_manager.create_commit_message_prompt(&title, &diff);
+        let event_tx = self.agent_event_tx.clone();
+        let task_id = task_id.to_string();
+        self.status_message = Some(format!("📝 {} is writing a commit message for '{}'", agent, title));
+
+        tokio::spawn(async move {
+            let message = AgentRunner::run_once(
+                &config,
+                &worktree,
+                &prompt,
+                std::time::Duration::from_secs(120),
+            )
+            .await
+            .map_err(|e| e.to_string())
+            .and_then(|m| if m.is_empty() { Err("empty reply".into()) } else { Ok(m) });
+            let _ = event_tx
+                .send(AgentEvent::CommitMessage { task_id, message })
+                .await;
+        });
+        Ok(())
+    }
+
+    /// Commit executor leftovers with `message` (falls back to the template) and move on to Review
+    fn finish_auto_commit(
+        &mut self,
+        task_id: &str,
+        message: Result<String, String>,
+    ) -> anyhow::Result<()> {
+        let Some(task) = self.tasks.iter().find(|t| t.id == task_id) else {
+            return Ok(());
+        };
+        let Some(worktree) = task.worktree.clone().map(PathBuf::from) else {
+            return Ok(());
+        };
+
+        let mut note = None;
+        let message = match message {
+            Ok(message) => message,
+            Err(e) => {
+                if self.git_config.auto_commit.agent.is_some() {
+                    note = Some(format!("commit message agent failed: {}", e));
+                }
+                let _ = self.worktree_manager.stage_all(&worktree);
+                let files = WorktreeValidator::new(worktree.clone())
+                    .staged_files()
+                    .unwrap_or_default()
+                    .iter()
+                    .map(|f| format!("- {}", f))
+                    .collect::<Vec<_>>()
+                    .join("\n");
+                let overview = self.plan_manager.plan_overview(task_id).unwrap_or_default();
+                render_template(
+                    self.git_config.auto_commit.message_template(),
+                    &[
+                        ("title", &task.title),
+                        ("id", &task.id),
+                        ("overview", &overview),
+                        ("files", &files),
+                        ("branch", task.branch.as_deref().unwrap_or_default()),
+                        ("base", &self.task_base_branch(task)),
+                    ],
+                )
+            }
+        };
+
+        if let Err(e) = self.worktree_manager.commit_all(&worktree, &message) {
+            note = Some(format!("auto-commit failed: {}", e));
+        }
+        self.complete_implementation(task_id, note)
+    }
+
+    /// Move a finished implementation to Review and open a PR when the branch has commits
+    fn complete_implementation(&mut self, task_id: &str, note: Option<String>) -> anyhow::Result<()> {
+        let Some(task) = self.tasks.iter().find(|t| t.id == task_id) else {
+            return Ok(());
+        };
+        let title = task.title.clone();
+        let existing_pr = task.pr_url.clone();
+        let has_commits = task.worktree.as_ref().is_some_and(|w| {
+            WorktreeValidator::new(PathBuf::from(w))
+                .has_new_commits(&self.task_base_branch(task))
+                .unwrap_or(false)
+        });
+        // Policy violations are reported on entering Review (errors block the merge)
+        let policy_notes = match self.check_policy(task_id) {
+            Ok(result) => result
+                .errors
+                .iter()
+                .map(|e| format!("⛔ {}", e))
+                .chain(result.warnings.iter().cloned())
+                .collect(),
+            Err(e) => vec![e],
+        };
+        if let Some(task) = self.tasks.iter_mut().find(|t| t.id == task_id) {
+            task.set_status(TaskStatus::Review);
+        }
+        self.store.save(&self.tasks)?;
+
+        // Auto-create PR if commits exist (not just uncommitted changes)
+        let message = if let Some(url) = existing_pr {
+            // Revision of a task that already has a PR: update it
+            match self.push_task_branch(task_id, false) {
+                Ok(()) => format!("✅ Implementation completed & PR updated: {}", url),
+                Err(e) => format!("✅ Implementation completed: {} ({})", title, e),
+            }
+        } else if has_commits {
+            match self.create_pr_for_task(task_id) {
+                Ok((url, warnings)) => with_notes(
+                    format!("✅ Implementation completed & PR created: {}", url),
+                    &warnings,
+                ),
+                Err(e) => format!("✅ Implementation completed: {} (PR failed: {})", title, e),
+            }
+        } else {
+            // Only uncommitted changes - can't create PR yet
+            format!("✅ Implementation completed (uncommitted): {}", title)
+        };
+        let message = with_notes(message, &policy_notes);
+        let message = with_notes(message, &self.restack_children(task_id));
+        self.status_message = Some(match note {
+            Some(note) => format!("{} ⚠️ {}", message, note),
+            None => message,
+        });
+        Ok(())
+    }
+
+    /// Poll the PRs of Review tasks in the background when the poll interval has passed
+    fn poll_pull_requests(&mut self) {
+        let Some(interval) = self.pr_poll_interval else {
+            return;
+        };
+        if self.pr_poll_in_flight || self.last_pr_poll.is_some_and(|t| t.elapsed() < interval) {
+            return;
+        }
+        self.last_pr_poll = Some(std::time::Instant::now());
+
+        let targets: Vec<(String, String)> = self
+            .tasks
+            .iter()
+            .filter(|t| t.status == TaskStatus::Review)
+            .filter_map(|t| Some((t.id.clone(), t.pr_url.clone()?)))
+            .collect();
+        if targets.is_empty() {
+            return;
+        }
+
+        self.pr_poll_in_flight = true;
+        let provider = Arc::clone(&self.pr_provider);
+        let event_tx = self.agent_event_tx.clone();
+        // Forge CLIs block, so poll on the blocking pool
+        let polled = targets.clone();
+        let poll = tokio::task::spawn_blocking(move || {
+            polled
+                .into_iter()
+                .map(|(task_id, url)| {
+                    let summary = provider.view(&url).map(|status| {
+                        // Missing checks should not hide the PR state
+                        let checks = provider.checks(&url).unwrap_or_default();
+                        PrSummary::new(&status, &checks)
+                    });
+                    (task_id, url, summary.map_err(|e| e.to_string()))
+                })
+                .collect()
+        });
+        // Results are always delivered, so a panicking poll does not stop polling
+        tokio::spawn(async move {
+            let results = match poll.await {
+                Ok(results) => results,
+                Err(e) => targets
+                    .into_iter()
+                    .map(|(task_id, url)| (task_id, url, Err(format!("PR poll failed: {}", e))))
+                    .collect(),
+            };
+            let _ = event_tx.send(AgentEvent::PullRequests { results }).await;
+        });
+    }
+
+    /// Record polled PR statuses: merged PRs finish their task, closed PRs and
+    /// requested changes are flagged
+    fn apply_pr_statuses(
+        &mut self,
+        results: Vec<(String, String, Result<PrSummary, String>)>,
+    ) -> anyhow::Result<()> {
+        self.pr_poll_in_flight = false;
+        let mut notes = Vec::new();
+        let mut merged = Vec::new();
+        let mut error = None;
+
+        for (task_id, url, result) in results {
+            let Some(task) = self
+                .tasks
+                .iter_mut()
+                .find(|t| t.id == task_id && t.pr_url.as_deref() == Some(url.as_str()))
+            else {
+                continue;
+            };
+            let summary = match result {
+                Ok(summary) => summary,
+                Err(e) => {
+                    error = Some(e);
+                    continue;
+                }
+            };
+            let previous = task.pr_status.replace(summary.clone());
+            if task.status != TaskStatus::Review {
+                continue;
+            }
+            let changed = previous.is_none_or(|p| (p.state, p.review) != (summary.state, summary.review));
+            match summary.state {
+                PrState::Merged => merged.push(task_id),
+                PrState::Closed if changed => notes.push(format!("🚩 PR of '{}' was closed", task.title)),
+                _ if changed && summary.review == Some(ReviewDecision::ChangesRequested) => {
+                    notes.push(format!("🚩 Changes requested on '{}'", task.title))
+                }
+                _ => {}
+            }
         }
+        self.store.save(&self.tasks)?;
 
+        for task_id in merged {
+            notes.push(self.finish_merged_pr(&task_id));
+        }
+        if !notes.is_empty() {
+            self.status_message = Some(notes.join("; "));
+        } else if error.is_some() && error != self.pr_poll_error {
+            self.status_message = Some(format!(
+                "⚠️ PR status unavailable: {}",
+                error.as_deref().unwrap_or_default()
+            ));
+        }
+        self.pr_poll_error = error;
         Ok(())
     }
 
+    /// Move a task whose PR was merged on the forge to Done, remove its worktree and retarget
+    /// its stacked tasks
+    fn finish_merged_pr(&mut self, task_id: &str) -> String {
+        let Some(task) = self.tasks.iter().find(|t| t.id == task_id) else {
+            return String::new();
+        };
+        let base_branch = self.task_base_branch(task);
+        let (title, worktree, branch) = (task.title.clone(), task.worktree.clone(), task.branch.clone());
+        if let Some(task) = self.tasks.iter_mut().find(|t| t.id == task_id) {
+            task.set_status(TaskStatus::Done);
+        }
+        let _ = self.store.save(&self.tasks);
+
+        let mut notes = Vec::new();
+        if let Some(worktree) = worktree {
+            if let Err(e) = self.worktree_manager.remove_path(std::path::Path::new(&worktree)) {
+                notes.push(format!("worktree not removed: {} (run G to clean up)", e));
+            }
+        }
+
+        // Stacked tasks move onto the base the PR was merged into. The merge is only on the
+        // remote, so they are rebased onto the fetched base.
+        let has_stacked = self.tasks.iter().any(|t| {
+            t.parent.as_deref() == Some(task_id)
+                && !matches!(t.status, TaskStatus::Done | TaskStatus::Cancelled)
+        });
+        if has_stacked {
+            let parent_tip = branch.and_then(|b| self.worktree_manager.branch_tip(&b).ok());
+            match self.worktree_manager.fetch_base(&base_branch) {
+                Ok(onto) => notes.extend(self.retarget_children(task_id, &base_branch, &onto, parent_tip)),
+                Err(e) => notes.push(format!(
+                    "stacked tasks still build on its branch (fetching {} failed: {})",
+                    base_branch, e
+                )),
+            }
+        }
+        self.clamp_selection();
+        with_notes(format!("🎉 PR of '{}' merged, task done", title), &notes)
+    }
+
     /// Update running agent count
     async fn update_running_count(&mut self) {
         let runner = self.agent_runner.lock().await;
@@ -1078,8 +2675,111 @@ impl App {
     }
 }
 
+/// Push a task branch to origin (sets upstream). `force` overwrites a rewritten
+/// (rebased) branch unless the remote moved since the last fetch.
+fn push_branch(branch: &str, worktree: &str, force: bool) -> Result<(), String> {
+    let push_output = std::process::Command::new("git")
+        .args(["push", "-u"])
+        .args(force.then_some("--force-with-lease"))
+        .args(["origin", branch])
+        .current_dir(worktree)
+        .output();
+
+    match push_output {
+        Ok(result) if !result.status.success() => {
+            let stderr = String::from_utf8_lossy(&result.stderr);
+            Err(format!("Push failed: {}", stderr.trim()))
+        }
+        Err(e) => Err(format!("Failed to run git push: {}", e)),
+        _ => Ok(()),
+    }
+}
+
+/// Append notes to a status message
+fn with_notes(message: String, notes: &[String]) -> String {
+    if notes.is_empty() {
+        message
+    } else {
+        format!("{} ({})", message, notes.join("; "))
+    }
+}
+
+/// `hive gc`: list orphaned worktrees and branches, prune them after confirmation.
+/// Unmerged branches are only deleted with `unmerged`.
+fn run_gc(yes: bool, unmerged: bool) -> anyhow::Result<()> {
+    let repo_root = PathBuf::from(".");
+    let hive_dir = PathBuf::from(".hive");
+    let tasks = TaskStore::new(&repo_root)?.load()?;
+    let git_config = GitConfig::load(&hive_dir).unwrap_or_default();
+    let default_base = git_config.base_branch.clone().unwrap_or_else(|| {
+        GitValidator::new(repo_root.clone())
+            .default_branch()
+            .unwrap_or_else(|_| "main".into())
+    });
+    let manager = WorktreeManager::new(repo_root.clone(), hive_dir)
+        .with_branch_naming(BranchNaming::new(&git_config.branch, &repo_root));
+    let (orphans, kept): (Vec<Orphan>, Vec<Orphan>) = manager
+        .find_orphans(&tasks, &default_base)?
+        .into_iter()
+        .partition(|o| unmerged || !o.is_unmerged());
+    for orphan in &kept {
+        println!("  keeping {} (--unmerged to delete)", orphan.describe());
+    }
+    if orphans.is_empty() {
+        println!("Nothing to clean up");
+        return Ok(());
+    }
+
+    for orphan in &orphans {
+        println!("  {}", orphan.describe());
+    }
+    if !yes {
+        print!("Prune {} orphans? [y/N] ", orphans.len());
+        io::Write::flush(&mut io::stdout())?;
+        let mut answer = String::new();
+        io::stdin().read_line(&mut answer)?;
+        if !matches!(answer.trim(), "y" | "Y" | "yes") {
+            println!("Aborted");
+            return Ok(());
+        }
+    }
+
+    let mut failed = 0;
+    for orphan in &orphans {
+        if let Err(e) = manager.prune_orphan(orphan) {
+            eprintln!("  failed to prune {}: {}", orphan.describe(), e);
+            failed += 1;
+        }
+    }
+    println!("Pruned {} of {} orphans", orphans.len() - failed, orphans.len());
+    if failed > 0 {
+        std::process::exit(1);
+    }
+    Ok(())
+}
+
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
+    let cli = Cli::parse();
+    match cli.command {
+        Some(Commands::Doctor) => {
+            let hive_dir = PathBuf::from(".hive");
+            let orchestrator = OrchestratorConfig::load(&hive_dir).unwrap_or_else(|e| {
+                eprintln!("warning: {:#} (using default agents)", e);
+                OrchestratorConfig::default()
+            });
+            let forge = PrConfig::load(&hive_dir).unwrap_or_default().provider;
+            let report = doctor::run(&orchestrator, forge, &PathBuf::from("."));
+            print!("{}", report.render());
+            if report.has_errors() {
+                std::process::exit(1);
+            }
+            return Ok(());
+        }
+        Some(Commands::Gc { yes, unmerged }) => return run_gc(yes, unmerged),
+        None => {}
+    }
+
     enable_raw_mode()?;
     io::stdout().execute(EnterAlternateScreen)?;
     let mut terminal = Terminal::new(CrosstermBackend::new(io::stdout()))?;
@@ -1091,9 +2791,12 @@ async fn main() -> anyhow::Result<()> {
         app.process_agent_events().await?;
         // Update running count
         app.update_running_count().await;
+        // Check PRs of Review tasks
+        app.poll_pull_requests();
         // Animate spinner
         app.spinner_frame = (app.spinner_frame + 1) % SPINNER_FRAMES.len();
 
+        app.diff_view.set_width(terminal.size()?.width);
         terminal.draw(|frame| ui(frame, &app))?;
 
         if event::poll(std::time::Duration::from_millis(100))? {
@@ -1120,6 +2823,7 @@ async fn main() -> anyhow::Result<()> {
                                 app.move_task_backward()?;
                             }
                             KeyCode::Char('g') => app.start_merge(),
+                            KeyCode::Char('u') => app.sync_selected_task(),
                             KeyCode::Char('p') => {
                                 app.create_pr()?;
                             }
@@ -1132,9 +2836,13 @@ async fn main() -> anyhow::Result<()> {
                             KeyCode::Char('S') => {
                                 app.open_settings();
                             }
+                            KeyCode::Char('G') => app.start_gc(),
+                            KeyCode::Char('W') => app.start_waive_secrets(),
+                            KeyCode::Char('f') => app.start_follow_up(),
+                            KeyCode::Char('N') => app.start_stacked_task(),
                             _ => {}
                         },
-                        InputMode::NewTaskTitle | InputMode::NewTaskDescription => match key.code {
+                        InputMode::NewTaskTitle | InputMode::NewTaskDescription | InputMode::FollowUp => match key.code {
                             KeyCode::Enter => app.confirm_input()?,
                             KeyCode::Esc => app.cancel_input(),
                             KeyCode::Backspace => app.handle_backspace(),
@@ -1165,14 +2873,39 @@ async fn main() -> anyhow::Result<()> {
                                 app.cancel_input();
                                 app.show_diff()?;
                             }
+                            KeyCode::Char('j') | KeyCode::Down => app.select_run(1),
+                            KeyCode::Char('k') | KeyCode::Up => app.select_run(-1),
+                            KeyCode::Char('r') => app.start_rerun(),
                             _ => {}
                         },
                         InputMode::ViewDiff => match key.code {
                             KeyCode::Esc | KeyCode::Char('q') => app.cancel_input(),
-                            KeyCode::Char('j') | KeyCode::Down => app.scroll_diff(1),
-                            KeyCode::Char('k') | KeyCode::Up => app.scroll_diff(-1),
-                            KeyCode::Char(' ') | KeyCode::PageDown => {
-                                for _ in 0..10 { app.scroll_diff(1); }
+                            KeyCode::Char('j') | KeyCode::Down => app.diff_view.move_cursor(1),
+                            KeyCode::Char('k') | KeyCode::Up => app.diff_view.move_cursor(-1),
+                            KeyCode::Char(' ') | KeyCode::PageDown => app.diff_view.move_cursor(10),
+                            KeyCode::Char('b') | KeyCode::PageUp => app.diff_view.move_cursor(-10),
+                            KeyCode::Char('n') => app.diff_view.next_hunk(),
+                            KeyCode::Char('N') => app.diff_view.prev_hunk(),
+                            KeyCode::Tab | KeyCode::Char(']') => app.diff_view.next_file(),
+                            KeyCode::BackTab | KeyCode::Char('[') => app.diff_view.prev_file(),
+                            KeyCode::Char('v') => app.diff_view.toggle_layout(),
+                            KeyCode::Char('c') => app.start_review_comment(),
+                            KeyCode::Char('x') => app.delete_review_comments()?,
+                            KeyCode::Char('R') => app.request_changes()?,
+                            _ => {}
+                        },
+                        InputMode::ReviewComment => match key.code {
+                            KeyCode::Enter => app.confirm_input()?,
+                            KeyCode::Esc => {
+                                app.input_buffer.clear();
+                                app.input_mode = InputMode::ViewDiff;
+                            }
+                            KeyCode::Backspace => app.handle_backspace(),
+                            KeyCode::Char('j') if key.modifiers.contains(crossterm::event::KeyModifiers::CONTROL) => {
+                                app.input_buffer.push('\n');
+                            }
+                            KeyCode::Char(c) if !key.modifiers.contains(crossterm::event::KeyModifiers::CONTROL) => {
+                                app.handle_input(c);
                             }
                             _ => {}
                         },
@@ -1181,6 +2914,28 @@ async fn main() -> anyhow::Result<()> {
                             KeyCode::Char('n') | KeyCode::Esc => app.cancel_input(),
                             _ => {}
                         },
+                        InputMode::ConfirmRerun => match key.code {
+                            KeyCode::Char('y') | KeyCode::Enter => app.confirm_rerun()?,
+                            KeyCode::Char('n') | KeyCode::Esc => {
+                                app.input_mode = InputMode::TaskDetail;
+                                app.status_message = None;
+                            }
+                            _ => {}
+                        },
+                        InputMode::ConfirmGc => match key.code {
+                            KeyCode::Char('y') | KeyCode::Enter => app.confirm_gc(false),
+                            KeyCode::Char('a') => app.confirm_gc(true),
+                            KeyCode::Char('n') | KeyCode::Esc => {
+                                app.gc_orphans.clear();
+                                app.cancel_input();
+                            }
+                            _ => {}
+                        },
+                        InputMode::ConfirmWaiveSecrets => match key.code {
+                            KeyCode::Char('y') | KeyCode::Enter => app.confirm_waive_secrets(),
+                            KeyCode::Char('n') | KeyCode::Esc => app.cancel_input(),
+                            _ => {}
+                        },
                         InputMode::Help => match key.code {
                             KeyCode::Esc | KeyCode::Char('q') | KeyCode::Char('?') => {
                                 app.input_mode = InputMode::Normal;
@@ -1215,17 +2970,13 @@ async fn main() -> anyhow::Result<()> {
                                         .unwrap_or(0);
                                 }
                             }
-                            KeyCode::Char('j') | KeyCode::Down => {
-                                if !app.selection_list.is_empty() {
-                                    app.selected_index = (app.selected_index + 1) % app.selection_list.len();
-                                }
+                            KeyCode::Char('j') | KeyCode::Down if !app.selection_list.is_empty() => {
+                                app.selected_index = (app.selected_index + 1) % app.selection_list.len();
                             }
-                            KeyCode::Char('k') | KeyCode::Up => {
-                                if !app.selection_list.is_empty() {
-                                    app.selected_index = app.selected_index
-                                        .checked_sub(1)
-                                        .unwrap_or(app.selection_list.len() - 1);
-                                }
+                            KeyCode::Char('k') | KeyCode::Up if !app.selection_list.is_empty() => {
+                                app.selected_index = app.selected_index
+                                    .checked_sub(1)
+                                    .unwrap_or(app.selection_list.len() - 1);
                             }
                             KeyCode::Enter => {
                                 // Set selected value
@@ -1280,7 +3031,23 @@ fn ui(frame: &mut Frame, app: &App) {
     } else {
         String::new()
     };
-    let header_text = format!(" HIVE - AI Agent Kanban  ({} tasks){}", task_count, running_indicator);
+    let total_usage = agent::total_usage(app.tasks.iter().flat_map(|t| &t.usage));
+    let usage_indicator = if total_usage.is_empty() {
+        String::new()
+    } else {
+        format!(" 💰 {} ", total_usage.summary())
+    };
+    let daily_limits = app.orchestrator.budget.daily;
+    let daily_indicator = if daily_limits.is_unlimited() {
+        String::new()
+    } else {
+        let today = Spend::today(app.usage_ledger.records());
+        format!(" 📅 today {} / {} ", today.usage.summary(), daily_limits.summary())
+    };
+    let header_text = format!(
+        " HIVE - AI Agent Kanban  ({} tasks){}{}{}",
+        task_count, running_indicator, usage_indicator, daily_indicator
+    );
     let header = Paragraph::new(header_text)
         .style(Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD))
         .alignment(Alignment::Center)
@@ -1343,7 +3110,25 @@ fn ui(frame: &mut Frame, app: &App) {
                 } else {
                     ""
                 };
-                ListItem::new(format!(" {}{} {}{}", spinner, status_icon, task.title, agent_icon)).style(style)
+                // Conflict marker
+                let conflict_icon = if task.needs_conflict_resolution() { " ⚠️" } else { "" };
+                // Possible secrets block PRs and merges
+                let secret_icon = if task.secret_findings.is_empty() { "" } else { " 🔒" };
+                // Policy errors block merging
+                let policy_icon = if task.policy_errors.is_empty() { "" } else { " ⛔" };
+                // Stacked on another task
+                let stack_marker = if task.parent.is_some() { "↳ " } else { "" };
+                // PR state (flagged when closed or changes were requested)
+                let pr_badge = match &task.pr_status {
+                    Some(pr) if pr.needs_attention() => format!(" 🚩 {}", pr.badge()),
+                    Some(pr) if task.status == TaskStatus::Review => format!(" [{}]", pr.badge()),
+                    _ => String::new(),
+                };
+                ListItem::new(format!(
+                    " {}{} {}{}{}{}{}{}{}",
+                    spinner, status_icon, stack_marker, task.title, agent_icon, conflict_icon, secret_icon, policy_icon, pr_badge
+                ))
+                .style(style)
             })
             .collect();
 
@@ -1399,7 +3184,7 @@ fn ui(frame: &mut Frame, app: &App) {
         InputMode::Normal => app
             .status_message
             .as_deref()
-            .unwrap_or(" [n]ew [a]ssign [d]iff [p]r [m]ove [g]merge [s]top [x]del [q]uit "),
+            .unwrap_or(" [n]ew [a]ssign [d]iff [f]ollow-up [p]r [m]ove [g]merge [s]top [x]del [q]uit "),
         _ => app.status_message.as_deref().unwrap_or(""),
     };
     let footer = Paragraph::new(footer_text)
@@ -1410,20 +3195,27 @@ fn ui(frame: &mut Frame, app: &App) {
 
     // Show popup in input mode
     match app.input_mode {
-        InputMode::NewTaskTitle | InputMode::NewTaskDescription => {
+        InputMode::NewTaskTitle | InputMode::NewTaskDescription | InputMode::FollowUp => {
             let popup_area = centered_rect(70, 30, area);
             frame.render_widget(Clear, popup_area);
 
             let (title, help) = match app.input_mode {
                 InputMode::NewTaskTitle => (
-                    "New Task - Title",
+                    "New Task - Title".to_string(),
                     " Enter: confirm | Ctrl+J: newline | ESC: cancel ",
                 ),
                 InputMode::NewTaskDescription => (
-                    "New Task - Description",
+                    "New Task - Description".to_string(),
                     " Enter: confirm (skip if empty) | Ctrl+J: newline | ESC: cancel ",
                 ),
-                _ => ("", ""),
+                InputMode::FollowUp => (
+                    format!(
+                        "Follow-up - {}",
+                        app.selected_task().map(|t| t.title.as_str()).unwrap_or_default()
+                    ),
+                    " Enter: run executor | Ctrl+J: newline | ESC: cancel ",
+                ),
+                _ => (String::new(), ""),
             };
 
             // Split popup into input area and help text
@@ -1469,12 +3261,15 @@ fn ui(frame: &mut Frame, app: &App) {
                         "claude" => ("🤖", "High code quality. Best for impl"),
                         _ => ("•", ""),
                     };
-                    let style = if i == app.selected_index {
-                        Style::default().bg(color).fg(Color::Black)
-                    } else {
-                        Style::default()
+                    let unavailable = app.unavailable_agents.contains(name);
+                    let style = match (i == app.selected_index, unavailable) {
+                        (true, false) => Style::default().bg(color).fg(Color::Black),
+                        (true, true) => Style::default().bg(Color::DarkGray).fg(Color::Gray),
+                        (false, true) => Style::default().fg(Color::DarkGray),
+                        (false, false) => Style::default(),
                     };
-                    ListItem::new(format!(" {} {} - {}", icon, name, desc)).style(style)
+                    let suffix = if unavailable { " (not installed)" } else { "" };
+                    ListItem::new(format!(" {} {} - {}{}", icon, name, desc, suffix)).style(style)
                 })
                 .collect();
 
@@ -1525,6 +3320,34 @@ fn ui(frame: &mut Frame, app: &App) {
                         Span::styled(branch, Style::default().fg(Color::Magenta)),
                     ]));
                 }
+                lines.push(Line::from(vec![
+                    Span::styled("Base: ", Style::default().fg(Color::Gray)),
+                    Span::styled(app.task_base_branch(task), Style::default().fg(Color::Magenta)),
+                ]));
+                if let Some(parent) = &task.parent {
+                    let title = app
+                        .tasks
+                        .iter()
+                        .find(|t| &t.id == parent)
+                        .map(|t| t.title.as_str())
+                        .unwrap_or("deleted task");
+                    lines.push(Line::from(vec![
+                        Span::styled("Stacked on: ", Style::default().fg(Color::Gray)),
+                        Span::styled(format!("{} ({})", title, parent), Style::default().fg(Color::Magenta)),
+                    ]));
+                }
+                let children: Vec<&str> = app
+                    .tasks
+                    .iter()
+                    .filter(|t| t.parent.as_ref() == Some(&task.id))
+                    .map(|t| t.title.as_str())
+                    .collect();
+                if !children.is_empty() {
+                    lines.push(Line::from(vec![
+                        Span::styled("Stacked tasks: ", Style::default().fg(Color::Gray)),
+                        Span::styled(children.join(", "), Style::default().fg(Color::Magenta)),
+                    ]));
+                }
                 if let Some(worktree) = &task.worktree {
                     lines.push(Line::from(vec![
                         Span::styled("Worktree: ", Style::default().fg(Color::Gray)),
@@ -1537,6 +3360,145 @@ fn ui(frame: &mut Frame, app: &App) {
                         Span::styled(pr_url, Style::default().fg(Color::LightCyan)),
                     ]));
                 }
+                if let Some(pr) = &task.pr_status {
+                    let color = if pr.needs_attention() { Color::Red } else { Color::White };
+                    lines.push(Line::from(vec![
+                        Span::styled("PR status: ", Style::default().fg(Color::Gray)),
+                        Span::styled(
+                            format!(
+                                "{} (checked {})",
+                                pr.badge().trim_start_matches("PR "),
+                                pr.checked_at.with_timezone(&chrono::Local).format("%H:%M")
+                            ),
+                            Style::default().fg(color),
+                        ),
+                    ]));
+                }
+                if !task.usage.is_empty() {
+                    lines.push(Line::from(vec![
+                        Span::styled("Usage: ", Style::default().fg(Color::Gray)),
+                        Span::styled(
+                            format!("{} ({} runs)", task.total_usage().summary(), task.usage.len()),
+                            Style::default().fg(Color::Yellow),
+                        ),
+                    ]));
+                    for (agent_name, usage) in task.usage_by_agent() {
+                        lines.push(Line::from(vec![
+                            Span::styled(format!("  {}: ", agent_name), Style::default().fg(Color::Gray)),
+                            Span::styled(
+                                format!(
+                                    "{} in / {} out / {} cached, ${:.2}",
+                                    agent::format_tokens(usage.input_tokens),
+                                    agent::format_tokens(usage.output_tokens),
+                                    agent::format_tokens(usage.cache_read_tokens + usage.cache_creation_tokens),
+                                    usage.cost_usd
+                                ),
+                                Style::default().fg(Color::White),
+                            ),
+                        ]));
+                    }
+                }
+                let budget = task.budget.unwrap_or(app.orchestrator.budget.per_task);
+                if !budget.is_unlimited() {
+                    lines.push(Line::from(vec![
+                        Span::styled("Budget: ", Style::default().fg(Color::Gray)),
+                        Span::styled(budget.summary(), Style::default().fg(Color::White)),
+                    ]));
+                }
+                if let Some(error) = &task.last_error {
+                    lines.push(Line::from(vec![
+                        Span::styled("Last error: ", Style::default().fg(Color::Gray)),
+                        Span::styled(error, Style::default().fg(Color::Red)),
+                    ]));
+                }
+                for failure in &task.bootstrap_errors {
+                    lines.push(Line::from(vec![
+                        Span::styled("Bootstrap: ", Style::default().fg(Color::Gray)),
+                        Span::styled(failure, Style::default().fg(Color::Red)),
+                    ]));
+                }
+                if !task.review_comments.is_empty() {
+                    lines.push(Line::from(vec![
+                        Span::styled("Review: ", Style::default().fg(Color::Gray)),
+                        Span::styled(
+                            format!("{} pending comments (R in diff view to request changes)", task.review_comments.len()),
+                            Style::default().fg(Color::Yellow),
+                        ),
+                    ]));
+                }
+                if task.needs_conflict_resolution() {
+                    lines.push(Line::from(vec![
+                        Span::styled("Conflicts: ", Style::default().fg(Color::Gray)),
+                        Span::styled(task.conflicts.join(", "), Style::default().fg(Color::Red)),
+                    ]));
+                }
+                for error in &task.policy_errors {
+                    lines.push(Line::from(vec![
+                        Span::styled("Policy: ", Style::default().fg(Color::Gray)),
+                        Span::styled(error, Style::default().fg(Color::Red)),
+                    ]));
+                }
+                for warning in &task.policy_warnings {
+                    lines.push(Line::from(vec![
+                        Span::styled("Policy: ", Style::default().fg(Color::Gray)),
+                        Span::styled(warning, Style::default().fg(Color::Yellow)),
+                    ]));
+                }
+                for finding in &task.secret_findings {
+                    lines.push(Line::from(vec![
+                        Span::styled("Secret: ", Style::default().fg(Color::Gray)),
+                        Span::styled(finding.describe(), Style::default().fg(Color::Red)),
+                    ]));
+                }
+                if !task.runs.is_empty() {
+                    lines.push(Line::from(""));
+                    lines.push(Line::styled(
+                        format!("Runs ({}) [j/k select, r rerun]", task.runs.len()),
+                        Style::default().fg(Color::Gray),
+                    ));
+                    for (i, run) in task.runs.iter().enumerate() {
+                        let is_selected = i == app.detail_run_index;
+                        let ended = run
+                            .ended_at
+                            .map(|t| t.with_timezone(&chrono::Local).format("%H:%M").to_string())
+                            .unwrap_or_else(|| "…".into());
+                        let exit = run
+                            .exit_code
+                            .map(|c| format!(" exit {}", c))
+                            .unwrap_or_default();
+                        let rerun = run
+                            .rerun_of
+                            .as_deref()
+                            .map(|r| format!(" (rerun of {})", r))
+                            .unwrap_or_default();
+                        let style = if is_selected {
+                            Style::default().bg(Color::DarkGray).fg(Color::White)
+                        } else {
+                            Style::default().fg(Color::White)
+                        };
+                        lines.push(Line::styled(
+                            format!(
+                                " {} {} {} {} {}-{} base {}{}{}",
+                                run.status.icon(),
+                                run.id,
+                                run.role.display_name(),
+                                run.agent,
+                                run.started_at.with_timezone(&chrono::Local).format("%m-%d %H:%M"),
+                                ended,
+                                run.short_base_commit(),
+                                exit,
+                                rerun
+                            ),
+                            style,
+                        ));
+                        if let Some(follow_up) = &run.follow_up {
+                            lines.push(Line::styled(
+                                format!("    ↳ follow-up: {}", follow_up.replace('\n', " ")),
+                                Style::default().fg(Color::Gray),
+                            ));
+                        }
+                    }
+                }
                 lines.push(Line::from(""));
                 lines.push(Line::from(vec![
                     Span::styled("ID: ", Style::default().fg(Color::DarkGray)),
@@ -1553,41 +3515,39 @@ fn ui(frame: &mut Frame, app: &App) {
                 frame.render_widget(detail, popup_area);
             }
         }
-        InputMode::ViewDiff => {
-            let popup_area = centered_rect(80, 80, area);
-            frame.render_widget(Clear, popup_area);
-
-            let lines: Vec<Line> = app.diff_content
-                .lines()
-                .skip(app.diff_scroll)
-                .take(popup_area.height as usize - 2)
-                .map(|line| {
-                    let style = if line.starts_with('+') && !line.starts_with("+++") {
-                        Style::default().fg(Color::Green)
-                    } else if line.starts_with('-') && !line.starts_with("---") {
-                        Style::default().fg(Color::Red)
-                    } else if line.starts_with("@@") {
-                        Style::default().fg(Color::Cyan)
-                    } else if line.starts_with("diff") || line.starts_with("index") {
-                        Style::default().fg(Color::Yellow)
-                    } else {
-                        Style::default().fg(Color::White)
-                    };
-                    Line::styled(line, style)
-                })
-                .collect();
-
-            let total_lines = app.diff_content.lines().count();
-            let title = format!("📄 Diff ({}/{} lines) [j/k scroll, ESC close]", app.diff_scroll + 1, total_lines);
-
-            let diff_view = Paragraph::new(lines)
-                .block(
-                    Block::default()
-                        .title(title)
-                        .borders(Borders::ALL)
-                        .border_style(Style::default().fg(Color::Yellow)),
-                );
-            frame.render_widget(diff_view, popup_area);
+        InputMode::ViewDiff | InputMode::ReviewComment => {
+            let comments = app
+                .selected_task()
+                .map(|t| t.review_comments.as_slice())
+                .unwrap_or_default();
+            render_diff_view(frame, &app.diff_view, comments, centered_rect(90, 85, area));
+
+            if app.input_mode == InputMode::ReviewComment {
+                let popup_area = centered_rect(60, 25, area);
+                frame.render_widget(Clear, popup_area);
+                let location = app
+                    .diff_cursor_anchor()
+                    .map(|(path, line, _, _)| format!("{}:{}", path, line))
+                    .unwrap_or_default();
+                let popup_layout = Layout::default()
+                    .direction(Direction::Vertical)
+                    .constraints([Constraint::Min(3), Constraint::Length(1)])
+                    .split(popup_area);
+                let input = Paragraph::new(app.input_buffer.as_str())
+                    .style(Style::default().fg(Color::Yellow))
+                    .wrap(ratatui::widgets::Wrap { trim: false })
+                    .block(
+                        Block::default()
+                            .title(format!("💬 Comment on {}", location))
+                            .borders(Borders::ALL)
+                            .border_style(Style::default().fg(Color::Cyan)),
+                    );
+                frame.render_widget(input, popup_layout[0]);
+                let help_text = Paragraph::new(" Enter: add comment | Ctrl+J: newline | ESC: cancel ")
+                    .style(Style::default().fg(Color::DarkGray))
+                    .alignment(Alignment::Center);
+                frame.render_widget(help_text, popup_layout[1]);
+            }
         }
         InputMode::ConfirmMerge => {
             if let Some(task) = app.selected_task() {
@@ -1599,13 +3559,23 @@ fn ui(frame: &mut Frame, app: &App) {
                     Line::from(vec![
                         Span::styled("Merge ", Style::default().fg(Color::White)),
                         Span::styled(&task.title, Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD)),
-                        Span::styled(" to main?", Style::default().fg(Color::White)),
+                        Span::styled(
+                            format!(" to {}?", app.task_base_branch(task)),
+                            Style::default().fg(Color::White),
+                        ),
                     ]),
                     Line::from(""),
                     Line::from(vec![
                         Span::styled("Branch: ", Style::default().fg(Color::Gray)),
                         Span::styled(task.branch.as_deref().unwrap_or("unknown"), Style::default().fg(Color::Magenta)),
                     ]),
+                    Line::from(vec![
+                        Span::styled("Strategy: ", Style::default().fg(Color::Gray)),
+                        Span::styled(
+                            app.git_config.merge.strategy.display_name(),
+                            Style::default().fg(Color::Magenta),
+                        ),
+                    ]),
                     Line::from(""),
                     Line::styled("[y] Yes  [n] No", Style::default().fg(Color::Yellow)),
                 ];
@@ -1621,8 +3591,105 @@ fn ui(frame: &mut Frame, app: &App) {
                 frame.render_widget(confirm, popup_area);
             }
         }
+        InputMode::ConfirmRerun => {
+            if let Some(run) = app
+                .selected_task()
+                .and_then(|t| t.runs.get(app.detail_run_index))
+            {
+                let popup_area = centered_rect(60, 30, area);
+                frame.render_widget(Clear, popup_area);
+
+                let lines = vec![
+                    Line::from(""),
+                    Line::from(vec![
+                        Span::styled("Rerun ", Style::default().fg(Color::White)),
+                        Span::styled(&run.id, Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD)),
+                        Span::styled(
+                            format!(" ({} {})", run.role.display_name(), run.agent),
+                            Style::default().fg(Color::White),
+                        ),
+                    ]),
+                    Line::from(""),
+                    Line::from(vec![
+                        Span::styled("Command: ", Style::default().fg(Color::Gray)),
+                        Span::styled(
+                            format!("{} {}", run.command, run.args.join(" ")),
+                            Style::default().fg(Color::Magenta),
+                        ),
+                    ]),
+                    Line::from(vec![
+                        Span::styled("Fresh worktree at ", Style::default().fg(Color::Gray)),
+                        Span::styled(run.short_base_commit(), Style::default().fg(Color::Magenta)),
+                    ]),
+                    Line::from(""),
+                    Line::styled("[y] Yes  [n] No", Style::default().fg(Color::Yellow)),
+                ];
+
+                let confirm = Paragraph::new(lines)
+                    .alignment(Alignment::Center)
+                    .block(
+                        Block::default()
+                            .title("🔁 Confirm Rerun")
+                            .borders(Borders::ALL)
+                            .border_style(Style::default().fg(Color::Yellow)),
+                    );
+                frame.render_widget(confirm, popup_area);
+            }
+        }
+        InputMode::ConfirmGc => {
+            let popup_area = centered_rect(70, 60, area);
+            frame.render_widget(Clear, popup_area);
+
+            let mut lines = vec![Line::from("")];
+            lines.extend(app.gc_orphans.iter().map(|orphan| {
+                Line::styled(format!("  {}", orphan.describe()), Style::default().fg(Color::White))
+            }));
+            lines.push(Line::from(""));
+            let keys = if app.gc_orphans.iter().any(Orphan::is_unmerged) {
+                "  [y] Prune all but unmerged branches  [a] Prune all  [n] Cancel"
+            } else {
+                "  [y] Prune all  [n] Cancel"
+            };
+            lines.push(Line::styled(keys, Style::default().fg(Color::Yellow)));
+
+            let confirm = Paragraph::new(lines)
+                .wrap(ratatui::widgets::Wrap { trim: false })
+                .block(
+                    Block::default()
+                        .title(format!("🧹 Prune {} Orphans", app.gc_orphans.len()))
+                        .borders(Borders::ALL)
+                        .border_style(Style::default().fg(Color::Yellow)),
+                );
+            frame.render_widget(confirm, popup_area);
+        }
+        InputMode::ConfirmWaiveSecrets => {
+            let popup_area = centered_rect(70, 60, area);
+            frame.render_widget(Clear, popup_area);
+
+            let findings = app.selected_task().map(|t| t.secret_findings.as_slice()).unwrap_or_default();
+            let mut lines = vec![Line::from("")];
+            lines.extend(findings.iter().map(|finding| {
+                Line::styled(format!("  {}", finding.describe()), Style::default().fg(Color::White))
+            }));
+            lines.push(Line::from(""));
+            lines.push(Line::styled(
+                "  Waived findings no longer block PRs and merges of this task",
+                Style::default().fg(Color::Gray),
+            ));
+            lines.push(Line::styled("  [y] Waive all  [n] Cancel", Style::default().fg(Color::Yellow)));
+
+            let confirm = Paragraph::new(lines)
+                .wrap(ratatui::widgets::Wrap { trim: false })
+                .block(
+                    Block::default()
+                        .title(format!("🔒 Waive {} Secret Findings", findings.len()))
+                        .borders(Borders::ALL)
+                        .border_style(Style::default().fg(Color::Red)),
+                );
+            frame.render_widget(confirm, popup_area);
+        }
         InputMode::Help => {
-            let popup_area = centered_rect(60, 70, area);
+            let popup_area = centered_rect(60, 75, area);
             frame.render_widget(Clear, popup_area);
 
             let help_lines = vec![
@@ -1633,12 +3700,18 @@ fn ui(frame: &mut Frame, app: &App) {
                 Line::from(""),
                 Line::styled("  Task Management", Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD)),
                 Line::from("  n    New task        a    Assign agent"),
+                Line::from("  N    New task stacked on the selected task"),
                 Line::from("  m/Tab  Move forward  M/S-Tab  Move back"),
                 Line::from("  x/Del  Delete task   Enter  Task detail"),
                 Line::from(""),
                 Line::styled("  Agents & Git", Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD)),
                 Line::from("  s    Stop agent      d    Show diff"),
-                Line::from("  p    Create PR       g    Merge to main"),
+                Line::from("  p    Create PR       g    Merge to base"),
+                Line::from("  u    Sync with base  r    Rerun run (detail)"),
+                Line::from("  G    Clean up orphaned worktrees/branches"),
+                Line::from("  W    Waive secret findings of the task"),
+                Line::from("  f    Follow-up instruction for the executor"),
+                Line::from("  c    Comment line (diff)  R  Request changes (diff)"),
                 Line::from(""),
                 Line::styled("  Other", Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD)),
                 Line::from("  S    Settings        ?    Show this help"),
@@ -1728,6 +3801,216 @@ fn ui(frame: &mut Frame, app: &App) {
     }
 }
 
+/// Two-pane diff view: file list with stats on the left, hunks of the selected file on the right.
+/// Only the visible rows are turned into lines; lines with review comments are marked.
+fn render_diff_view(frame: &mut Frame, view: &DiffView, comments: &[ReviewComment], area: Rect) {
+    frame.render_widget(Clear, area);
+    let panes = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Percentage(30), Constraint::Percentage(70)])
+        .split(area);
+
+    // File list, scrolled to keep the selection visible
+    let list_height = panes[0].height.saturating_sub(2) as usize;
+    let first = (view.selected() + 1).saturating_sub(list_height);
+    let files: Vec<Line> = view
+        .files()
+        .iter()
+        .enumerate()
+        .skip(first)
+        .take(list_height)
+        .map(|(index, file)| {
+            let kind_color = match file.kind {
+                ChangeKind::Added => Color::Green,
+                ChangeKind::Deleted => Color::Red,
+                ChangeKind::Renamed => Color::Magenta,
+                ChangeKind::Modified => Color::Yellow,
+            };
+            let selected = index == view.selected();
+            let path_style = if selected {
+                Style::default().fg(Color::Black).bg(Color::Cyan)
+            } else {
+                Style::default().fg(Color::White)
+            };
+            Line::from(vec![
+                Span::styled(format!("{} ", file.kind.symbol()), Style::default().fg(kind_color)),
+                Span::styled(format!("+{:<4}", file.additions), Style::default().fg(Color::Green)),
+                Span::styled(format!("-{:<4} ", file.deletions), Style::default().fg(Color::Red)),
+                Span::styled(file.display_path(), path_style),
+            ])
+        })
+        .collect();
+    let (additions, deletions) = view.totals();
+    let file_list = Paragraph::new(files).block(
+        Block::default()
+            .title(format!("📁 {} files +{} -{}", view.files().len(), additions, deletions))
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(Color::Yellow)),
+    );
+    frame.render_widget(file_list, panes[0]);
+
+    // Hunks of the selected file
+    let Some(file) = view.selected_file() else {
+        return;
+    };
+    // Last line of the pane shows the comments of the cursor line
+    let hunk_height = panes[1].height.saturating_sub(3) as usize;
+    let first = view.window(hunk_height);
+    let rows: Vec<(bool, DiffRow)> = view
+        .rows()
+        .iter()
+        .enumerate()
+        .skip(first)
+        .take(hunk_height)
+        .map(|(index, row)| (index == view.cursor(), *row))
+        .collect();
+    let comments_on = |hunk: usize, line: usize| -> Vec<&ReviewComment> {
+        let Some(diff_line) = view.line(hunk, line) else {
+            return Vec::new();
+        };
+        let removed = diff_line.kind == LineKind::Removed;
+        let number = if removed { diff_line.old_line } else { diff_line.new_line };
+        comments
+            .iter()
+            .filter(|c| number.is_some_and(|n| c.is_on(&file.path, n, removed)))
+            .collect()
+    };
+    let line = |hunk: usize, line: usize, numbers: (bool, bool), cursor: bool| {
+        let commented = !comments_on(hunk, line).is_empty();
+        diff_line(view, hunk, line, numbers, cursor, commented)
+    };
+    let header = |hunk: usize, cursor: bool| {
+        let style = Style::default().fg(Color::Cyan);
+        Line::styled(
+            format!(
+                "{}{}",
+                if cursor { "▶" } else { " " },
+                view.hunk(hunk).map(|h| h.header.as_str()).unwrap_or_default()
+            ),
+            if cursor { style.bg(Color::DarkGray) } else { style },
+        )
+    };
+    let footer = match view.cursor_line().map(|(hunk, line)| comments_on(hunk, line)) {
+        Some(on_line) if !on_line.is_empty() => Line::styled(
+            format!(
+                "💬 {}",
+                on_line.iter().map(|c| c.body.replace('\n', " ")).collect::<Vec<_>>().join(" | ")
+            ),
+            Style::default().fg(Color::Yellow),
+        ),
+        _ if !comments.is_empty() => Line::styled(
+            format!("💬 {} pending comments [x delete, R request changes]", comments.len()),
+            Style::default().fg(Color::DarkGray),
+        ),
+        _ => Line::styled("[c comment on the selected line]", Style::default().fg(Color::DarkGray)),
+    };
+    // Lines of one pane; `side` picks the old (Some(true)) or new (Some(false)) half of pairs
+    let pane_lines = |side: Option<bool>| -> Vec<Line> {
+        let mut lines: Vec<Line> = rows
+            .iter()
+            .map(|&(cursor, row)| match row {
+                DiffRow::Hunk(hunk) => header(hunk, cursor),
+                DiffRow::Line(hunk, l) => line(hunk, l, (true, true), cursor),
+                DiffRow::Pair(hunk, old, new) => match side {
+                    Some(true) => old.map(|l| line(hunk, l, (true, false), cursor)),
+                    _ => new.map(|l| line(hunk, l, (false, true), cursor)),
+                }
+                .unwrap_or_default(),
+            })
+            .collect();
+        if file.binary {
+            lines.push(Line::styled("Binary file", Style::default().fg(Color::DarkGray)));
+        } else if file.hunks.is_empty() {
+            lines.push(Line::styled("No content changes", Style::default().fg(Color::DarkGray)));
+        }
+        lines.resize(hunk_height, Line::default());
+        lines.push(footer.clone());
+        lines
+    };
+    let pane = |lines: Vec<Line<'static>>, title: String| {
+        Paragraph::new(lines).block(
+            Block::default()
+                .title(title)
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(Color::Yellow)),
+        )
+    };
+
+    let title = format!(
+        "📄 {} ({}/{}) [j/k move, n/N hunk, Tab/S-Tab file, v layout, c comment, ESC close]",
+        file.display_path(),
+        (view.cursor() + 1).min(view.rows().len()),
+        view.rows().len()
+    );
+    if view.side_by_side() {
+        let sides = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
+            .split(panes[1]);
+        frame.render_widget(pane(pane_lines(Some(true)), title), sides[0]);
+        let new_title = format!("📄 {}", file.path);
+        frame.render_widget(pane(pane_lines(Some(false)), new_title), sides[1]);
+    } else {
+        frame.render_widget(pane(pane_lines(None), title), panes[1]);
+    }
+}
+
+/// One diff line with line numbers, syntax colors and word-level change highlights.
+/// `numbers` selects the old/new line number columns; the cursor line and commented
+/// lines are marked in the gutter.
+fn diff_line(
+    view: &DiffView,
+    hunk: usize,
+    line: usize,
+    numbers: (bool, bool),
+    cursor: bool,
+    commented: bool,
+) -> Line<'static> {
+    let Some(diff_line) = view.line(hunk, line) else {
+        return Line::default();
+    };
+    let (sign, color, background, emphasis) = match diff_line.kind {
+        LineKind::Added => ('+', Color::Green, Color::Rgb(0, 40, 0), Color::Rgb(0, 100, 0)),
+        LineKind::Removed => ('-', Color::Red, Color::Rgb(50, 0, 0), Color::Rgb(120, 0, 0)),
+        LineKind::Context => (' ', Color::White, Color::Reset, Color::Reset),
+    };
+    let number = |n: Option<u32>| n.map(|n| format!("{:>4} ", n)).unwrap_or_else(|| "     ".into());
+    let mut gutter = String::new();
+    if numbers.0 {
+        gutter.push_str(&number(diff_line.old_line));
+    }
+    if numbers.1 {
+        gutter.push_str(&number(diff_line.new_line));
+    }
+
+    let marker = match (cursor, commented) {
+        (_, true) => "●",
+        (true, false) => "▶",
+        (false, false) => " ",
+    };
+    let gutter_style = if cursor {
+        Style::default().fg(Color::White).bg(Color::DarkGray)
+    } else {
+        Style::default().fg(Color::DarkGray)
+    };
+
+    let mut spans = vec![
+        Span::styled(marker, gutter_style.fg(Color::Yellow)),
+        Span::styled(gutter, gutter_style),
+        Span::styled(sign.to_string(), Style::default().fg(color).bg(background)),
+    ];
+    spans.extend(view.segments(hunk, line).into_iter().map(|segment| {
+        let style = Style::default().fg(segment.color.unwrap_or(color));
+        let style = if segment.emphasized {
+            style.bg(emphasis).add_modifier(Modifier::BOLD)
+        } else {
+            style.bg(background)
+        };
+        Span::styled(segment.text, style)
+    }));
+    Line::from(spans)
+}
+
 /// Calculate centered rectangle
 fn centered_rect(percent_x: u16, percent_y: u16, r: Rect) -> Rect {
     let popup_layout = Layout::default()
@@ -0,0 +1,367 @@
+use crate::git::{render_template, FileDiff};
+use crate::task::{RunStatus, Task};
+
+/// Default pull request description. Sections whose placeholders are all empty are dropped.
+pub const DEFAULT_BODY_TEMPLATE: &str = "## Summary
+{summary}
+
+## Plan
+{overview}
+
+### Steps
+{steps}
+
+## Changes
+{stat}
+
+{files}
+
+## Verification
+{verification}
+
+## Agents
+{agents}
+
+## Related Tasks
+{related}
+
+---
+🤖 Generated with Hive";
+
+/// Files listed individually before the rest is summarized
+const MAX_LISTED_FILES: usize = 50;
+
+/// Everything a pull request description is built from
+pub struct PrBody<'a> {
+    pub task: &'a Task,
+    /// All tasks (to link the parent and stacked children)
+    pub tasks: &'a [Task],
+    /// "## Overview" section of the plan
+    pub overview: Option<String>,
+    /// "## Implementation Steps" section of the plan
+    pub steps: Option<String>,
+    /// Changes of the task branch against its base
+    pub files: &'a [FileDiff],
+    pub base: &'a str,
+}
+
+impl PrBody<'_> {
+    /// Render the description from a template with `{summary}`, `{title}`, `{id}`,
+    /// `{overview}`, `{steps}`, `{stat}`, `{files}`, `{verification}`, `{agents}`,
+    /// `{related}`, `{branch}` and `{base}` placeholders
+    pub fn render(&self, template: &str) -> String {
+        let task = self.task;
+        let summary = if task.description.is_empty() {
+            &task.title
+        } else {
+            &task.description
+        };
+        let rendered = render_template(
+            template,
+            &[
+                ("summary", summary),
+                ("title", &task.title),
+                ("id", &task.id),
+                ("overview", self.overview.as_deref().unwrap_or_default()),
+                ("steps", self.steps.as_deref().unwrap_or_default()),
+                ("stat", &self.stat()),
+                ("files", &self.files_summary()),
+                ("verification", &self.verification()),
+                ("agents", &self.agents()),
+                ("related", &self.related()),
+                ("branch", task.branch.as_deref().unwrap_or_default()),
+                ("base", self.base),
+            ],
+        );
+        drop_empty_sections(&rendered)
+    }
+
+    /// e.g. "3 files changed, +40 -12"
+    fn stat(&self) -> String {
+        if self.files.is_empty() {
+            return String::new();
+        }
+        let additions: usize = self.files.iter().map(|f| f.additions).sum();
+        let deletions: usize = self.files.iter().map(|f| f.deletions).sum();
+        format!(
+            "{} file{} changed, +{} -{}",
+            self.files.len(),
+            if self.files.len() == 1 { "" } else { "s" },
+            additions,
+            deletions
+        )
+    }
+
+    /// One line per file: "- `M` src/lib.rs (+3 -1)"
+    fn files_summary(&self) -> String {
+        let mut lines: Vec<String> = self
+            .files
+            .iter()
+            .take(MAX_LISTED_FILES)
+            .map(|file| {
+                let counts = if file.binary {
+                    "binary".to_string()
+                } else {
+                    format!("+{} -{}", file.additions, file.deletions)
+                };
+                format!("- `{}` {} ({})", file.kind.symbol(), file.display_path(), counts)
+            })
+            .collect();
+        if self.files.len() > MAX_LISTED_FILES {
+            lines.push(format!("- … and {} more files", self.files.len() - MAX_LISTED_FILES));
+        }
+        lines.join("\n")
+    }
+
+    /// Outcome of every finished agent run
+    fn verification(&self) -> String {
+        self.task
+            .runs
+            .iter()
+            .filter(|run| run.status != RunStatus::Running)
+            .map(|run| {
+                let mut line = format!("- {} {} `{}`", run.status.icon(), run.role.display_name(), run.agent);
+                if let Some(code) = run.exit_code {
+                    line.push_str(&format!(" (exit {})", code));
+                }
+                if let Some(follow_up) = &run.follow_up {
+                    line.push_str(&format!(" — follow-up: {}", first_line(follow_up)));
+                }
+                if let Some(error) = &run.error {
+                    line.push_str(&format!(" — {}", first_line(error)));
+                }
+                line
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    /// Agents that worked on the task with their run count and usage
+    fn agents(&self) -> String {
+        let task = self.task;
+        let usage = task.usage_by_agent();
+        let mut agents: Vec<(&str, &str)> = Vec::new();
+        for run in &task.runs {
+            let entry = (run.role.display_name(), run.agent.as_str());
+            if !agents.contains(&entry) {
+                agents.push(entry);
+            }
+        }
+        agents
+            .into_iter()
+            .map(|(role, agent)| {
+                let runs = task
+                    .runs
+                    .iter()
+                    .filter(|run| run.role.display_name() == role && run.agent == agent)
+                    .count();
+                let mut line = format!(
+                    "- {}: `{}` ({} run{})",
+                    role,
+                    agent,
+                    runs,
+                    if runs == 1 { "" } else { "s" }
+                );
+                if let Some(usage) = usage.get(agent).filter(|u| !u.is_empty()) {
+                    line.push_str(&format!(" — {}", usage.summary()));
+                }
+                line
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    /// Parent the task is stacked on and tasks stacked on it
+    fn related(&self) -> String {
+        let describe = |task: &Task| match &task.pr_url {
+            Some(url) => format!("{} ({})", task.title, url),
+            None => match &task.branch {
+                Some(branch) => format!("{} (`{}`)", task.title, branch),
+                None => task.title.clone(),
+            },
+        };
+        let parent = self
+            .task
+            .parent
+            .as_deref()
+            .and_then(|id| self.tasks.iter().find(|t| t.id == id))
+            .map(|parent| format!("- Stacked on: {}", describe(parent)));
+        let children = self
+            .tasks
+            .iter()
+            .filter(|t| t.parent.as_deref() == Some(self.task.id.as_str()))
+            .map(|child| format!("- Stacked: {}", describe(child)));
+        parent.into_iter().chain(children).collect::<Vec<_>>().join("\n")
+    }
+}
+
+fn first_line(text: &str) -> &str {
+    text.lines().next().unwrap_or_default().trim()
+}
+
+/// Heading level of a markdown line ("## X" → 2)
+fn heading_level(line: &str) -> Option<usize> {
+    let level = line.chars().take_while(|c| *c == '#').count();
+    (level > 0 && line[level..].starts_with(' ')).then_some(level)
+}
+
+/// Remove headings whose section (up to the next heading of the same or a higher
+/// level, or a `---` rule) has no content
+fn drop_empty_sections(markdown: &str) -> String {
+    let lines: Vec<&str> = markdown.lines().collect();
+
+    // Heading levels, ignoring lines inside code fences
+    let mut in_fence = false;
+    let levels: Vec<Option<usize>> = lines
+        .iter()
+        .map(|line| {
+            if line.trim_start().starts_with("```") {
+                in_fence = !in_fence;
+                return None;
+            }
+            if in_fence {
+                None
+            } else {
+                heading_level(line)
+            }
+        })
+        .collect();
+
+    let has_content = |start: usize, level: usize| {
+        for (line, line_level) in lines.iter().zip(&levels).skip(start + 1) {
+            match line_level {
+                Some(l) if *l <= level => return false,
+                Some(_) => continue,
+                None if line.trim() == "---" => return false,
+                None if !line.trim().is_empty() => return true,
+                None => {}
+            }
+        }
+        false
+    };
+
+    let kept: Vec<&str> = lines
+        .iter()
+        .enumerate()
+        .filter(|(i, _)| levels[*i].is_none_or(|level| has_content(*i, level)))
+        .map(|(_, line)| *line)
+        .collect();
+    render_template(&kept.join("\n"), &[])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::agent::AgentRole;
+    use crate::git::ChangeKind;
+    use crate::task::RunRecord;
+
+    fn file(path: &str, kind: ChangeKind, additions: usize, deletions: usize) -> FileDiff {
+        FileDiff {
+            path: path.into(),
+            old_path: None,
+            kind,
+            additions,
+            deletions,
+            binary: false,
+            hunks: Vec::new(),
+        }
+    }
+
+    fn run(role: AgentRole, agent: &str, status: RunStatus, exit_code: i32) -> RunRecord {
+        let mut run = RunRecord::new(role, agent, agent, Vec::new(), "prompt", "/tmp");
+        run.finish(status, Some(exit_code), None);
+        run
+    }
+
+    // ========================================
+    // Sections
+    // ========================================
+
+    #[test]
+    fn test_drop_empty_sections() {
+        let markdown = "## A\ntext\n\n## B\n\n### B1\n\n## C\n### C1\nitem\n\n---\nfooter";
+        assert_eq!(drop_empty_sections(markdown), "## A\ntext\n\n## C\n### C1\nitem\n\n---\nfooter");
+    }
+
+    #[test]
+    fn test_code_fence_is_content() {
+        let markdown = "## Steps\n```sh\n# build\n```\n## Empty";
+        assert_eq!(drop_empty_sections(markdown), "## Steps\n```sh\n# build\n```");
+    }
+
+    // ========================================
+    // Rendering
+    // ========================================
+
+    #[test]
+    fn test_render_minimal_task() {
+        let task = Task::new("Add login", "");
+        let body = PrBody {
+            task: &task,
+            tasks: std::slice::from_ref(&task),
+            overview: None,
+            steps: None,
+            files: &[],
+            base: "main",
+        };
+        assert_eq!(
+            body.render(DEFAULT_BODY_TEMPLATE),
+            "## Summary\nAdd login\n\n---\n🤖 Generated with Hive"
+        );
+    }
+
+    #[test]
+    fn test_render_full_task() {
+        let mut parent = Task::new("Auth API", "");
+        parent.pr_url = Some("https://github.com/o/r/pull/1".into());
+        let mut task = Task::new("Add login", "Login form");
+        task.parent = Some(parent.id.clone());
+        task.runs = vec![
+            run(AgentRole::Planner, "gemini", RunStatus::Succeeded, 0),
+            run(AgentRole::Executor, "claude", RunStatus::Failed, 1),
+            run(AgentRole::Executor, "claude", RunStatus::Succeeded, 0),
+        ];
+        let mut child = Task::new("Logout", "");
+        child.parent = Some(task.id.clone());
+        child.branch = Some("hive/logout".into());
+        let tasks = vec![parent, task.clone(), child];
+        let files = [
+            file("src/login.rs", ChangeKind::Added, 40, 0),
+            file("src/lib.rs", ChangeKind::Modified, 2, 1),
+        ];
+
+        let body = PrBody {
+            task: &task,
+            tasks: &tasks,
+            overview: Some("Adds a login form".into()),
+            steps: Some("1. Form\n2. Handler".into()),
+            files: &files,
+            base: "hive/auth",
+        }
+        .render(DEFAULT_BODY_TEMPLATE);
+
+        assert!(body.starts_with("## Summary\nLogin form\n\n## Plan\nAdds a login form\n\n### Steps\n1. Form"));
+        assert!(body.contains("2 files changed, +42 -1\n\n- `A` src/login.rs (+40 -0)\n- `M` src/lib.rs (+2 -1)"));
+        assert!(body.contains("- ✅ planner `gemini` (exit 0)\n- ❌ executor `claude` (exit 1)"));
+        assert!(body.contains("- planner: `gemini` (1 run)\n- executor: `claude` (2 runs)"));
+        assert!(body.contains("- Stacked on: Auth API (https://github.com/o/r/pull/1)\n- Stacked: Logout (`hive/logout`)"));
+    }
+
+    #[test]
+    fn test_render_custom_template() {
+        let task = Task::new("Fix bug", "");
+        let body = PrBody {
+            task: &task,
+            tasks: &[],
+            overview: None,
+            steps: None,
+            files: &[file("a.rs", ChangeKind::Modified, 1, 1)],
+            base: "main",
+        };
+        assert_eq!(
+            body.render("{title} → {base}\n\n## Notes\n{verification}\n\n## Diff\n{stat}"),
+            "Fix bug → main\n\n## Diff\n1 file changed, +1 -1"
+        );
+    }
+}
@@ -0,0 +1,232 @@
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+
+use super::body::DEFAULT_BODY_TEMPLATE;
+use super::fake::FakeProvider;
+use super::github::GithubProvider;
+use super::gitlab::GitlabProvider;
+use super::provider::PullRequestProvider;
+
+/// Default file of the fake provider (relative to the hive directory)
+pub const DEFAULT_FAKE_FILE: &str = "fake-prs.json";
+
+/// Default seconds between pull request status polls
+pub const DEFAULT_POLL_SECS: u64 = 60;
+
+/// Forge hosting the project's pull requests
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Forge {
+    /// GitHub via `gh`
+    #[default]
+    Github,
+    /// GitLab via `glab`
+    Gitlab,
+    /// Local JSON file (no remote)
+    Fake,
+}
+
+/// Options applied to newly opened pull requests. Set in the `pr` config section and
+/// overridable per task (`pr_options` in tasks.json); unset fields fall back.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PrOptions {
+    /// Open as draft
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub draft: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub labels: Option<Vec<String>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reviewers: Option<Vec<String>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub assignees: Option<Vec<String>>,
+}
+
+impl PrOptions {
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// These options with the fields set in `overrides` replaced
+    pub fn overridden_by(&self, overrides: &PrOptions) -> PrOptions {
+        PrOptions {
+            draft: overrides.draft.or(self.draft),
+            labels: overrides.labels.clone().or_else(|| self.labels.clone()),
+            reviewers: overrides.reviewers.clone().or_else(|| self.reviewers.clone()),
+            assignees: overrides.assignees.clone().or_else(|| self.assignees.clone()),
+        }
+    }
+
+    pub fn draft(&self) -> bool {
+        self.draft.unwrap_or(false)
+    }
+
+    pub fn labels(&self) -> &[String] {
+        self.labels.as_deref().unwrap_or_default()
+    }
+
+    pub fn reviewers(&self) -> &[String] {
+        self.reviewers.as_deref().unwrap_or_default()
+    }
+
+    pub fn assignees(&self) -> &[String] {
+        self.assignees.as_deref().unwrap_or_default()
+    }
+}
+
+/// Pull request configuration
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct PrConfig {
+    /// Forge the pull requests are opened on
+    #[serde(default)]
+    pub provider: Forge,
+    /// File of the fake provider (defaults to `.hive/fake-prs.json`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fake_file: Option<PathBuf>,
+    /// Seconds between status polls of Review tasks' pull requests (0 disables polling)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub poll_secs: Option<u64>,
+    /// Description template (see `DEFAULT_BODY_TEMPLATE` for placeholders)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub body_template: Option<String>,
+    /// Project-wide draft, labels, reviewers and assignees
+    #[serde(flatten)]
+    pub options: PrOptions,
+}
+
+impl PrConfig {
+    /// Load from config file
+    pub fn load(hive_dir: &Path) -> Result<Self> {
+        let config_path = hive_dir.join("config.json");
+        if config_path.exists() {
+            let content = std::fs::read_to_string(&config_path)
+                .context("Failed to read config.json")?;
+            let config: serde_json::Value = serde_json::from_str(&content)
+                .context("Failed to parse config.json")?;
+
+            // Load pr section if exists
+            if let Some(pr) = config.get("pr") {
+                return serde_json::from_value(pr.clone()).context("Failed to parse pr config");
+            }
+        }
+        Ok(Self::default())
+    }
+
+    /// Interval between status polls (None when disabled)
+    pub fn poll_interval(&self) -> Option<Duration> {
+        match self.poll_secs.unwrap_or(DEFAULT_POLL_SECS) {
+            0 => None,
+            secs => Some(Duration::from_secs(secs)),
+        }
+    }
+
+    /// Description template (configured or default)
+    pub fn body_template(&self) -> &str {
+        self.body_template.as_deref().unwrap_or(DEFAULT_BODY_TEMPLATE)
+    }
+
+    /// Create the configured provider
+    pub fn provider(&self, repo_root: &Path, hive_dir: &Path) -> Box<dyn PullRequestProvider> {
+        match self.provider {
+            Forge::Github => Box::new(GithubProvider::new(repo_root.to_path_buf())),
+            Forge::Gitlab => Box::new(GitlabProvider::new(repo_root.to_path_buf())),
+            Forge::Fake => Box::new(FakeProvider::new(
+                self.fake_file
+                    .clone()
+                    .unwrap_or_else(|| hive_dir.join(DEFAULT_FAKE_FILE)),
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_load_missing_config() {
+        let temp_dir = TempDir::new().unwrap();
+        let config = PrConfig::load(temp_dir.path()).unwrap();
+        assert_eq!(config.provider, Forge::Github);
+        assert_eq!(config.poll_interval(), Some(Duration::from_secs(DEFAULT_POLL_SECS)));
+        assert_eq!(config.provider(temp_dir.path(), temp_dir.path()).name(), "GitHub");
+    }
+
+    #[test]
+    fn test_load_provider() {
+        let temp_dir = TempDir::new().unwrap();
+        std::fs::write(
+            temp_dir.path().join("config.json"),
+            r#"{"git": {}, "pr": {"provider": "gitlab", "poll_secs": 0}}"#,
+        )
+        .unwrap();
+        let config = PrConfig::load(temp_dir.path()).unwrap();
+        assert_eq!(config.provider, Forge::Gitlab);
+        assert_eq!(config.poll_interval(), None);
+        assert_eq!(config.provider(temp_dir.path(), temp_dir.path()).name(), "GitLab");
+        assert_eq!(config.body_template(), DEFAULT_BODY_TEMPLATE);
+    }
+
+    #[test]
+    fn test_load_options() {
+        let temp_dir = TempDir::new().unwrap();
+        std::fs::write(
+            temp_dir.path().join("config.json"),
+            r#"{"pr": {"draft": true, "labels": ["hive"], "reviewers": ["alice"], "body_template": "{summary}"}}"#,
+        )
+        .unwrap();
+        let config = PrConfig::load(temp_dir.path()).unwrap();
+        assert!(config.options.draft());
+        assert_eq!(config.options.labels(), ["hive"]);
+        assert!(config.options.assignees().is_empty());
+        assert_eq!(config.body_template(), "{summary}");
+    }
+
+    #[test]
+    fn test_task_overrides_options() {
+        let project = PrOptions {
+            draft: Some(true),
+            labels: Some(vec!["hive".into()]),
+            reviewers: Some(vec!["alice".into()]),
+            assignees: None,
+        };
+        let task = PrOptions {
+            draft: Some(false),
+            reviewers: Some(Vec::new()),
+            assignees: Some(vec!["bob".into()]),
+            ..Default::default()
+        };
+        let options = project.overridden_by(&task);
+        assert!(!options.draft());
+        assert_eq!(options.labels(), ["hive"]);
+        assert!(options.reviewers().is_empty());
+        assert_eq!(options.assignees(), ["bob"]);
+        assert!(PrOptions::default().is_empty());
+        assert_eq!(project.overridden_by(&PrOptions::default()), project);
+    }
+
+    #[test]
+    fn test_fake_provider_file() {
+        let temp_dir = TempDir::new().unwrap();
+        let config = PrConfig {
+            provider: Forge::Fake,
+            ..Default::default()
+        };
+        let provider = config.provider(temp_dir.path(), temp_dir.path());
+        let url = provider
+            .create(&crate::pr::NewPullRequest {
+                title: "T".into(),
+                body: String::new(),
+                head: "hive/t".into(),
+                base: "main".into(),
+                draft: false,
+                assignees: Vec::new(),
+            })
+            .unwrap();
+        assert_eq!(url, "fake://pull/1");
+        assert!(temp_dir.path().join(DEFAULT_FAKE_FILE).exists());
+    }
+}
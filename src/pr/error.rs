@@ -0,0 +1,34 @@
+use thiserror::Error;
+
+/// Pull request provider error
+#[derive(Debug, Error)]
+pub enum PrError {
+    #[error("Failed to run {tool}: {source}")]
+    Spawn {
+        tool: String,
+        source: std::io::Error,
+    },
+    #[error("{tool} {command} failed: {stderr}")]
+    Command {
+        tool: String,
+        command: String,
+        stderr: String,
+    },
+    #[error("{tool} {command} timed out after {secs}s")]
+    Timeout {
+        tool: String,
+        command: String,
+        secs: u64,
+    },
+    #[error("Unexpected {tool} output: {message}")]
+    Parse { tool: String, message: String },
+    #[error("Pull request not found: {0}")]
+    NotFound(String),
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+    #[error(transparent)]
+    Json(#[from] serde_json::Error),
+}
+
+/// Result of pull request operations
+pub type Result<T> = std::result::Result<T, PrError>;
@@ -0,0 +1,213 @@
+use std::path::PathBuf;
+
+use serde::{Deserialize, Serialize};
+
+use super::error::{PrError, Result};
+use super::provider::{
+    Check, NewPullRequest, PrState, PrStatus, PullRequestProvider, ReviewDecision,
+};
+
+/// Pull request recorded by the fake provider
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FakePullRequest {
+    pub url: String,
+    pub title: String,
+    pub body: String,
+    pub head: String,
+    pub base: String,
+    pub state: PrState,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub review: Option<ReviewDecision>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub checks: Vec<Check>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub labels: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub reviewers: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub assignees: Vec<String>,
+}
+
+/// Forge stand-in that keeps pull requests in a JSON file (for tests and offline use).
+/// Edit the file to simulate reviews, checks or merges.
+pub struct FakeProvider {
+    path: PathBuf,
+}
+
+impl FakeProvider {
+    pub fn new(path: PathBuf) -> Self {
+        Self { path }
+    }
+
+    /// All recorded pull requests
+    pub fn load(&self) -> Result<Vec<FakePullRequest>> {
+        match std::fs::read_to_string(&self.path) {
+            Ok(content) => Ok(serde_json::from_str(&content)?),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Vec::new()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    fn save(&self, prs: &[FakePullRequest]) -> Result<()> {
+        if let Some(parent) = self.path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(&self.path, serde_json::to_string_pretty(prs)?)?;
+        Ok(())
+    }
+
+    /// Change a recorded pull request
+    pub fn update(&self, url: &str, change: impl FnOnce(&mut FakePullRequest)) -> Result<()> {
+        let mut prs = self.load()?;
+        let pr = prs
+            .iter_mut()
+            .find(|pr| pr.url == url)
+            .ok_or_else(|| PrError::NotFound(url.into()))?;
+        change(pr);
+        self.save(&prs)
+    }
+
+    fn get(&self, url: &str) -> Result<FakePullRequest> {
+        self.load()?
+            .into_iter()
+            .find(|pr| pr.url == url)
+            .ok_or_else(|| PrError::NotFound(url.into()))
+    }
+}
+
+impl PullRequestProvider for FakeProvider {
+    fn name(&self) -> &'static str {
+        "Fake"
+    }
+
+    fn create(&self, request: &NewPullRequest) -> Result<String> {
+        let mut prs = self.load()?;
+        let url = format!("fake://pull/{}", prs.len() + 1);
+        prs.push(FakePullRequest {
+            url: url.clone(),
+            title: request.title.clone(),
+            body: request.body.clone(),
+            head: request.head.clone(),
+            base: request.base.clone(),
+            state: if request.draft { PrState::Draft } else { PrState::Open },
+            review: None,
+            checks: Vec::new(),
+            labels: Vec::new(),
+            reviewers: Vec::new(),
+            assignees: request.assignees.clone(),
+        });
+        self.save(&prs)?;
+        Ok(url)
+    }
+
+    fn view(&self, url: &str) -> Result<PrStatus> {
+        let pr = self.get(url)?;
+        Ok(PrStatus {
+            state: pr.state,
+            review: pr.review,
+            base: pr.base,
+        })
+    }
+
+    fn checks(&self, url: &str) -> Result<Vec<Check>> {
+        Ok(self.get(url)?.checks)
+    }
+
+    fn add_labels(&self, url: &str, labels: &[String]) -> Result<()> {
+        self.update(url, |pr| {
+            for label in labels {
+                if !pr.labels.contains(label) {
+                    pr.labels.push(label.clone());
+                }
+            }
+        })
+    }
+
+    fn add_reviewers(&self, url: &str, reviewers: &[String]) -> Result<()> {
+        self.update(url, |pr| {
+            for reviewer in reviewers {
+                if !pr.reviewers.contains(reviewer) {
+                    pr.reviewers.push(reviewer.clone());
+                }
+            }
+        })
+    }
+
+    fn set_base(&self, url: &str, base: &str) -> Result<()> {
+        self.update(url, |pr| pr.base = base.into())
+    }
+
+    fn close(&self, url: &str) -> Result<()> {
+        self.update(url, |pr| pr.state = PrState::Closed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pr::provider::CheckState;
+    use tempfile::TempDir;
+
+    fn request(draft: bool) -> NewPullRequest {
+        NewPullRequest {
+            title: "Add login".into(),
+            body: "Body".into(),
+            head: "hive/task-1".into(),
+            base: "main".into(),
+            draft,
+            assignees: vec!["bob".into()],
+        }
+    }
+
+    #[test]
+    fn test_create_and_view() {
+        let temp_dir = TempDir::new().unwrap();
+        let provider = FakeProvider::new(temp_dir.path().join("prs.json"));
+
+        let url = provider.create(&request(false)).unwrap();
+        assert_eq!(url, "fake://pull/1");
+        assert_eq!(provider.create(&request(true)).unwrap(), "fake://pull/2");
+
+        let status = provider.view(&url).unwrap();
+        assert_eq!(
+            status,
+            PrStatus {
+                state: PrState::Open,
+                review: None,
+                base: "main".into()
+            }
+        );
+        assert_eq!(provider.view("fake://pull/2").unwrap().state, PrState::Draft);
+        assert!(matches!(provider.view("fake://pull/9"), Err(PrError::NotFound(_))));
+    }
+
+    #[test]
+    fn test_edit_and_close() {
+        let temp_dir = TempDir::new().unwrap();
+        let provider = FakeProvider::new(temp_dir.path().join("prs.json"));
+        let url = provider.create(&request(false)).unwrap();
+
+        provider.add_labels(&url, &["hive".into(), "hive".into()]).unwrap();
+        provider.add_reviewers(&url, &["alice".into()]).unwrap();
+        provider.set_base(&url, "develop").unwrap();
+        provider
+            .update(&url, |pr| {
+                pr.checks = vec![Check {
+                    name: "ci".into(),
+                    state: CheckState::Failed,
+                    url: None,
+                }]
+            })
+            .unwrap();
+
+        let pr = &provider.load().unwrap()[0];
+        assert_eq!(pr.labels, vec!["hive"]);
+        assert_eq!(pr.reviewers, vec!["alice"]);
+        assert_eq!(pr.assignees, vec!["bob"]);
+        assert_eq!(provider.view(&url).unwrap().base, "develop");
+        assert_eq!(provider.checks(&url).unwrap()[0].state, CheckState::Failed);
+
+        provider.close(&url).unwrap();
+        assert_eq!(provider.view(&url).unwrap().state, PrState::Closed);
+    }
+}
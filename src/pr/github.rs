@@ -0,0 +1,203 @@
+use std::path::PathBuf;
+
+use serde::Deserialize;
+
+use super::error::{PrError, Result};
+use super::provider::{
+    parse_json, run_checked, run_cli, Check, CheckState, NewPullRequest, PrState, PrStatus,
+    PullRequestProvider, ReviewDecision,
+};
+
+const GH: &str = "gh";
+
+/// GitHub through the `gh` CLI
+pub struct GithubProvider {
+    /// Directory `gh` runs in (selects the repository)
+    repo_root: PathBuf,
+}
+
+impl GithubProvider {
+    pub fn new(repo_root: PathBuf) -> Self {
+        Self { repo_root }
+    }
+
+    fn gh(&self, args: &[&str]) -> Result<String> {
+        run_checked(GH, args, &self.repo_root)
+    }
+}
+
+/// `gh pr view --json state,isDraft,reviewDecision,baseRefName`
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GhView {
+    state: String,
+    #[serde(default)]
+    is_draft: bool,
+    #[serde(default)]
+    review_decision: Option<String>,
+    base_ref_name: String,
+}
+
+fn parse_view(output: &str) -> Result<PrStatus> {
+    let view: GhView = parse_json(GH, output)?;
+    let state = match (view.state.as_str(), view.is_draft) {
+        ("MERGED", _) => PrState::Merged,
+        ("CLOSED", _) => PrState::Closed,
+        (_, true) => PrState::Draft,
+        _ => PrState::Open,
+    };
+    let review = match view.review_decision.as_deref() {
+        Some("APPROVED") => Some(ReviewDecision::Approved),
+        Some("CHANGES_REQUESTED") => Some(ReviewDecision::ChangesRequested),
+        Some("REVIEW_REQUIRED") => Some(ReviewDecision::ReviewRequired),
+        _ => None,
+    };
+    Ok(PrStatus {
+        state,
+        review,
+        base: view.base_ref_name,
+    })
+}
+
+/// `gh pr checks --json name,bucket,link`
+#[derive(Deserialize)]
+struct GhCheck {
+    name: String,
+    bucket: String,
+    #[serde(default)]
+    link: Option<String>,
+}
+
+fn parse_checks(output: &str) -> Result<Vec<Check>> {
+    let checks: Vec<GhCheck> = parse_json(GH, output)?;
+    Ok(checks
+        .into_iter()
+        .map(|check| Check {
+            state: match check.bucket.as_str() {
+                "pass" => CheckState::Passed,
+                "fail" | "cancel" => CheckState::Failed,
+                "skipping" => CheckState::Skipped,
+                _ => CheckState::Pending,
+            },
+            name: check.name,
+            url: check.link.filter(|link| !link.is_empty()),
+        })
+        .collect())
+}
+
+impl PullRequestProvider for GithubProvider {
+    fn name(&self) -> &'static str {
+        "GitHub"
+    }
+
+    fn create(&self, request: &NewPullRequest) -> Result<String> {
+        let mut args = vec![
+            "pr", "create", "--title", &request.title, "--body", &request.body,
+            "--head", &request.head, "--base", &request.base,
+        ];
+        if request.draft {
+            args.push("--draft");
+        }
+        let assignees = request.assignees.join(",");
+        if !assignees.is_empty() {
+            args.extend(["--assignee", &assignees]);
+        }
+        // gh prints progress before the URL
+        let output = self.gh(&args)?;
+        output
+            .lines()
+            .rev()
+            .find(|line| line.starts_with("http"))
+            .map(String::from)
+            .ok_or_else(|| PrError::Parse {
+                tool: GH.into(),
+                message: format!("no pull request URL in '{}'", output),
+            })
+    }
+
+    fn view(&self, url: &str) -> Result<PrStatus> {
+        parse_view(&self.gh(&["pr", "view", url, "--json", "state,isDraft,reviewDecision,baseRefName"])?)
+    }
+
+    fn checks(&self, url: &str) -> Result<Vec<Check>> {
+        // Exits non-zero while checks are pending or failing, and when there are none
+        let output = run_cli(GH, &["pr", "checks", url, "--json", "name,bucket,link"], &self.repo_root)?;
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        if stdout.trim().is_empty() {
+            let stderr = String::from_utf8_lossy(&output.stderr).trim().to_string();
+            if output.status.success() || stderr.contains("no checks reported") {
+                return Ok(Vec::new());
+            }
+            return Err(PrError::Command {
+                tool: GH.into(),
+                command: "pr checks".into(),
+                stderr,
+            });
+        }
+        parse_checks(&stdout)
+    }
+
+    fn add_labels(&self, url: &str, labels: &[String]) -> Result<()> {
+        if !labels.is_empty() {
+            self.gh(&["pr", "edit", url, "--add-label", &labels.join(",")])?;
+        }
+        Ok(())
+    }
+
+    fn add_reviewers(&self, url: &str, reviewers: &[String]) -> Result<()> {
+        if !reviewers.is_empty() {
+            self.gh(&["pr", "edit", url, "--add-reviewer", &reviewers.join(",")])?;
+        }
+        Ok(())
+    }
+
+    fn set_base(&self, url: &str, base: &str) -> Result<()> {
+        self.gh(&["pr", "edit", url, "--base", base]).map(|_| ())
+    }
+
+    fn close(&self, url: &str) -> Result<()> {
+        self.gh(&["pr", "close", url]).map(|_| ())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_view() {
+        let status = parse_view(
+            r#"{"state":"OPEN","isDraft":false,"reviewDecision":"CHANGES_REQUESTED","baseRefName":"main"}"#,
+        )
+        .unwrap();
+        assert_eq!(status.state, PrState::Open);
+        assert_eq!(status.review, Some(ReviewDecision::ChangesRequested));
+        assert_eq!(status.base, "main");
+
+        let draft = parse_view(r#"{"state":"OPEN","isDraft":true,"reviewDecision":"","baseRefName":"dev"}"#).unwrap();
+        assert_eq!(draft.state, PrState::Draft);
+        assert_eq!(draft.review, None);
+
+        let merged = parse_view(r#"{"state":"MERGED","isDraft":false,"baseRefName":"main"}"#).unwrap();
+        assert_eq!(merged.state, PrState::Merged);
+        assert!(parse_view("not json").is_err());
+    }
+
+    #[test]
+    fn test_parse_checks() {
+        let checks = parse_checks(
+            r#"[{"name":"test","bucket":"pass","link":"https://ci/1"},
+                {"name":"lint","bucket":"fail","link":""},
+                {"name":"deploy","bucket":"pending"},
+                {"name":"docs","bucket":"skipping"}]"#,
+        )
+        .unwrap();
+        let states: Vec<CheckState> = checks.iter().map(|c| c.state).collect();
+        assert_eq!(
+            states,
+            vec![CheckState::Passed, CheckState::Failed, CheckState::Pending, CheckState::Skipped]
+        );
+        assert_eq!(checks[0].url.as_deref(), Some("https://ci/1"));
+        assert_eq!(checks[1].url, None);
+    }
+}
@@ -0,0 +1,188 @@
+use std::path::PathBuf;
+
+use serde::Deserialize;
+
+use super::error::{PrError, Result};
+use super::provider::{
+    parse_json, run_checked, Check, CheckState, NewPullRequest, PrState, PrStatus,
+    PullRequestProvider, ReviewDecision,
+};
+
+const GLAB: &str = "glab";
+
+/// GitLab merge requests through the `glab` CLI
+pub struct GitlabProvider {
+    /// Directory `glab` runs in (selects the project)
+    repo_root: PathBuf,
+}
+
+impl GitlabProvider {
+    pub fn new(repo_root: PathBuf) -> Self {
+        Self { repo_root }
+    }
+
+    fn glab(&self, args: &[&str]) -> Result<String> {
+        run_checked(GLAB, args, &self.repo_root)
+    }
+
+    fn view_json(&self, url: &str) -> Result<GlabView> {
+        parse_json(GLAB, &self.glab(&["mr", "view", url, "--output", "json"])?)
+    }
+}
+
+/// `glab mr view --output json` (GitLab API merge request)
+#[derive(Deserialize)]
+struct GlabView {
+    state: String,
+    #[serde(default)]
+    draft: bool,
+    target_branch: String,
+    #[serde(default)]
+    detailed_merge_status: Option<String>,
+    #[serde(default)]
+    head_pipeline: Option<GlabPipeline>,
+}
+
+#[derive(Deserialize)]
+struct GlabPipeline {
+    status: String,
+    #[serde(default)]
+    web_url: Option<String>,
+}
+
+impl GlabView {
+    fn status(&self) -> PrStatus {
+        let state = match (self.state.as_str(), self.draft) {
+            ("merged", _) => PrState::Merged,
+            ("closed" | "locked", _) => PrState::Closed,
+            (_, true) => PrState::Draft,
+            _ => PrState::Open,
+        };
+        let review = match self.detailed_merge_status.as_deref() {
+            Some("requested_changes") => Some(ReviewDecision::ChangesRequested),
+            Some("not_approved") => Some(ReviewDecision::ReviewRequired),
+            Some("mergeable") => Some(ReviewDecision::Approved),
+            _ => None,
+        };
+        PrStatus {
+            state,
+            review,
+            base: self.target_branch.clone(),
+        }
+    }
+
+    /// The head pipeline is reported as a single check
+    fn checks(&self) -> Vec<Check> {
+        self.head_pipeline
+            .iter()
+            .map(|pipeline| Check {
+                name: "pipeline".into(),
+                state: match pipeline.status.as_str() {
+                    "success" => CheckState::Passed,
+                    "failed" | "canceled" => CheckState::Failed,
+                    "skipped" | "manual" => CheckState::Skipped,
+                    _ => CheckState::Pending,
+                },
+                url: pipeline.web_url.clone(),
+            })
+            .collect()
+    }
+}
+
+impl PullRequestProvider for GitlabProvider {
+    fn name(&self) -> &'static str {
+        "GitLab"
+    }
+
+    fn create(&self, request: &NewPullRequest) -> Result<String> {
+        let mut args = vec![
+            "mr", "create", "--title", &request.title, "--description", &request.body,
+            "--source-branch", &request.head, "--target-branch", &request.base, "--yes",
+        ];
+        if request.draft {
+            args.push("--draft");
+        }
+        let assignees = request.assignees.join(",");
+        if !assignees.is_empty() {
+            args.extend(["--assignee", &assignees]);
+        }
+        // glab prints the merge request URL after a summary line
+        let output = self.glab(&args)?;
+        output
+            .split_whitespace()
+            .rev()
+            .find(|word| word.starts_with("http"))
+            .map(String::from)
+            .ok_or_else(|| PrError::Parse {
+                tool: GLAB.into(),
+                message: format!("no merge request URL in '{}'", output),
+            })
+    }
+
+    fn view(&self, url: &str) -> Result<PrStatus> {
+        Ok(self.view_json(url)?.status())
+    }
+
+    fn checks(&self, url: &str) -> Result<Vec<Check>> {
+        Ok(self.view_json(url)?.checks())
+    }
+
+    fn add_labels(&self, url: &str, labels: &[String]) -> Result<()> {
+        if !labels.is_empty() {
+            self.glab(&["mr", "update", url, "--label", &labels.join(",")])?;
+        }
+        Ok(())
+    }
+
+    fn add_reviewers(&self, url: &str, reviewers: &[String]) -> Result<()> {
+        if !reviewers.is_empty() {
+            self.glab(&["mr", "update", url, "--reviewer", &reviewers.join(",")])?;
+        }
+        Ok(())
+    }
+
+    fn set_base(&self, url: &str, base: &str) -> Result<()> {
+        self.glab(&["mr", "update", url, "--target-branch", base]).map(|_| ())
+    }
+
+    fn close(&self, url: &str) -> Result<()> {
+        self.glab(&["mr", "close", url]).map(|_| ())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_view() {
+        let view: GlabView = parse_json(
+            GLAB,
+            r#"{"state":"opened","draft":false,"target_branch":"main",
+                "detailed_merge_status":"requested_changes",
+                "head_pipeline":{"status":"running","web_url":"https://gitlab/p/1"}}"#,
+        )
+        .unwrap();
+        let status = view.status();
+        assert_eq!(status.state, PrState::Open);
+        assert_eq!(status.review, Some(ReviewDecision::ChangesRequested));
+        assert_eq!(status.base, "main");
+        assert_eq!(
+            view.checks(),
+            vec![Check {
+                name: "pipeline".into(),
+                state: CheckState::Pending,
+                url: Some("https://gitlab/p/1".into()),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_parse_view_without_pipeline() {
+        let view: GlabView =
+            parse_json(GLAB, r#"{"state":"merged","target_branch":"dev"}"#).unwrap();
+        assert_eq!(view.status().state, PrState::Merged);
+        assert_eq!(view.status().review, None);
+        assert!(view.checks().is_empty());
+    }
+}
@@ -0,0 +1,13 @@
+mod body;
+mod config;
+mod error;
+mod fake;
+mod github;
+mod gitlab;
+mod provider;
+
+pub use body::PrBody;
+pub use config::{Forge, PrConfig, PrOptions};
+pub use provider::{
+    NewPullRequest, PrState, PrSummary, PullRequestProvider, ReviewDecision,
+};
@@ -0,0 +1,267 @@
+use std::path::Path;
+use std::process::{Command, Output};
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use super::error::{PrError, Result};
+use crate::process::output_with_timeout;
+
+/// Longest a forge CLI call may run before it is killed (e.g. a stalled network request)
+const CLI_TIMEOUT: Duration = Duration::from_secs(120);
+
+/// Pull request to open
+#[derive(Debug, Clone, PartialEq)]
+pub struct NewPullRequest {
+    pub title: String,
+    pub body: String,
+    /// Branch with the changes
+    pub head: String,
+    /// Branch the changes are merged into
+    pub base: String,
+    /// Open as draft
+    pub draft: bool,
+    /// Users assigned to the pull request
+    pub assignees: Vec<String>,
+}
+
+/// Lifecycle state of a pull request
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PrState {
+    Open,
+    Draft,
+    Merged,
+    Closed,
+}
+
+impl PrState {
+    pub fn display_name(&self) -> &'static str {
+        match self {
+            Self::Open => "open",
+            Self::Draft => "draft",
+            Self::Merged => "merged",
+            Self::Closed => "closed",
+        }
+    }
+}
+
+/// Review outcome of a pull request
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ReviewDecision {
+    Approved,
+    ChangesRequested,
+    ReviewRequired,
+}
+
+impl ReviewDecision {
+    pub fn display_name(&self) -> &'static str {
+        match self {
+            Self::Approved => "approved",
+            Self::ChangesRequested => "changes requested",
+            Self::ReviewRequired => "review required",
+        }
+    }
+}
+
+/// Current status of a pull request
+#[derive(Debug, Clone, PartialEq)]
+pub struct PrStatus {
+    pub state: PrState,
+    /// None when the forge reports no review requirement
+    pub review: Option<ReviewDecision>,
+    /// Branch the pull request targets
+    pub base: String,
+}
+
+/// Outcome of a CI check
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CheckState {
+    Pending,
+    Passed,
+    Failed,
+    Skipped,
+}
+
+impl CheckState {
+    pub fn icon(&self) -> &'static str {
+        match self {
+            Self::Pending => "⏳",
+            Self::Passed => "✓",
+            Self::Failed => "✗",
+            Self::Skipped => "–",
+        }
+    }
+
+    /// Combined state of several checks: any failure fails, then anything pending is pending
+    pub fn combine(checks: &[Check]) -> Option<Self> {
+        [Self::Failed, Self::Pending, Self::Passed, Self::Skipped]
+            .into_iter()
+            .find(|state| checks.iter().any(|check| check.state == *state))
+    }
+}
+
+/// CI check run on a pull request
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Check {
+    pub name: String,
+    pub state: CheckState,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+}
+
+/// Pull request status as last seen by hive (recorded on the task)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PrSummary {
+    pub state: PrState,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub review: Option<ReviewDecision>,
+    /// Combined CI check state (None without checks)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub checks: Option<CheckState>,
+    pub checked_at: DateTime<Utc>,
+}
+
+impl PrSummary {
+    pub fn new(status: &PrStatus, checks: &[Check]) -> Self {
+        Self {
+            state: status.state,
+            review: status.review,
+            checks: CheckState::combine(checks),
+            checked_at: Utc::now(),
+        }
+    }
+
+    /// Closed without merging, or changes were requested
+    pub fn needs_attention(&self) -> bool {
+        self.state == PrState::Closed
+            || (self.state != PrState::Merged && self.review == Some(ReviewDecision::ChangesRequested))
+    }
+
+    /// Short form for task cards, e.g. "PR open ✓ approved"
+    pub fn badge(&self) -> String {
+        let mut badge = format!("PR {}", self.state.display_name());
+        if let Some(checks) = self.checks {
+            badge.push(' ');
+            badge.push_str(checks.icon());
+        }
+        if let Some(review) = self.review {
+            badge.push(' ');
+            badge.push_str(review.display_name());
+        }
+        badge
+    }
+}
+
+/// Forge hosting pull requests. Pull requests are identified by their URL.
+pub trait PullRequestProvider: Send + Sync {
+    /// Display name (e.g. "GitHub")
+    fn name(&self) -> &'static str;
+    /// Open a pull request and return its URL
+    fn create(&self, request: &NewPullRequest) -> Result<String>;
+    /// Current state, review decision and base branch
+    fn view(&self, url: &str) -> Result<PrStatus>;
+    /// CI checks of the latest commit
+    fn checks(&self, url: &str) -> Result<Vec<Check>>;
+    /// Add labels (existing labels are kept)
+    fn add_labels(&self, url: &str, labels: &[String]) -> Result<()>;
+    /// Request reviews
+    fn add_reviewers(&self, url: &str, reviewers: &[String]) -> Result<()>;
+    /// Change the branch the pull request targets
+    fn set_base(&self, url: &str, base: &str) -> Result<()>;
+    /// Close without merging
+    #[allow(dead_code)]
+    fn close(&self, url: &str) -> Result<()>;
+}
+
+/// Run a forge CLI in `dir` and return its output (without checking the exit status).
+/// Calls that take longer than `CLI_TIMEOUT` are killed.
+pub(super) fn run_cli(tool: &str, args: &[&str], dir: &Path) -> Result<Output> {
+    output_with_timeout(Command::new(tool).args(args).current_dir(dir), CLI_TIMEOUT).map_err(
+        |source| match source.kind() {
+            std::io::ErrorKind::TimedOut => PrError::Timeout {
+                tool: tool.into(),
+                command: args.iter().take(2).copied().collect::<Vec<_>>().join(" "),
+                secs: CLI_TIMEOUT.as_secs(),
+            },
+            _ => PrError::Spawn {
+                tool: tool.into(),
+                source,
+            },
+        },
+    )
+}
+
+/// Run a forge CLI in `dir` and return its trimmed stdout, failing on a non-zero exit
+pub(super) fn run_checked(tool: &str, args: &[&str], dir: &Path) -> Result<String> {
+    let output = run_cli(tool, args, dir)?;
+    if !output.status.success() {
+        return Err(PrError::Command {
+            tool: tool.into(),
+            command: args.iter().take(2).copied().collect::<Vec<_>>().join(" "),
+            stderr: String::from_utf8_lossy(&output.stderr).trim().to_string(),
+        });
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+/// Parse CLI JSON output
+pub(super) fn parse_json<T: serde::de::DeserializeOwned>(tool: &str, output: &str) -> Result<T> {
+    serde_json::from_str(output).map_err(|e| PrError::Parse {
+        tool: tool.into(),
+        message: e.to_string(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn check(state: CheckState) -> Check {
+        Check {
+            name: "ci".into(),
+            state,
+            url: None,
+        }
+    }
+
+    fn summary(state: PrState, review: Option<ReviewDecision>, checks: &[Check]) -> PrSummary {
+        let status = PrStatus {
+            state,
+            review,
+            base: "main".into(),
+        };
+        PrSummary::new(&status, checks)
+    }
+
+    #[test]
+    fn test_combine_checks() {
+        use CheckState::*;
+        assert_eq!(CheckState::combine(&[]), None);
+        assert_eq!(CheckState::combine(&[check(Passed), check(Skipped)]), Some(Passed));
+        assert_eq!(CheckState::combine(&[check(Passed), check(Pending)]), Some(Pending));
+        assert_eq!(CheckState::combine(&[check(Pending), check(Failed)]), Some(Failed));
+        assert_eq!(CheckState::combine(&[check(Skipped)]), Some(Skipped));
+    }
+
+    #[test]
+    fn test_summary_badge_and_attention() {
+        let approved = summary(
+            PrState::Open,
+            Some(ReviewDecision::Approved),
+            &[check(CheckState::Passed)],
+        );
+        assert_eq!(approved.badge(), "PR open ✓ approved");
+        assert!(!approved.needs_attention());
+
+        let changes = summary(PrState::Draft, Some(ReviewDecision::ChangesRequested), &[]);
+        assert_eq!(changes.badge(), "PR draft changes requested");
+        assert!(changes.needs_attention());
+
+        assert!(summary(PrState::Closed, None, &[]).needs_attention());
+        assert!(!summary(PrState::Merged, Some(ReviewDecision::ChangesRequested), &[]).needs_attention());
+    }
+}
@@ -1,5 +1,10 @@
+#[allow(clippy::module_inception)]
 mod task;
+mod review;
+mod run;
 mod store;
 
+pub use review::ReviewComment;
+pub use run::{RunRecord, RunStatus};
 pub use task::{Task, TaskStatus};
 pub use store::TaskStore;
@@ -0,0 +1,99 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// Review comment attached to a diff line
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ReviewComment {
+    /// File path (new path for renames)
+    pub path: String,
+    /// Line number (old file for removed lines, new file otherwise)
+    pub line: u32,
+    /// Anchored on a removed line
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub removed: bool,
+    /// Content of the commented line
+    pub code: String,
+    /// Comment text
+    pub body: String,
+    /// Created timestamp
+    pub created_at: DateTime<Utc>,
+}
+
+impl ReviewComment {
+    /// Create a new comment
+    pub fn new(
+        path: impl Into<String>,
+        line: u32,
+        removed: bool,
+        code: impl Into<String>,
+        body: impl Into<String>,
+    ) -> Self {
+        Self {
+            path: path.into(),
+            line,
+            removed,
+            code: code.into(),
+            body: body.into(),
+            created_at: Utc::now(),
+        }
+    }
+
+    /// `path:line` anchor (removed lines are marked as such)
+    pub fn location(&self) -> String {
+        if self.removed {
+            format!("{}:{} (removed line)", self.path, self.line)
+        } else {
+            format!("{}:{}", self.path, self.line)
+        }
+    }
+
+    /// Check if the comment is anchored on the given line
+    pub fn is_on(&self, path: &str, line: u32, removed: bool) -> bool {
+        self.path == path && self.line == line && self.removed == removed
+    }
+
+    /// Comment as a prompt section for the executor
+    pub fn to_prompt(&self) -> String {
+        format!(
+            "### `{}`\n```\n{}\n```\n{}",
+            self.location(),
+            self.code,
+            self.body
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_location_and_anchor() {
+        let comment = ReviewComment::new("src/lib.rs", 12, false, "let x = 1;", "Rename x");
+        assert_eq!(comment.location(), "src/lib.rs:12");
+        assert!(comment.is_on("src/lib.rs", 12, false));
+        assert!(!comment.is_on("src/lib.rs", 12, true));
+        assert!(!comment.is_on("src/main.rs", 12, false));
+
+        let removed = ReviewComment::new("src/lib.rs", 3, true, "old()", "Keep this call");
+        assert_eq!(removed.location(), "src/lib.rs:3 (removed line)");
+    }
+
+    #[test]
+    fn test_to_prompt() {
+        let comment = ReviewComment::new("src/lib.rs", 12, false, "let x = 1;", "Rename x");
+        assert_eq!(
+            comment.to_prompt(),
+            "### `src/lib.rs:12`\n```\nlet x = 1;\n```\nRename x"
+        );
+    }
+
+    #[test]
+    fn test_serialization_skips_default_side() {
+        let comment = ReviewComment::new("a.rs", 1, false, "", "Fix");
+        let json = serde_json::to_string(&comment).unwrap();
+        assert!(!json.contains("removed"));
+        let parsed: ReviewComment = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed, comment);
+    }
+}
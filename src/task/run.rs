@@ -0,0 +1,182 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use super::TaskStatus;
+use crate::agent::AgentRole;
+
+/// Run status
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum RunStatus {
+    #[default]
+    Running,
+    Succeeded,
+    Failed,
+}
+
+impl RunStatus {
+    /// Get icon for the status
+    pub fn icon(&self) -> &'static str {
+        match self {
+            Self::Running => "⏳",
+            Self::Succeeded => "✅",
+            Self::Failed => "❌",
+        }
+    }
+}
+
+/// Provenance of a single planner/executor invocation
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RunRecord {
+    /// Unique run ID
+    pub id: String,
+    /// Planner or executor
+    pub role: AgentRole,
+    /// Agent name
+    pub agent: String,
+    /// Executed command
+    pub command: String,
+    /// Arguments (without the prompt)
+    pub args: Vec<String>,
+    /// Prompt passed as the last argument
+    pub prompt: String,
+    /// Working directory (worktree) of the run
+    pub working_dir: String,
+    /// HEAD commit of the worktree when the run started
+    pub base_commit: Option<String>,
+    /// Started timestamp
+    pub started_at: DateTime<Utc>,
+    /// Ended timestamp
+    pub ended_at: Option<DateTime<Utc>>,
+    /// Run status
+    #[serde(default)]
+    pub status: RunStatus,
+    /// Process exit code
+    pub exit_code: Option<i32>,
+    /// Failure reason
+    pub error: Option<String>,
+    /// Log file path
+    pub log_path: Option<String>,
+    /// Run this run reproduces
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rerun_of: Option<String>,
+    /// Follow-up instruction the run was started with
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub follow_up: Option<String>,
+    /// Branch of the run's own worktree (reruns; other runs use the task's branch)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub branch: Option<String>,
+    /// Status the task returns to if the run fails (revisions of a reviewed task)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub prior_status: Option<TaskStatus>,
+}
+
+impl RunRecord {
+    /// Create a new running record
+    pub fn new(
+        role: AgentRole,
+        agent: impl Into<String>,
+        command: impl Into<String>,
+        args: Vec<String>,
+        prompt: impl Into<String>,
+        working_dir: impl Into<String>,
+    ) -> Self {
+        let id = format!("run-{}", Uuid::new_v4().to_string().split('-').next().unwrap());
+        Self {
+            id,
+            role,
+            agent: agent.into(),
+            command: command.into(),
+            args,
+            prompt: prompt.into(),
+            working_dir: working_dir.into(),
+            base_commit: None,
+            started_at: Utc::now(),
+            ended_at: None,
+            status: RunStatus::Running,
+            exit_code: None,
+            error: None,
+            log_path: None,
+            rerun_of: None,
+            follow_up: None,
+            branch: None,
+            prior_status: None,
+        }
+    }
+
+    /// Mark run as finished
+    pub fn finish(&mut self, status: RunStatus, exit_code: Option<i32>, error: Option<String>) {
+        self.status = status;
+        self.exit_code = exit_code;
+        self.error = error;
+        self.ended_at = Some(Utc::now());
+    }
+
+    /// Short base commit (7 chars)
+    pub fn short_base_commit(&self) -> &str {
+        self.base_commit
+            .as_deref()
+            .map(|c| &c[..c.len().min(7)])
+            .unwrap_or("-")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn create_run() -> RunRecord {
+        RunRecord::new(
+            AgentRole::Executor,
+            "claude",
+            "claude",
+            vec!["-p".into()],
+            "Implement it",
+            "/tmp/worktree",
+        )
+    }
+
+    #[test]
+    fn test_run_record_creation() {
+        let run = create_run();
+        assert!(run.id.starts_with("run-"));
+        assert_eq!(run.status, RunStatus::Running);
+        assert!(run.ended_at.is_none());
+        assert_eq!(run.args, vec!["-p".to_string()]);
+        assert_eq!(run.prompt, "Implement it");
+    }
+
+    #[test]
+    fn test_run_record_finish() {
+        let mut run = create_run();
+        run.finish(RunStatus::Failed, Some(1), Some("Exit code: Some(1)".into()));
+
+        assert_eq!(run.status, RunStatus::Failed);
+        assert_eq!(run.exit_code, Some(1));
+        assert!(run.ended_at.is_some());
+    }
+
+    #[test]
+    fn test_short_base_commit() {
+        let mut run = create_run();
+        assert_eq!(run.short_base_commit(), "-");
+        run.base_commit = Some("0123456789abcdef".into());
+        assert_eq!(run.short_base_commit(), "0123456");
+    }
+
+    #[test]
+    fn test_run_record_json_round_trip() {
+        let mut run = create_run();
+        run.base_commit = Some("abc".into());
+        let json = serde_json::to_string(&run).unwrap();
+        assert!(json.contains("\"role\":\"executor\""));
+        assert!(json.contains("\"status\":\"running\""));
+
+        let deserialized: RunRecord = serde_json::from_str(&json).unwrap();
+        assert_eq!(deserialized.id, run.id);
+        assert_eq!(deserialized.base_commit, run.base_commit);
+        assert!(deserialized.rerun_of.is_none());
+        assert!(!json.contains("follow_up"));
+    }
+}
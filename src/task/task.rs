@@ -1,7 +1,15 @@
+use std::collections::BTreeMap;
+
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
+use super::review::ReviewComment;
+use super::run::{RunRecord, RunStatus};
+use crate::agent::{BudgetLimits, TokenUsage, UsageRecord};
+use crate::git::SecretFinding;
+use crate::pr::{PrOptions, PrSummary};
+
 /// Task status
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
 #[serde(rename_all = "snake_case")]
@@ -36,7 +44,7 @@ impl TaskStatus {
 
     /// Convert to column index
     /// Todo=0, Planning/PlanReview/InProgress=1, Review=2, Done=3
-    pub fn to_column_index(&self) -> Option<usize> {
+    pub fn to_column_index(self) -> Option<usize> {
         match self {
             Self::Todo => Some(0),
             Self::Planning | Self::PlanReview | Self::InProgress => Some(1),
@@ -79,8 +87,20 @@ pub struct Task {
     pub agent: Option<String>,
     /// Working branch name
     pub branch: Option<String>,
+    /// Branch the task is based on and merged into (falls back to the project default)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub base_branch: Option<String>,
+    /// Parent task this task is stacked on (the parent's branch is the base branch)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub parent: Option<String>,
+    /// Parent branch commit the task branch was last stacked on
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub parent_head: Option<String>,
     /// Worktree path
     pub worktree: Option<String>,
+    /// Failures while bootstrapping the worktree (copies, symlinks, setup commands)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub bootstrap_errors: Vec<String>,
     /// Created timestamp
     pub created_at: DateTime<Utc>,
     /// Started timestamp
@@ -91,6 +111,42 @@ pub struct Task {
     pub output_log: Option<String>,
     /// Pull request URL
     pub pr_url: Option<String>,
+    /// Pull request status from the last poll
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pr_status: Option<PrSummary>,
+    /// Pull request options overriding the project's (`pr` config section)
+    #[serde(default, skip_serializing_if = "PrOptions::is_empty")]
+    pub pr_options: PrOptions,
+    /// Token/cost usage per agent run
+    #[serde(default)]
+    pub usage: Vec<UsageRecord>,
+    /// Budget override for this task (falls back to the configured per-task budget)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub budget: Option<BudgetLimits>,
+    /// Reason of the last agent failure
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_error: Option<String>,
+    /// Planner/executor run history
+    #[serde(default)]
+    pub runs: Vec<RunRecord>,
+    /// Files conflicting with the base branch (found by the last sync)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub conflicts: Vec<String>,
+    /// Possible secrets found by the last scan that are not waived (block PRs and merges)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub secret_findings: Vec<SecretFinding>,
+    /// Fingerprints of secret findings waived by the user
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub secret_waivers: Vec<String>,
+    /// Policy violations found by the last check that block merging
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub policy_errors: Vec<String>,
+    /// Policy violations found by the last check that only warn
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub policy_warnings: Vec<String>,
+    /// Pending review comments (sent to the executor when changes are requested)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub review_comments: Vec<ReviewComment>,
 }
 
 impl Task {
@@ -106,12 +162,28 @@ impl Task {
             executor: None,
             agent: None,
             branch: None,
+            base_branch: None,
+            parent: None,
+            parent_head: None,
             worktree: None,
+            bootstrap_errors: Vec::new(),
             created_at: Utc::now(),
             started_at: None,
             completed_at: None,
             output_log: None,
             pr_url: None,
+            pr_status: None,
+            pr_options: PrOptions::default(),
+            usage: Vec::new(),
+            budget: None,
+            last_error: None,
+            runs: Vec::new(),
+            conflicts: Vec::new(),
+            secret_findings: Vec::new(),
+            secret_waivers: Vec::new(),
+            policy_errors: Vec::new(),
+            policy_warnings: Vec::new(),
+            review_comments: Vec::new(),
         }
     }
 
@@ -119,10 +191,8 @@ impl Task {
     pub fn set_status(&mut self, status: TaskStatus) {
         self.status = status;
         match status {
-            TaskStatus::Planning | TaskStatus::InProgress => {
-                if self.started_at.is_none() {
-                    self.started_at = Some(Utc::now());
-                }
+            TaskStatus::Planning | TaskStatus::InProgress if self.started_at.is_none() => {
+                self.started_at = Some(Utc::now());
             }
             TaskStatus::Done | TaskStatus::Cancelled => {
                 self.completed_at = Some(Utc::now());
@@ -144,6 +214,170 @@ impl Task {
         self.branch = Some(branch.into());
     }
 
+    /// Stack this task on `parent`: branch from and target the parent's branch
+    pub fn stack_on(&mut self, parent: &Task) -> Result<(), &'static str> {
+        if matches!(parent.status, TaskStatus::Done | TaskStatus::Cancelled) {
+            return Err("Cannot stack on a finished task");
+        }
+        let branch = parent.branch.clone().ok_or("Parent task has no branch yet")?;
+        self.parent = Some(parent.id.clone());
+        self.base_branch = Some(branch);
+        Ok(())
+    }
+
+    /// Unstack onto `base_branch` (after the parent was merged)
+    pub fn retarget(&mut self, base_branch: impl Into<String>) {
+        self.base_branch = Some(base_branch.into());
+        self.parent = None;
+        self.parent_head = None;
+    }
+
+    /// Record usage of a finished agent run
+    #[allow(dead_code)]
+    pub fn record_usage(&mut self, agent: impl Into<String>, usage: TokenUsage, duration_secs: u64) {
+        self.usage.push(UsageRecord::new(agent, usage, duration_secs));
+    }
+
+    /// Record a started run
+    pub fn start_run(&mut self, run: RunRecord) {
+        self.output_log = run.log_path.clone();
+        self.runs.push(run);
+    }
+
+    /// Finish a run and record its usage (returned for the project-wide ledger)
+    pub fn finish_run(
+        &mut self,
+        run_id: &str,
+        status: RunStatus,
+        exit_code: Option<i32>,
+        error: Option<String>,
+        usage: TokenUsage,
+        duration_secs: u64,
+    ) -> Option<UsageRecord> {
+        let run = self.runs.iter_mut().find(|r| r.id == run_id)?;
+        run.finish(status, exit_code, error);
+        let mut record = UsageRecord::new(run.agent.clone(), usage, duration_secs);
+        record.run_id = Some(run_id.to_string());
+        self.usage.push(record.clone());
+        Some(record)
+    }
+
+    /// Why a recorded run of this task may not be rerun, if it may not
+    pub fn rerun_blocked(&self) -> Option<&'static str> {
+        match self.status {
+            TaskStatus::Done | TaskStatus::Cancelled => Some("Finished tasks cannot be rerun"),
+            TaskStatus::Review if self.pr_url.is_some() => {
+                Some("Task has an open PR; request changes or add a follow-up instead")
+            }
+            _ => None,
+        }
+    }
+
+    /// Whether the latest run reproduces an earlier one (in its own worktree)
+    pub fn last_run_is_rerun(&self) -> bool {
+        self.runs.last().is_some_and(|r| r.rerun_of.is_some())
+    }
+
+    /// Move the task back after its agent failed: a revision returns to the status it
+    /// started from, keeping the executor; a first run steps back and clears its agent.
+    /// Returns the new status and the cleared assignment, if any.
+    pub fn revert_failed_run(&mut self) -> (TaskStatus, Option<&'static str>) {
+        // Reruns work in their own worktree and leave the task as it is
+        if self.last_run_is_rerun() {
+            return (self.status, None);
+        }
+        if let Some(status) = self.runs.last().and_then(|r| r.prior_status) {
+            self.set_status(status);
+            return (status, None);
+        }
+        let (status, cleared) = match self.status {
+            TaskStatus::Planning => {
+                self.planner = None;
+                (TaskStatus::Todo, Some("planner"))
+            }
+            TaskStatus::InProgress => {
+                self.executor = None;
+                (TaskStatus::PlanReview, Some("executor"))
+            }
+            status => (status, None),
+        };
+        self.set_status(status);
+        (status, cleared)
+    }
+
+    /// Check if the task branch conflicts with its base
+    pub fn needs_conflict_resolution(&self) -> bool {
+        !self.conflicts.is_empty()
+    }
+
+    /// Keep the findings of a secret scan that were not waived
+    pub fn record_secret_findings(&mut self, findings: Vec<SecretFinding>) {
+        self.secret_findings = findings
+            .into_iter()
+            .filter(|f| !self.secret_waivers.contains(&f.fingerprint))
+            .collect();
+    }
+
+    /// Waive all current secret findings. Returns how many were waived.
+    pub fn waive_secret_findings(&mut self) -> usize {
+        let findings = std::mem::take(&mut self.secret_findings);
+        self.secret_waivers
+            .extend(findings.iter().map(|f| f.fingerprint.clone()));
+        findings.len()
+    }
+
+    /// Add a review comment
+    pub fn add_review_comment(&mut self, comment: ReviewComment) {
+        self.review_comments.push(comment);
+    }
+
+    /// Remove review comments anchored on a line, returning how many were removed
+    pub fn remove_review_comments(&mut self, path: &str, line: u32, removed: bool) -> usize {
+        let before = self.review_comments.len();
+        self.review_comments.retain(|c| !c.is_on(path, line, removed));
+        before - self.review_comments.len()
+    }
+
+    /// Get run by ID
+    pub fn get_run(&self, run_id: &str) -> Option<&RunRecord> {
+        self.runs.iter().find(|r| r.id == run_id)
+    }
+
+    /// Follow-up instructions given to the task's executor, oldest first
+    pub fn follow_ups(&self) -> Vec<&str> {
+        self.runs.iter().filter_map(|r| r.follow_up.as_deref()).collect()
+    }
+
+    /// Check if any run is still running
+    pub fn has_running_run(&self) -> bool {
+        self.runs.iter().any(|r| r.status == RunStatus::Running)
+    }
+
+    /// Mark runs left running by a previous session (quit or crash) as failed.
+    /// Returns whether any run was interrupted.
+    pub fn interrupt_stale_runs(&mut self) -> bool {
+        let mut interrupted = false;
+        for run in self.runs.iter_mut().filter(|r| r.status == RunStatus::Running) {
+            run.finish(
+                RunStatus::Failed,
+                None,
+                Some("Interrupted: hive exited before the run finished".into()),
+            );
+            interrupted = true;
+        }
+        interrupted
+    }
+
+    /// Total usage across all runs
+    pub fn total_usage(&self) -> TokenUsage {
+        crate::agent::total_usage(&self.usage)
+    }
+
+    /// Usage grouped by agent
+    pub fn usage_by_agent(&self) -> BTreeMap<String, TokenUsage> {
+        crate::agent::usage_by_agent(&self.usage)
+    }
+
     /// Assign an agent (backward compatibility)
     #[allow(dead_code)]
     pub fn assign_agent(&mut self, agent: impl Into<String>, branch: impl Into<String>) {
@@ -477,6 +711,269 @@ mod tests {
         assert_eq!(deserialized.branch, task.branch);
     }
 
+    #[test]
+    fn test_task_deserialize_without_usage() {
+        // Tasks saved before usage accounting have no "usage" field
+        let mut value = serde_json::to_value(Task::new("Old", "")).unwrap();
+        value.as_object_mut().unwrap().remove("usage");
+
+        let task: Task = serde_json::from_value(value).unwrap();
+        assert!(task.usage.is_empty());
+    }
+
+    // ========================================
+    // Usage Tests
+    // ========================================
+
+    #[test]
+    fn test_record_usage_aggregates_per_task_and_agent() {
+        let mut task = Task::new("Test", "");
+        let usage = TokenUsage {
+            input_tokens: 100,
+            output_tokens: 20,
+            cost_usd: 0.1,
+            ..Default::default()
+        };
+        task.record_usage("gemini", usage, 60);
+        task.record_usage("claude", usage, 120);
+        task.record_usage("claude", usage, 30);
+
+        assert_eq!(task.usage.len(), 3);
+        assert_eq!(task.total_usage().total_tokens(), 360);
+
+        let by_agent = task.usage_by_agent();
+        assert_eq!(by_agent["claude"].input_tokens, 200);
+        assert_eq!(by_agent["gemini"].output_tokens, 20);
+    }
+
+    // ========================================
+    // Run History Tests
+    // ========================================
+
+    #[test]
+    fn test_start_and_finish_run() {
+        use crate::agent::AgentRole;
+
+        let mut task = Task::new("Test", "");
+        let mut run = RunRecord::new(AgentRole::Planner, "gemini", "gemini", vec!["-y".into()], "plan", "/wt");
+        run.log_path = Some("/logs/run.log".into());
+        let run_id = run.id.clone();
+
+        task.start_run(run);
+        assert!(task.has_running_run());
+        assert_eq!(task.output_log, Some("/logs/run.log".into()));
+
+        let usage = TokenUsage {
+            input_tokens: 10,
+            ..Default::default()
+        };
+        task.finish_run(&run_id, RunStatus::Succeeded, Some(0), None, usage, 42);
+
+        assert!(!task.has_running_run());
+        let run = task.get_run(&run_id).unwrap();
+        assert_eq!(run.status, RunStatus::Succeeded);
+        assert_eq!(run.exit_code, Some(0));
+        assert_eq!(task.usage.len(), 1);
+        assert_eq!(task.usage[0].run_id.as_deref(), Some(run_id.as_str()));
+        assert_eq!(task.usage[0].agent, "gemini");
+        assert_eq!(task.usage[0].duration_secs, 42);
+    }
+
+    #[test]
+    fn test_follow_ups_in_run_order() {
+        use crate::agent::AgentRole;
+
+        let mut task = Task::new("Test", "");
+        let run = |follow_up: Option<&str>| {
+            let mut run = RunRecord::new(AgentRole::Executor, "claude", "claude", vec![], "p", "/wt");
+            run.follow_up = follow_up.map(String::from);
+            run
+        };
+        task.start_run(run(None));
+        task.start_run(run(Some("Add a test for the empty case")));
+        task.start_run(run(Some("Update the README")));
+
+        assert_eq!(
+            task.follow_ups(),
+            vec!["Add a test for the empty case", "Update the README"]
+        );
+    }
+
+    #[test]
+    fn test_interrupt_stale_runs() {
+        use crate::agent::AgentRole;
+
+        let mut task = Task::new("Test", "");
+        task.start_run(RunRecord::new(AgentRole::Executor, "claude", "claude", vec![], "p", "/wt"));
+        assert!(task.interrupt_stale_runs());
+
+        assert!(!task.has_running_run());
+        assert_eq!(task.runs[0].status, RunStatus::Failed);
+        assert!(task.runs[0].error.as_deref().unwrap().starts_with("Interrupted"));
+        assert!(!task.interrupt_stale_runs());
+    }
+
+    #[test]
+    fn test_revert_failed_run() {
+        use crate::agent::AgentRole;
+
+        // A first execution steps back to PlanReview without its executor
+        let mut task = Task::new("Test", "");
+        task.assign_executor("claude", "hive/test");
+        task.set_status(TaskStatus::InProgress);
+        task.start_run(RunRecord::new(AgentRole::Executor, "claude", "claude", vec![], "p", "/wt"));
+        assert_eq!(task.revert_failed_run(), (TaskStatus::PlanReview, Some("executor")));
+        assert_eq!(task.executor, None);
+
+        // A revision of a reviewed task returns to Review with its executor
+        let mut task = Task::new("Test", "");
+        task.assign_executor("claude", "hive/test");
+        task.pr_url = Some("https://example.com/pr/1".into());
+        task.set_status(TaskStatus::InProgress);
+        let mut run = RunRecord::new(AgentRole::Executor, "claude", "claude", vec![], "changes", "/wt");
+        run.prior_status = Some(TaskStatus::Review);
+        task.start_run(run);
+        assert_eq!(task.revert_failed_run(), (TaskStatus::Review, None));
+        assert_eq!(task.executor.as_deref(), Some("claude"));
+        assert!(task.pr_url.is_some());
+    }
+
+    #[test]
+    fn test_failed_follow_up_returns_to_review() {
+        use crate::agent::AgentRole;
+
+        let mut task = Task::new("Test", "");
+        task.assign_executor("claude", "hive/test");
+        task.set_status(TaskStatus::InProgress);
+        task.start_run(RunRecord::new(AgentRole::Executor, "claude", "claude", vec![], "plan", "/wt"));
+        let first = task.runs[0].id.clone();
+        task.finish_run(&first, RunStatus::Succeeded, Some(0), None, TokenUsage::default(), 1);
+        task.set_status(TaskStatus::Review);
+
+        // Follow-up moves the task to InProgress and fails
+        let mut run = RunRecord::new(AgentRole::Executor, "claude", "claude", vec![], "follow", "/wt");
+        run.follow_up = Some("Handle the empty list".into());
+        run.prior_status = Some(task.status);
+        let run_id = run.id.clone();
+        task.set_status(TaskStatus::InProgress);
+        task.start_run(run);
+        task.finish_run(&run_id, RunStatus::Failed, Some(1), Some("boom".into()), TokenUsage::default(), 1);
+
+        assert_eq!(task.revert_failed_run(), (TaskStatus::Review, None));
+        assert_eq!(task.status, TaskStatus::Review);
+        assert_eq!(task.executor.as_deref(), Some("claude"));
+        assert_eq!(task.follow_ups(), vec!["Handle the empty list"]);
+    }
+
+    #[test]
+    fn test_reruns_leave_the_task_alone() {
+        use crate::agent::AgentRole;
+
+        let mut task = Task::new("Test", "");
+        task.set_status(TaskStatus::Review);
+        assert_eq!(task.rerun_blocked(), None);
+        task.pr_url = Some("https://example.com/pr/1".into());
+        assert!(task.rerun_blocked().is_some());
+        task.set_status(TaskStatus::Done);
+        assert!(task.rerun_blocked().is_some());
+
+        let mut task = Task::new("Test", "");
+        task.assign_executor("claude", "hive/test");
+        task.set_status(TaskStatus::PlanReview);
+        let mut run = RunRecord::new(AgentRole::Executor, "claude", "claude", vec![], "p", "/wt-rerun");
+        run.rerun_of = Some("run-1".into());
+        task.start_run(run);
+        assert!(task.last_run_is_rerun());
+        assert_eq!(task.revert_failed_run(), (TaskStatus::PlanReview, None));
+        assert_eq!(task.executor.as_deref(), Some("claude"));
+    }
+
+    #[test]
+    fn test_finish_unknown_run_is_ignored() {
+        let mut task = Task::new("Test", "");
+        let record = task.finish_run("run-missing", RunStatus::Failed, None, None, TokenUsage::default(), 0);
+        assert!(record.is_none());
+        assert!(task.usage.is_empty());
+    }
+
+    // ========================================
+    // Stacking Tests
+    // ========================================
+
+    #[test]
+    fn test_stack_on_and_retarget() {
+        let mut parent = Task::new("Parent", "");
+        let mut child = Task::new("Child", "");
+        assert_eq!(child.stack_on(&parent), Err("Parent task has no branch yet"));
+
+        parent.branch = Some("hive/parent".into());
+        child.stack_on(&parent).unwrap();
+        assert_eq!(child.parent.as_deref(), Some(parent.id.as_str()));
+        assert_eq!(child.base_branch.as_deref(), Some("hive/parent"));
+
+        child.parent_head = Some("abc123".into());
+        child.retarget("main");
+        assert_eq!(child.base_branch.as_deref(), Some("main"));
+        assert!(child.parent.is_none());
+        assert!(child.parent_head.is_none());
+
+        parent.set_status(TaskStatus::Done);
+        assert!(Task::new("Other", "").stack_on(&parent).is_err());
+    }
+
+    // ========================================
+    // Review Comment Tests
+    // ========================================
+
+    #[test]
+    fn test_add_and_remove_review_comments() {
+        let mut task = Task::new("Test", "");
+        task.add_review_comment(ReviewComment::new("a.rs", 3, false, "x", "First"));
+        task.add_review_comment(ReviewComment::new("a.rs", 3, false, "x", "Second"));
+        task.add_review_comment(ReviewComment::new("a.rs", 3, true, "y", "Removed side"));
+        assert_eq!(task.review_comments.len(), 3);
+
+        assert_eq!(task.remove_review_comments("a.rs", 3, false), 2);
+        assert_eq!(task.review_comments.len(), 1);
+        assert!(task.review_comments[0].removed);
+        assert_eq!(task.remove_review_comments("b.rs", 3, true), 0);
+    }
+
+    #[test]
+    fn test_review_comments_skipped_when_empty() {
+        let task = Task::new("Test", "");
+        let json = serde_json::to_string(&task).unwrap();
+        assert!(!json.contains("review_comments"));
+    }
+
+    // ========================================
+    // Secret Finding Tests
+    // ========================================
+
+    fn finding(fingerprint: &str) -> SecretFinding {
+        SecretFinding {
+            path: ".env".into(),
+            line: None,
+            kind: "dotenv file".into(),
+            preview: ".env".into(),
+            fingerprint: fingerprint.into(),
+        }
+    }
+
+    #[test]
+    fn test_waived_secret_findings_are_dropped() {
+        let mut task = Task::new("Test", "");
+        task.record_secret_findings(vec![finding("a"), finding("b")]);
+        assert_eq!(task.secret_findings.len(), 2);
+
+        assert_eq!(task.waive_secret_findings(), 2);
+        assert!(task.secret_findings.is_empty());
+        assert_eq!(task.secret_waivers, vec!["a", "b"]);
+
+        task.record_secret_findings(vec![finding("a"), finding("c")]);
+        assert_eq!(task.secret_findings, vec![finding("c")]);
+    }
+
     // ========================================
     // Timestamp Tests
     // ========================================
@@ -505,6 +1002,20 @@ mod tests {
         assert_eq!(task.started_at, first_started);
     }
 
+    #[test]
+    fn test_conflicts_round_trip() {
+        let mut task = Task::new("Test", "");
+        assert!(!task.needs_conflict_resolution());
+        let json = serde_json::to_string(&task).unwrap();
+        assert!(!json.contains("conflicts"));
+
+        task.conflicts = vec!["src/lib.rs".into()];
+        let json = serde_json::to_string(&task).unwrap();
+        let deserialized: Task = serde_json::from_str(&json).unwrap();
+        assert!(deserialized.needs_conflict_resolution());
+        assert_eq!(deserialized.conflicts, vec!["src/lib.rs".to_string()]);
+    }
+
     // ========================================
     // Edge Cases
     // ========================================